@@ -0,0 +1,571 @@
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tracing::{info, warn};
+
+use super::TokenCache;
+use crate::config::LarkConfig;
+use crate::error::{ErrorKind, LarkError, Result};
+use crate::http::{ApiRequest, Transport};
+
+#[derive(Debug, Serialize)]
+struct AppAccessTokenRequest<'a> {
+    app_id: &'a str,
+    app_secret: &'a str,
+    app_ticket: &'a str,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AppAccessTokenResponse {
+    pub code: i32,
+    pub msg: String,
+    #[serde(default)]
+    pub app_access_token: String,
+    #[serde(default)]
+    pub expire: i32,
+}
+
+#[derive(Debug, Serialize)]
+struct StoreTenantAccessTokenRequest<'a> {
+    app_access_token: &'a str,
+    tenant_key: &'a str,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct StoreTenantAccessTokenResponse {
+    pub code: i32,
+    pub msg: String,
+    #[serde(default)]
+    pub tenant_access_token: String,
+    #[serde(default)]
+    pub expire: i32,
+}
+
+#[derive(Debug, Serialize)]
+struct AppTicketResendRequest<'a> {
+    app_id: &'a str,
+    app_secret: &'a str,
+}
+
+/// Token manager for marketplace (ISV) apps
+///
+/// Lark pushes an `app_ticket` event to the app every hour. The ticket is exchanged for
+/// an `app_access_token`, which in turn yields one tenant access token per `tenant_key`
+/// of the tenants that installed the app. Both levels are cached.
+///
+/// See: https://open.feishu.cn/document/server-docs/authentication-management/access-token/app_access_token
+pub struct MarketplaceTokenManager {
+    transport: Arc<Transport>,
+    app_ticket: Mutex<Option<String>>,
+    app_token: Mutex<Option<TokenCache>>,
+    /// Tenant tokens with the app access token they were obtained with
+    tenant_tokens: Mutex<HashMap<String, (TokenCache, String)>>,
+    /// Serializes app access token fetches
+    app_lock: tokio::sync::Mutex<()>,
+    /// Serializes the tenant token fetches of each tenant, so that only one request per
+    /// tenant reaches the auth endpoints
+    tenant_locks: Mutex<HashMap<String, Arc<tokio::sync::Mutex<()>>>>,
+    /// Token refresh buffer time in seconds (default: 60 seconds)
+    refresh_buffer: u64,
+}
+
+impl MarketplaceTokenManager {
+    /// Create a manager that uses the given configuration and HTTP client
+    pub fn with_config(config: Arc<LarkConfig>, client: Client) -> Self {
+        Self::with_transport(Arc::new(Transport::new(config, client)))
+    }
+
+    pub(crate) fn with_transport(transport: Arc<Transport>) -> Self {
+        Self {
+            transport,
+            app_ticket: Mutex::new(None),
+            app_token: Mutex::new(None),
+            tenant_tokens: Mutex::new(HashMap::new()),
+            app_lock: tokio::sync::Mutex::new(()),
+            tenant_locks: Mutex::new(HashMap::new()),
+            refresh_buffer: 60,
+        }
+    }
+
+    pub fn with_refresh_buffer(mut self, seconds: u64) -> Self {
+        self.refresh_buffer = seconds;
+        self
+    }
+
+    /// Store the latest `app_ticket` pushed by Lark
+    pub fn set_app_ticket(&self, app_ticket: impl Into<String>) {
+        *self.app_ticket.lock().unwrap() = Some(app_ticket.into());
+    }
+
+    pub fn app_ticket(&self) -> Option<String> {
+        self.app_ticket.lock().unwrap().clone()
+    }
+
+    /// Event hook: feed every (decrypted) event callback body to this method.
+    ///
+    /// Returns `true` if the event was an `app_ticket` event for this app and the ticket
+    /// was stored. Both the v1 (`event.type`) and v2 (`header.event_type`) schemas are
+    /// accepted.
+    pub fn handle_event(&self, payload: &Value) -> bool {
+        let event = &payload["event"];
+        let event_type = payload["header"]["event_type"]
+            .as_str()
+            .or_else(|| event["type"].as_str());
+        if event_type != Some("app_ticket") {
+            return false;
+        }
+
+        let app_id = event["app_id"]
+            .as_str()
+            .or_else(|| payload["header"]["app_id"].as_str());
+        if app_id.is_some_and(|id| id != self.transport.config().app_id) {
+            warn!(?app_id, "Ignoring app_ticket for another app");
+            return false;
+        }
+
+        match event["app_ticket"].as_str() {
+            Some(ticket) => {
+                info!("Received app_ticket");
+                self.set_app_ticket(ticket);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Ask Lark to push the `app_ticket` event again, e.g. after a restart
+    pub async fn resend_app_ticket(&self) -> Result<()> {
+        let config = self.transport.config();
+        let request = ApiRequest::post("/open-apis/auth/v3/app_ticket/resend").json(
+            &AppTicketResendRequest {
+                app_id: &config.app_id,
+                app_secret: &config.app_secret,
+            },
+        )?;
+        let response = self.execute(&request).await?;
+        let body = response.json::<Value>()?;
+        match body["code"].as_i64().unwrap_or(-1) {
+            0 => Ok(()),
+            code => Err(LarkError::api(
+                code as i32,
+                body["msg"].as_str().unwrap_or_default(),
+                response.status,
+                response.log_id,
+            )),
+        }
+    }
+
+    pub async fn get_app_access_token(&self) -> Result<String> {
+        if let Some(token) = valid(&self.app_token.lock().unwrap()) {
+            return Ok(token);
+        }
+
+        let _guard = self.app_lock.lock().await;
+        self.app_access_token_locked().await
+    }
+
+    /// Get the tenant access token of the tenant identified by `tenant_key`
+    pub async fn get_tenant_token(&self, tenant_key: &str) -> Result<String> {
+        if let Some(token) = self.cached_tenant_token(tenant_key) {
+            return Ok(token);
+        }
+
+        let lock = self.tenant_lock(tenant_key);
+        let _guard = lock.lock().await;
+        if let Some(token) = self.cached_tenant_token(tenant_key) {
+            return Ok(token);
+        }
+        self.fetch_tenant_token(tenant_key).await
+    }
+
+    /// Replace `rejected` with a fresh token unless a concurrent caller already did. The
+    /// app access token it was obtained with is fetched again too, as Lark also rejects
+    /// tenant tokens when that one was revoked.
+    pub(crate) async fn refresh_rejected(
+        &self,
+        tenant_key: &str,
+        rejected: &str,
+    ) -> Result<String> {
+        let lock = self.tenant_lock(tenant_key);
+        let _guard = lock.lock().await;
+        if let Some(token) = self.cached_tenant_token(tenant_key)
+            && token != rejected
+        {
+            return Ok(token);
+        }
+        let removed = self.tenant_tokens.lock().unwrap().remove(tenant_key);
+        if let Some((cached, app_token)) = removed
+            && cached.token == rejected
+        {
+            self.invalidate_app_token(&app_token);
+        }
+        self.fetch_tenant_token(tenant_key).await
+    }
+
+    fn cached_tenant_token(&self, tenant_key: &str) -> Option<String> {
+        let cached = self.tenant_tokens.lock().unwrap().get(tenant_key).cloned();
+        valid(&cached.map(|(cache, _)| cache))
+    }
+
+    fn tenant_lock(&self, tenant_key: &str) -> Arc<tokio::sync::Mutex<()>> {
+        self.tenant_locks
+            .lock()
+            .unwrap()
+            .entry(tenant_key.to_string())
+            .or_default()
+            .clone()
+    }
+
+    /// Drop the cached app access token if it is still `token`
+    fn invalidate_app_token(&self, token: &str) {
+        let mut cached = self.app_token.lock().unwrap();
+        if cached.as_ref().is_some_and(|cached| cached.token == token) {
+            *cached = None;
+        }
+    }
+
+    /// Callers must hold `app_lock`
+    async fn app_access_token_locked(&self) -> Result<String> {
+        if let Some(token) = valid(&self.app_token.lock().unwrap()) {
+            return Ok(token);
+        }
+
+        let Some(app_ticket) = self.app_ticket() else {
+            // The ticket arrives asynchronously through the event callback
+            self.resend_app_ticket().await?;
+            return Err(LarkError::Unauthorized(
+                "app_ticket not received yet, a resend has been requested".to_string(),
+            ));
+        };
+
+        let config = self.transport.config();
+        let request = ApiRequest::post("/open-apis/auth/v3/app_access_token").json(
+            &AppAccessTokenRequest {
+                app_id: &config.app_id,
+                app_secret: &config.app_secret,
+                app_ticket: &app_ticket,
+            },
+        )?;
+        let response = self.execute(&request).await?;
+        let token_response = response.json::<AppAccessTokenResponse>()?;
+        if token_response.code != 0 {
+            return Err(LarkError::api(
+                token_response.code,
+                token_response.msg,
+                response.status,
+                response.log_id,
+            ));
+        }
+
+        let token = token_response.app_access_token;
+        *self.app_token.lock().unwrap() = Some(TokenCache::new(
+            token.clone(),
+            token_response.expire,
+            self.refresh_buffer,
+        ));
+        Ok(token)
+    }
+
+    /// Callers must hold the lock of the tenant
+    async fn fetch_tenant_token(&self, tenant_key: &str) -> Result<String> {
+        let fetched = match self.request_tenant_token(tenant_key).await {
+            // The app access token was revoked or expired early: fetch it again, once
+            Err(LarkError::Api {
+                kind: ErrorKind::Auth,
+                ..
+            }) => self.request_tenant_token(tenant_key).await,
+            fetched => fetched,
+        };
+        #[cfg(feature = "metrics")]
+        crate::metrics::record_token_refresh("marketplace_tenant", &fetched);
+        fetched
+    }
+
+    async fn request_tenant_token(&self, tenant_key: &str) -> Result<String> {
+        let app_access_token = {
+            let _guard = self.app_lock.lock().await;
+            self.app_access_token_locked().await?
+        };
+
+        let request = ApiRequest::post("/open-apis/auth/v3/tenant_access_token").json(
+            &StoreTenantAccessTokenRequest {
+                app_access_token: &app_access_token,
+                tenant_key,
+            },
+        )?;
+        let response = self.execute(&request).await?;
+        let token_response = response.json::<StoreTenantAccessTokenResponse>()?;
+        if token_response.code != 0 {
+            let err = LarkError::api(
+                token_response.code,
+                token_response.msg,
+                response.status,
+                response.log_id,
+            );
+            if err.kind() == ErrorKind::Auth {
+                self.invalidate_app_token(&app_access_token);
+            }
+            return Err(err);
+        }
+
+        let token = token_response.tenant_access_token;
+        self.tenant_tokens.lock().unwrap().insert(
+            tenant_key.to_string(),
+            (
+                TokenCache::new(token.clone(), token_response.expire, self.refresh_buffer),
+                app_access_token,
+            ),
+        );
+        Ok(token)
+    }
+
+    async fn execute(&self, request: &ApiRequest) -> Result<crate::http::RawResponse> {
+        self.transport
+            .config()
+            .retry
+            .execute(&self.transport, &request.clone().idempotent(), None)
+            .await
+    }
+}
+
+/// Token of `cache` if it has not entered its refresh window
+fn valid(cache: &Option<TokenCache>) -> Option<String> {
+    cache
+        .as_ref()
+        .filter(|cached| cached.is_valid() && std::time::Instant::now() < cached.refresh_at)
+        .map(|cached| cached.token.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::AppType;
+    use crate::{ErrorKind, LarkClient};
+    use serde_json::json;
+    #[cfg(feature = "im")]
+    use wiremock::matchers::header;
+    use wiremock::matchers::{body_partial_json, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn client(server: &MockServer) -> LarkClient {
+        let config = LarkConfig::new("cli_store", "secret")
+            .with_base_url(server.uri())
+            .with_app_type(AppType::Marketplace);
+        LarkClient::new(config).unwrap()
+    }
+
+    #[cfg(feature = "im")]
+    async fn mount_auth(server: &MockServer) {
+        Mock::given(method("POST"))
+            .and(path("/open-apis/auth/v3/app_access_token"))
+            .and(body_partial_json(json!({"app_ticket": "ticket-1"})))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "code": 0,
+                "msg": "ok",
+                "app_access_token": "a-1",
+                "expire": 7200
+            })))
+            .expect(1)
+            .mount(server)
+            .await;
+        for tenant in ["tenant-a", "tenant-b"] {
+            Mock::given(method("POST"))
+                .and(path("/open-apis/auth/v3/tenant_access_token"))
+                .and(body_partial_json(
+                    json!({"app_access_token": "a-1", "tenant_key": tenant}),
+                ))
+                .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                    "code": 0,
+                    "msg": "ok",
+                    "tenant_access_token": format!("t-{}", tenant),
+                    "expire": 7200
+                })))
+                .expect(1)
+                .mount(server)
+                .await;
+        }
+    }
+
+    #[test]
+    fn test_handle_event() {
+        let client = LarkClient::new(LarkConfig::new("cli_store", "secret")).unwrap();
+        let manager = client.marketplace();
+
+        let v1 = json!({
+            "type": "event_callback",
+            "event": {"app_id": "cli_store", "app_ticket": "ticket-v1", "type": "app_ticket"}
+        });
+        assert!(manager.handle_event(&v1));
+        assert_eq!(manager.app_ticket().as_deref(), Some("ticket-v1"));
+
+        let other_app = json!({
+            "event": {"app_id": "cli_other", "app_ticket": "ticket-x", "type": "app_ticket"}
+        });
+        assert!(!manager.handle_event(&other_app));
+
+        let message = json!({"header": {"event_type": "im.message.receive_v1"}, "event": {}});
+        assert!(!manager.handle_event(&message));
+        assert_eq!(manager.app_ticket().as_deref(), Some("ticket-v1"));
+    }
+
+    #[cfg(feature = "im")]
+    #[tokio::test]
+    async fn test_per_tenant_tokens() {
+        let server = MockServer::start().await;
+        mount_auth(&server).await;
+        Mock::given(method("GET"))
+            .and(path("/open-apis/im/v1/chats"))
+            .and(header("Authorization", "Bearer t-tenant-b"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "code": 0,
+                "msg": "success",
+                "data": {"items": [], "has_more": false}
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = client(&server);
+        client.marketplace().set_app_ticket("ticket-1");
+
+        let market = client.marketplace();
+        assert_eq!(
+            market.get_tenant_token("tenant-a").await.unwrap(),
+            "t-tenant-a"
+        );
+        assert_eq!(
+            market.get_tenant_token("tenant-a").await.unwrap(),
+            "t-tenant-a"
+        );
+
+        let chats = client
+            .for_tenant("tenant-b")
+            .chat()
+            .get_chat_group_list()
+            .await
+            .unwrap();
+        assert!(chats.is_empty());
+    }
+
+    /// Mount an app access token endpoint handing out `a-1` and then `a-2`
+    async fn mount_rotating_app_token(server: &MockServer) {
+        for token in ["a-1", "a-2"] {
+            Mock::given(method("POST"))
+                .and(path("/open-apis/auth/v3/app_access_token"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                    "code": 0,
+                    "msg": "ok",
+                    "app_access_token": token,
+                    "expire": 7200
+                })))
+                .up_to_n_times(1)
+                .expect(1)
+                .mount(server)
+                .await;
+        }
+    }
+
+    async fn mount_tenant_token(server: &MockServer, app_token: &str, body: Value) {
+        Mock::given(method("POST"))
+            .and(path("/open-apis/auth/v3/tenant_access_token"))
+            .and(body_partial_json(json!({"app_access_token": app_token})))
+            .respond_with(ResponseTemplate::new(200).set_body_json(body))
+            .expect(1)
+            .mount(server)
+            .await;
+    }
+
+    #[cfg(feature = "im")]
+    #[tokio::test]
+    async fn test_rejected_token_refreshes_app_token() {
+        let server = MockServer::start().await;
+        mount_rotating_app_token(&server).await;
+        for (app_token, tenant_token) in [("a-1", "t-1"), ("a-2", "t-2")] {
+            let body = json!({"code": 0, "msg": "ok", "tenant_access_token": tenant_token, "expire": 7200});
+            mount_tenant_token(&server, app_token, body).await;
+        }
+        Mock::given(method("GET"))
+            .and(path("/open-apis/im/v1/chats"))
+            .and(header("Authorization", "Bearer t-1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "code": 99991663,
+                "msg": "Invalid access token for authorization"
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/open-apis/im/v1/chats"))
+            .and(header("Authorization", "Bearer t-2"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "code": 0,
+                "msg": "success",
+                "data": {"items": [], "has_more": false}
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = client(&server);
+        client.marketplace().set_app_ticket("ticket-1");
+        let chats = client
+            .for_tenant("tenant-a")
+            .chat()
+            .get_chat_group_list()
+            .await
+            .unwrap();
+        assert!(chats.is_empty());
+        assert_eq!(
+            client.marketplace().get_app_access_token().await.unwrap(),
+            "a-2"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_revoked_app_token_is_fetched_again() {
+        let server = MockServer::start().await;
+        mount_rotating_app_token(&server).await;
+        let revoked = json!({"code": 99991664, "msg": "invalid app access token"});
+        mount_tenant_token(&server, "a-1", revoked).await;
+        let body = json!({"code": 0, "msg": "ok", "tenant_access_token": "t-2", "expire": 7200});
+        mount_tenant_token(&server, "a-2", body).await;
+
+        let client = client(&server);
+        let market = client.marketplace();
+        market.set_app_ticket("ticket-1");
+        assert_eq!(market.get_tenant_token("tenant-a").await.unwrap(), "t-2");
+    }
+
+    #[tokio::test]
+    async fn test_missing_ticket_requests_resend() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/open-apis/auth/v3/app_ticket/resend"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"code": 0, "msg": "ok"})))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let err = client(&server)
+            .marketplace()
+            .get_tenant_token("tenant-a")
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Auth);
+    }
+
+    #[cfg(feature = "im")]
+    #[tokio::test]
+    async fn test_tenant_mode_requires_tenant_key() {
+        let server = MockServer::start().await;
+
+        let err = client(&server)
+            .chat()
+            .get_chat_group_list()
+            .await
+            .unwrap_err();
+        assert!(matches!(err, LarkError::Config(_)));
+    }
+}
@@ -0,0 +1,406 @@
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tracing::warn;
+
+use crate::config::LarkConfig;
+use crate::error::{LarkError, Result};
+use crate::http::{ApiRequest, Transport};
+use store::StoredToken;
+
+pub mod marketplace;
+pub mod store;
+pub mod user;
+
+/// Codes returned when the access token sent with a request is missing, malformed,
+/// revoked or expired on the Lark side
+pub(crate) const INVALID_TOKEN_CODES: [i32; 7] = [
+    99991661, 99991663, 99991664, 99991665, 99991668, 99991671, 99991677,
+];
+
+#[derive(Debug, Serialize)]
+pub struct TenantAccessTokenRequest {
+    pub app_id: String,
+    pub app_secret: String,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct TenantAccessTokenResponse {
+    pub code: i32,
+    pub msg: String,
+    #[serde(default)]
+    pub tenant_access_token: String,
+    #[serde(default)]
+    pub expire: i32,
+}
+
+impl TenantAccessTokenResponse {
+    pub fn is_success(&self) -> bool {
+        self.code == 0
+    }
+}
+
+#[derive(Debug, Clone)]
+struct TokenCache {
+    token: String,
+    /// When a background refresh should start
+    refresh_at: Instant,
+    /// When the token must no longer be sent
+    expiry: Instant,
+}
+
+impl TokenCache {
+    fn new(token: String, expire_secs: i32, refresh_buffer_secs: u64) -> Self {
+        let now = Instant::now();
+        let lifetime = Duration::from_secs(expire_secs.max(0) as u64);
+        let buffer = Duration::from_secs(refresh_buffer_secs);
+
+        Self {
+            token,
+            refresh_at: now + lifetime.saturating_sub(buffer),
+            expiry: now + lifetime.saturating_sub(EXPIRY_SAFETY_MARGIN),
+        }
+    }
+
+    fn is_valid(&self) -> bool {
+        Instant::now() < self.expiry
+    }
+}
+
+/// Tokens are never sent during the last seconds of their lifetime, to absorb clock
+/// drift and request latency
+const EXPIRY_SAFETY_MARGIN: Duration = Duration::from_secs(5);
+
+/// Feishu token manager that manages token refresh
+///
+/// Concurrent callers that find no valid token share one in-flight fetch. Once a token
+/// enters the `refresh_buffer` window, it is still handed out while a background task
+/// fetches its replacement.
+///
+/// Fetched tokens are written to the configured [`store::TokenStore`], and a token found
+/// there is reused instead of fetching one when the in-memory cache is empty.
+pub struct FeishuTokenManager {
+    state: Arc<TokenState>,
+}
+
+struct TokenState {
+    transport: Arc<Transport>,
+    token_cache: Mutex<Option<TokenCache>>,
+    /// Serializes token fetches so that only one request reaches the auth endpoint
+    refresh_lock: tokio::sync::Mutex<()>,
+    /// Set while a background refresh task is running
+    refreshing: AtomicBool,
+    /// Token refresh buffer time in seconds (default: 60 seconds)
+    refresh_buffer: AtomicU64,
+}
+
+impl FeishuTokenManager {
+    /// Create a token manager configured from the `FEISHU_*` environment variables
+    ///
+    /// # Panics
+    ///
+    /// Panics if the HTTP client cannot be built, see [`LarkConfig::http_client`].
+    pub fn new() -> Self {
+        let config = LarkConfig::from_env();
+        let client = config.http_client().expect("Failed to create HTTP client");
+        Self::with_config(Arc::new(config), client)
+    }
+
+    /// Create a token manager that uses the given configuration and HTTP client
+    pub fn with_config(config: Arc<LarkConfig>, client: Client) -> Self {
+        Self::with_transport(Arc::new(Transport::new(config, client)))
+    }
+
+    pub(crate) fn with_transport(transport: Arc<Transport>) -> Self {
+        Self {
+            state: Arc::new(TokenState {
+                transport,
+                token_cache: Mutex::new(None),
+                refresh_lock: tokio::sync::Mutex::new(()),
+                refreshing: AtomicBool::new(false),
+                refresh_buffer: AtomicU64::new(60), // 1 minute buffer before expiry
+            }),
+        }
+    }
+
+    pub fn with_refresh_buffer(self, seconds: u64) -> Self {
+        self.state.refresh_buffer.store(seconds, Ordering::Relaxed);
+        self
+    }
+
+    pub async fn get_token(&self) -> Result<String> {
+        if let Some(cached) = self.state.cached() {
+            if Instant::now() >= cached.refresh_at {
+                self.spawn_background_refresh();
+            }
+            return Ok(cached.token);
+        }
+
+        let _guard = self.state.refresh_lock.lock().await;
+        // Another caller may have completed the fetch while we were waiting
+        if let Some(cached) = self.state.cached() {
+            return Ok(cached.token);
+        }
+        if let Some(stored) = self.state.load_stored() {
+            return Ok(stored.token);
+        }
+        self.state.refresh_token().await
+    }
+
+    /// Drop the cached token and fetch a new one, e.g. after Lark rejected the cached token
+    pub async fn force_refresh(&self) -> Result<String> {
+        let _guard = self.state.refresh_lock.lock().await;
+        self.state.refresh_token().await
+    }
+
+    /// Replace `rejected` with a fresh token.
+    ///
+    /// When several requests are rejected with the same token, only the first one
+    /// fetches; the others pick up the token it stored.
+    pub(crate) async fn refresh_rejected(&self, rejected: &str) -> Result<String> {
+        let _guard = self.state.refresh_lock.lock().await;
+        match self.state.cached() {
+            Some(cached) if cached.token != rejected => Ok(cached.token),
+            _ => self.state.refresh_token().await,
+        }
+    }
+
+    fn spawn_background_refresh(&self) {
+        let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+            return;
+        };
+        if self.state.refreshing.swap(true, Ordering::AcqRel) {
+            return;
+        }
+
+        let state = self.state.clone();
+        runtime.spawn(async move {
+            let _guard = state.refresh_lock.lock().await;
+            // Another process sharing the store may have refreshed already
+            let due = |cached: Option<TokenCache>| {
+                cached.is_none_or(|cached| Instant::now() >= cached.refresh_at)
+            };
+            if due(state.cached())
+                && due(state.load_stored())
+                && let Err(e) = state.refresh_token().await
+            {
+                warn!(error = %e, "Background token refresh failed");
+            }
+            state.refreshing.store(false, Ordering::Release);
+        });
+    }
+}
+
+impl TokenState {
+    /// The cached token, if it may still be sent
+    fn cached(&self) -> Option<TokenCache> {
+        let cache = self.token_cache.lock().unwrap();
+
+        cache.as_ref().filter(|cached| cached.is_valid()).cloned()
+    }
+
+    /// Load a still valid token from the token store into the cache
+    fn load_stored(&self) -> Option<TokenCache> {
+        let config = self.transport.config();
+        let stored = config.token_store.load(&config.token_store_key())?;
+        let remaining = stored.remaining_secs().min(i32::MAX as u64) as i32;
+        let cache = TokenCache::new(
+            stored.token,
+            remaining,
+            self.refresh_buffer.load(Ordering::Relaxed),
+        );
+        if !cache.is_valid() {
+            return None;
+        }
+
+        *self.token_cache.lock().unwrap() = Some(cache.clone());
+        Some(cache)
+    }
+
+    /// Fetch a token, cache it and persist it. Callers must hold `refresh_lock`.
+    async fn refresh_token(&self) -> Result<String> {
+        let fetched = self.fetch_tenant_access_token().await;
+        #[cfg(feature = "metrics")]
+        crate::metrics::record_token_refresh("tenant", &fetched);
+        let token_response = fetched?;
+        let token = token_response.tenant_access_token.clone();
+        let cache = TokenCache::new(
+            token.clone(),
+            token_response.expire,
+            self.refresh_buffer.load(Ordering::Relaxed),
+        );
+
+        let mut cache_lock = self.token_cache.lock().unwrap();
+        *cache_lock = Some(cache);
+        drop(cache_lock);
+
+        let config = self.transport.config();
+        let stored = StoredToken::new(token.clone(), token_response.expire.max(0) as u64);
+        if let Err(e) = config.token_store.save(&config.token_store_key(), &stored) {
+            warn!(error = %e, "Failed to persist tenant access token");
+        }
+
+        Ok(token)
+    }
+
+    /// Fetch a new tenant access token from the API
+    async fn fetch_tenant_access_token(&self) -> Result<TenantAccessTokenResponse> {
+        let config = self.transport.config();
+        if config.app_id.is_empty() {
+            return Err(LarkError::Config(
+                "app_id is not configured (set FEISHU_APP_ID)".to_string(),
+            ));
+        }
+        if config.app_secret.is_empty() {
+            return Err(LarkError::Config(
+                "app_secret is not configured (set FEISHU_APP_SECRET)".to_string(),
+            ));
+        }
+
+        let request_body = TenantAccessTokenRequest {
+            app_id: config.app_id.clone(),
+            app_secret: config.app_secret.clone(),
+        };
+        let request = ApiRequest::post("/open-apis/auth/v3/tenant_access_token/internal")
+            .json(&request_body)?;
+
+        let response = config
+            .retry
+            .execute(&self.transport, &request.idempotent(), None)
+            .await?;
+        let token_response = response.json::<TenantAccessTokenResponse>()?;
+
+        match token_response.is_success() {
+            true => Ok(token_response),
+            false => Err(LarkError::api(
+                token_response.code,
+                token_response.msg,
+                response.status,
+                response.log_id,
+            )),
+        }
+    }
+}
+
+impl Default for FeishuTokenManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use store::TokenStore;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[tokio::test]
+    async fn test_feishu_auth_client() {
+        let server = larkrs_mock::MockLarkServer::start().await;
+        let config = LarkConfig::new("id", "secret").with_base_url(server.uri());
+        let auth_client = FeishuTokenManager::with_config(Arc::new(config), Client::new());
+
+        // First token fetch
+        let token1 = auth_client.get_token().await.expect("Failed to get token");
+        assert!(token1.starts_with("t-mock-"), "unexpected token {}", token1);
+        assert_eq!(server.state().token_requests(), 1);
+
+        // Second token fetch should use cache
+        let token2 = auth_client.get_token().await.expect("Failed to get token");
+        assert_eq!(token1, token2, "Cached token should be the same");
+        assert_eq!(server.state().token_requests(), 1);
+    }
+
+    fn token_response(token: &str, expire: i32) -> ResponseTemplate {
+        ResponseTemplate::new(200).set_body_json(json!({
+            "code": 0,
+            "msg": "ok",
+            "tenant_access_token": token,
+            "expire": expire
+        }))
+    }
+
+    fn manager(server: &MockServer) -> FeishuTokenManager {
+        let config = LarkConfig::new("id", "secret").with_base_url(server.uri());
+        FeishuTokenManager::with_config(Arc::new(config), Client::new())
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_callers_share_one_fetch() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/open-apis/auth/v3/tenant_access_token/internal"))
+            .respond_with(token_response("t-1", 7200).set_delay(Duration::from_millis(200)))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let manager = Arc::new(manager(&server));
+        let mut tasks = tokio::task::JoinSet::new();
+        for _ in 0..10 {
+            let manager = manager.clone();
+            tasks.spawn(async move { manager.get_token().await });
+        }
+        while let Some(token) = tasks.join_next().await {
+            assert_eq!(token.unwrap().unwrap(), "t-1");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_background_refresh_inside_buffer() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/open-apis/auth/v3/tenant_access_token/internal"))
+            .respond_with(token_response("t-1", 120))
+            .up_to_n_times(1)
+            .with_priority(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/open-apis/auth/v3/tenant_access_token/internal"))
+            .respond_with(token_response("t-2", 7200))
+            .mount(&server)
+            .await;
+
+        // A buffer as long as the lifetime puts the first token in the refresh window at once
+        let manager = manager(&server).with_refresh_buffer(120);
+        assert_eq!(manager.get_token().await.unwrap(), "t-1");
+        // Still valid, handed out without waiting while the refresh runs in the background
+        assert_eq!(manager.get_token().await.unwrap(), "t-1");
+
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        assert_eq!(manager.get_token().await.unwrap(), "t-2");
+        assert_eq!(server.received_requests().await.unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_token_store_shared_between_managers() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/open-apis/auth/v3/tenant_access_token/internal"))
+            .respond_with(token_response("t-1", 7200))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let dir = tempfile::tempdir().unwrap();
+        let store = Arc::new(store::FileTokenStore::new(dir.path().join("tokens.json")));
+        let config = Arc::new(
+            LarkConfig::new("id", "secret")
+                .with_base_url(server.uri())
+                .with_token_store(store.clone()),
+        );
+
+        let first = FeishuTokenManager::with_config(config.clone(), Client::new());
+        assert_eq!(first.get_token().await.unwrap(), "t-1");
+        assert_eq!(store.load(&config.token_store_key()).unwrap().token, "t-1");
+
+        // A second process with the same store reuses the token without fetching
+        let second = FeishuTokenManager::with_config(config, Client::new());
+        assert_eq!(second.get_token().await.unwrap(), "t-1");
+    }
+}
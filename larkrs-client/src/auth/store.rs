@@ -0,0 +1,208 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// A token persisted by a [`TokenStore`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StoredToken {
+    pub token: String,
+    /// Unix timestamp (seconds) at which the token expires
+    pub expires_at: u64,
+}
+
+impl StoredToken {
+    pub fn new(token: impl Into<String>, expires_in_secs: u64) -> Self {
+        Self {
+            token: token.into(),
+            expires_at: unix_now() + expires_in_secs,
+        }
+    }
+
+    /// Seconds until expiry, zero once expired
+    pub fn remaining_secs(&self) -> u64 {
+        self.expires_at.saturating_sub(unix_now())
+    }
+}
+
+/// Storage backing the token cache of [`super::FeishuTokenManager`]
+///
+/// The manager keeps the current token in memory and only consults the store when it has
+/// none, and writes to it after every fetch. A shared store thus lets short-lived
+/// processes reuse a token obtained by another one.
+pub trait TokenStore: Send + Sync + Debug {
+    fn load(&self, key: &str) -> Option<StoredToken>;
+
+    fn save(&self, key: &str, token: &StoredToken) -> io::Result<()>;
+}
+
+/// Process-local store, the default
+#[derive(Debug, Default)]
+pub struct MemoryTokenStore {
+    tokens: Mutex<HashMap<String, StoredToken>>,
+}
+
+impl MemoryTokenStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl TokenStore for MemoryTokenStore {
+    fn load(&self, key: &str) -> Option<StoredToken> {
+        self.tokens.lock().unwrap().get(key).cloned()
+    }
+
+    fn save(&self, key: &str, token: &StoredToken) -> io::Result<()> {
+        self.tokens
+            .lock()
+            .unwrap()
+            .insert(key.to_string(), token.clone());
+        Ok(())
+    }
+}
+
+/// JSON file store shared between processes
+///
+/// Reads take a shared lock and writes an exclusive lock on the file. On Unix the file is
+/// created with mode `0600`, since it holds credentials.
+#[derive(Debug, Clone)]
+pub struct FileTokenStore {
+    path: PathBuf,
+}
+
+impl FileTokenStore {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// `$XDG_CACHE_HOME/larkrs/tokens.json`, falling back on `$HOME/.cache/larkrs/tokens.json`
+    pub fn default_path() -> Option<PathBuf> {
+        let cache_dir = std::env::var_os("XDG_CACHE_HOME")
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| Path::new(&home).join(".cache")))?;
+        Some(cache_dir.join("larkrs").join("tokens.json"))
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Open the file for writing, creating it and its directory if needed
+    fn open(&self) -> io::Result<File> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        let mut options = OpenOptions::new();
+        options.read(true).write(true).create(true).truncate(false);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+            options.mode(0o600);
+            let file = options.open(&self.path)?;
+            file.set_permissions(fs::Permissions::from_mode(0o600))?;
+            Ok(file)
+        }
+        #[cfg(not(unix))]
+        options.open(&self.path)
+    }
+
+    fn read_all(file: &mut File) -> io::Result<HashMap<String, StoredToken>> {
+        let mut content = String::new();
+        file.read_to_string(&mut content)?;
+        if content.trim().is_empty() {
+            return Ok(HashMap::new());
+        }
+        // A corrupt file is treated as empty and overwritten on the next save
+        Ok(serde_json::from_str(&content).unwrap_or_default())
+    }
+}
+
+impl TokenStore for FileTokenStore {
+    fn load(&self, key: &str) -> Option<StoredToken> {
+        // A missing file means no token; it is only created by `save`
+        let mut file = File::open(&self.path).ok()?;
+        file.lock_shared().ok()?;
+        let tokens = Self::read_all(&mut file).ok()?;
+        tokens.get(key).cloned()
+    }
+
+    fn save(&self, key: &str, token: &StoredToken) -> io::Result<()> {
+        let mut file = self.open()?;
+        file.lock()?;
+
+        let mut tokens = Self::read_all(&mut file)?;
+        // Drop entries of other apps that have expired in the meantime
+        tokens.retain(|_, stored| stored.remaining_secs() > 0);
+        tokens.insert(key.to_string(), token.clone());
+
+        file.set_len(0)?;
+        file.seek(SeekFrom::Start(0))?;
+        file.write_all(&serde_json::to_vec_pretty(&tokens)?)?;
+        file.flush()
+    }
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_memory_store() {
+        let store = MemoryTokenStore::new();
+        assert!(store.load("app").is_none());
+
+        let token = StoredToken::new("t-1", 7200);
+        store.save("app", &token).unwrap();
+        assert_eq!(store.load("app"), Some(token));
+    }
+
+    #[test]
+    fn test_file_store_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("nested").join("tokens.json");
+        let store = FileTokenStore::new(&path);
+        assert!(store.load("app-a").is_none());
+        assert!(!path.parent().unwrap().exists());
+
+        store.save("app-a", &StoredToken::new("t-a", 7200)).unwrap();
+        store.save("app-b", &StoredToken::new("t-b", 7200)).unwrap();
+
+        // A second handle, as another process would open it
+        let other = FileTokenStore::new(&path);
+        assert_eq!(other.load("app-a").unwrap().token, "t-a");
+        assert_eq!(other.load("app-b").unwrap().token, "t-b");
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+    }
+
+    #[test]
+    fn test_file_store_ignores_corrupt_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("tokens.json");
+        fs::write(&path, "not json").unwrap();
+
+        let store = FileTokenStore::new(&path);
+        assert!(store.load("app").is_none());
+        store.save("app", &StoredToken::new("t-1", 60)).unwrap();
+        assert_eq!(store.load("app").unwrap().token, "t-1");
+    }
+}
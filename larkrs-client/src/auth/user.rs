@@ -0,0 +1,390 @@
+use reqwest::{Client, Url};
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::config::LarkConfig;
+use crate::error::{LarkError, Result};
+use crate::http::{ApiRequest, Transport};
+
+/// A user access token obtained through the OAuth 2 authorization-code flow.
+///
+/// Serializable so applications can persist it and restore it with
+/// [`UserTokenManager::set_token`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UserToken {
+    pub access_token: String,
+    #[serde(default)]
+    pub refresh_token: Option<String>,
+    /// Unix timestamp (seconds) at which `access_token` expires
+    pub expires_at: u64,
+    /// Unix timestamp (seconds) at which `refresh_token` expires
+    #[serde(default)]
+    pub refresh_expires_at: Option<u64>,
+    #[serde(default)]
+    pub scope: Option<String>,
+}
+
+impl UserToken {
+    fn access_valid(&self, buffer: Duration) -> bool {
+        unix_now() + buffer.as_secs() < self.expires_at
+    }
+
+    fn refresh_valid(&self) -> bool {
+        self.refresh_token.is_some()
+            && self
+                .refresh_expires_at
+                .is_none_or(|expires_at| unix_now() < expires_at)
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct OAuthTokenRequest<'a> {
+    grant_type: &'a str,
+    client_id: &'a str,
+    client_secret: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    code: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    redirect_uri: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    refresh_token: Option<&'a str>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct OAuthTokenResponse {
+    pub code: i32,
+    #[serde(default)]
+    pub access_token: String,
+    #[serde(default)]
+    pub expires_in: u64,
+    #[serde(default)]
+    pub refresh_token: Option<String>,
+    #[serde(default)]
+    pub refresh_token_expires_in: Option<u64>,
+    #[serde(default)]
+    pub scope: Option<String>,
+    #[serde(default)]
+    pub error: Option<String>,
+    #[serde(default)]
+    pub error_description: Option<String>,
+}
+
+/// User access token manager implementing Lark's OAuth 2 authorization-code flow
+///
+/// 1. Send the user to [`authorize_url`](Self::authorize_url)
+/// 2. Exchange the `code` Lark redirects back with via [`exchange_code`](Self::exchange_code)
+/// 3. Run API clients as the user with [`crate::LarkClient::as_user`]; the access token is
+///    refreshed, and the refresh token rotated, when it expires
+///
+/// See: https://open.feishu.cn/document/authentication-management/access-token/obtain-oauth-code
+pub struct UserTokenManager {
+    transport: Arc<Transport>,
+    token: Mutex<Option<UserToken>>,
+    refresh_lock: tokio::sync::Mutex<()>,
+    /// Token refresh buffer time in seconds (default: 60 seconds)
+    refresh_buffer: u64,
+}
+
+impl UserTokenManager {
+    /// Create a manager that uses the given configuration and HTTP client
+    pub fn with_config(config: Arc<LarkConfig>, client: Client) -> Self {
+        Self::with_transport(Arc::new(Transport::new(config, client)))
+    }
+
+    pub(crate) fn with_transport(transport: Arc<Transport>) -> Self {
+        Self {
+            transport,
+            token: Mutex::new(None),
+            refresh_lock: tokio::sync::Mutex::new(()),
+            refresh_buffer: 60,
+        }
+    }
+
+    pub fn with_refresh_buffer(mut self, seconds: u64) -> Self {
+        self.refresh_buffer = seconds;
+        self
+    }
+
+    /// Build the URL the user opens to grant the app access.
+    ///
+    /// `state` is echoed back to `redirect_uri` together with the authorization code.
+    pub fn authorize_url(&self, redirect_uri: &str, scopes: &[&str], state: &str) -> String {
+        let config = self.transport.config();
+        let mut params = vec![
+            ("client_id", config.app_id.clone()),
+            ("response_type", "code".to_string()),
+            ("redirect_uri", redirect_uri.to_string()),
+            ("state", state.to_string()),
+        ];
+        if !scopes.is_empty() {
+            params.push(("scope", scopes.join(" ")));
+        }
+
+        match Url::parse_with_params(&config.url("/open-apis/authen/v1/authorize"), &params) {
+            Ok(url) => url.to_string(),
+            Err(_) => config.url("/open-apis/authen/v1/authorize"),
+        }
+    }
+
+    /// Exchange an authorization code for a user token and keep it
+    pub async fn exchange_code(&self, code: &str, redirect_uri: &str) -> Result<UserToken> {
+        let _guard = self.refresh_lock.lock().await;
+        let config = self.transport.config();
+        let request = OAuthTokenRequest {
+            grant_type: "authorization_code",
+            client_id: &config.app_id,
+            client_secret: &config.app_secret,
+            code: Some(code),
+            redirect_uri: Some(redirect_uri),
+            refresh_token: None,
+        };
+        self.request_token(&request).await
+    }
+
+    /// Get a valid user access token, refreshing it if needed
+    pub async fn get_token(&self) -> Result<String> {
+        if let Some(token) = self.valid_token() {
+            return Ok(token);
+        }
+
+        let _guard = self.refresh_lock.lock().await;
+        if let Some(token) = self.valid_token() {
+            return Ok(token);
+        }
+        Ok(self.refresh_locked().await?.access_token)
+    }
+
+    /// Refresh the access token with the stored refresh token, rotating the refresh token
+    pub async fn refresh(&self) -> Result<UserToken> {
+        let _guard = self.refresh_lock.lock().await;
+        self.refresh_locked().await
+    }
+
+    /// Replace `rejected` with a fresh token unless a concurrent caller already did
+    pub(crate) async fn refresh_rejected(&self, rejected: &str) -> Result<String> {
+        let _guard = self.refresh_lock.lock().await;
+        match self.token() {
+            Some(token) if token.access_token != rejected && token.access_valid(Duration::ZERO) => {
+                Ok(token.access_token)
+            }
+            _ => Ok(self.refresh_locked().await?.access_token),
+        }
+    }
+
+    /// Current token, e.g. to persist it
+    pub fn token(&self) -> Option<UserToken> {
+        self.token.lock().unwrap().clone()
+    }
+
+    /// Restore a previously obtained token
+    pub fn set_token(&self, token: UserToken) {
+        *self.token.lock().unwrap() = Some(token);
+    }
+
+    fn valid_token(&self) -> Option<String> {
+        let buffer = Duration::from_secs(self.refresh_buffer);
+        self.token()
+            .filter(|token| token.access_valid(buffer))
+            .map(|token| token.access_token)
+    }
+
+    /// Callers must hold `refresh_lock`
+    async fn refresh_locked(&self) -> Result<UserToken> {
+        let refresh_token = match self.token() {
+            Some(token) if token.refresh_valid() => token.refresh_token.unwrap_or_default(),
+            Some(_) => {
+                return Err(LarkError::Unauthorized(
+                    "user refresh token expired, the user must authorize again".to_string(),
+                ));
+            }
+            None => {
+                return Err(LarkError::Unauthorized(
+                    "no user token, complete the authorization flow first".to_string(),
+                ));
+            }
+        };
+
+        let config = self.transport.config();
+        let request = OAuthTokenRequest {
+            grant_type: "refresh_token",
+            client_id: &config.app_id,
+            client_secret: &config.app_secret,
+            code: None,
+            redirect_uri: None,
+            refresh_token: Some(&refresh_token),
+        };
+        let refreshed = self.request_token(&request).await;
+        #[cfg(feature = "metrics")]
+        crate::metrics::record_token_refresh("user", &refreshed);
+        refreshed
+    }
+
+    async fn request_token(&self, body: &OAuthTokenRequest<'_>) -> Result<UserToken> {
+        let request = ApiRequest::post("/open-apis/authen/v2/oauth/token").json(body)?;
+        let response = self
+            .transport
+            .config()
+            .retry
+            .execute(&self.transport, &request, None)
+            .await?;
+        let token_response = response.json::<OAuthTokenResponse>()?;
+
+        if token_response.code != 0 {
+            let msg = token_response
+                .error_description
+                .or(token_response.error)
+                .unwrap_or_default();
+            return Err(LarkError::api(
+                token_response.code,
+                msg,
+                response.status,
+                response.log_id,
+            ));
+        }
+
+        let now = unix_now();
+        let token = UserToken {
+            access_token: token_response.access_token,
+            refresh_token: token_response.refresh_token,
+            expires_at: now + token_response.expires_in,
+            refresh_expires_at: token_response
+                .refresh_token_expires_in
+                .map(|secs| now + secs),
+            scope: token_response.scope,
+        };
+        self.set_token(token.clone());
+
+        Ok(token)
+    }
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::LarkClient;
+    use serde_json::json;
+    #[cfg(feature = "im")]
+    use wiremock::matchers::header;
+    use wiremock::matchers::{body_partial_json, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn client(server: &MockServer) -> LarkClient {
+        let config = LarkConfig::new("cli_test", "secret").with_base_url(server.uri());
+        LarkClient::new(config).unwrap()
+    }
+
+    fn oauth_response(access: &str, refresh: &str) -> ResponseTemplate {
+        ResponseTemplate::new(200).set_body_json(json!({
+            "code": 0,
+            "access_token": access,
+            "expires_in": 7200,
+            "refresh_token": refresh,
+            "refresh_token_expires_in": 604800,
+            "token_type": "Bearer",
+            "scope": "wiki:wiki:readonly"
+        }))
+    }
+
+    #[test]
+    fn test_authorize_url() {
+        let manager = LarkClient::new(LarkConfig::new("cli_test", "secret"))
+            .unwrap()
+            .user_token_manager();
+        let url = manager.authorize_url(
+            "https://example.com/callback",
+            &["wiki:wiki:readonly", "docx:document:readonly"],
+            "xyz",
+        );
+
+        assert!(url.starts_with("https://open.feishu.cn/open-apis/authen/v1/authorize?"));
+        assert!(url.contains("client_id=cli_test"));
+        assert!(url.contains("redirect_uri=https%3A%2F%2Fexample.com%2Fcallback"));
+        assert!(url.contains("scope=wiki%3Awiki%3Areadonly+docx%3Adocument%3Areadonly"));
+        assert!(url.contains("state=xyz"));
+    }
+
+    #[cfg(feature = "im")]
+    #[tokio::test]
+    async fn test_exchange_code_and_call_as_user() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/open-apis/authen/v2/oauth/token"))
+            .and(body_partial_json(
+                json!({"grant_type": "authorization_code", "code": "code-1"}),
+            ))
+            .respond_with(oauth_response("u-1", "ur-1"))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/open-apis/im/v1/chats"))
+            .and(header("Authorization", "Bearer u-1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "code": 0,
+                "msg": "success",
+                "data": {"items": [{"chat_id": "oc_1", "name": "mine"}], "has_more": false}
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = client(&server);
+        let user = Arc::new(client.user_token_manager());
+        let token = user
+            .exchange_code("code-1", "https://example.com/callback")
+            .await
+            .unwrap();
+        assert_eq!(token.refresh_token.as_deref(), Some("ur-1"));
+
+        let chats = client
+            .as_user(user)
+            .chat()
+            .get_chat_group_list()
+            .await
+            .unwrap();
+        assert_eq!(chats[0].name, "mine");
+    }
+
+    #[tokio::test]
+    async fn test_expired_token_is_refreshed_and_rotated() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/open-apis/authen/v2/oauth/token"))
+            .and(body_partial_json(
+                json!({"grant_type": "refresh_token", "refresh_token": "ur-1"}),
+            ))
+            .respond_with(oauth_response("u-2", "ur-2"))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let user = client(&server).user_token_manager();
+        user.set_token(UserToken {
+            access_token: "u-1".to_string(),
+            refresh_token: Some("ur-1".to_string()),
+            expires_at: unix_now() - 1,
+            refresh_expires_at: Some(unix_now() + 3600),
+            scope: None,
+        });
+
+        assert_eq!(user.get_token().await.unwrap(), "u-2");
+        assert_eq!(user.token().unwrap().refresh_token.as_deref(), Some("ur-2"));
+    }
+
+    #[tokio::test]
+    async fn test_missing_token_is_unauthorized() {
+        let server = MockServer::start().await;
+        let user = client(&server).user_token_manager();
+
+        let err = user.get_token().await.unwrap_err();
+        assert_eq!(err.kind(), crate::ErrorKind::Auth);
+    }
+}
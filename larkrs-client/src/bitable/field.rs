@@ -0,0 +1,358 @@
+//! Types and properties of Bitable fields.
+//!
+//! See: https://open.feishu.cn/document/server-docs/docs/bitable-v1/app-table-field/guide
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value, json};
+
+/// Field type, the `type` of a field
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(from = "i32", into = "i32")]
+pub enum FieldType {
+    /// Text, also email and barcode fields
+    #[default]
+    Text,
+    /// Number, also currency, progress and rating fields
+    Number,
+    SingleSelect,
+    MultiSelect,
+    DateTime,
+    Checkbox,
+    User,
+    Phone,
+    Url,
+    Attachment,
+    /// One-way link to records of a table
+    SingleLink,
+    Lookup,
+    Formula,
+    /// Two-way link to records of a table
+    DuplexLink,
+    Location,
+    GroupChat,
+    CreatedTime,
+    ModifiedTime,
+    CreatedUser,
+    ModifiedUser,
+    AutoNumber,
+    Unknown(i32),
+}
+
+impl FieldType {
+    /// Whether Lark computes the values of the field, which then cannot be written
+    pub fn is_read_only(self) -> bool {
+        matches!(
+            self,
+            Self::Lookup
+                | Self::Formula
+                | Self::CreatedTime
+                | Self::ModifiedTime
+                | Self::CreatedUser
+                | Self::ModifiedUser
+                | Self::AutoNumber
+        )
+    }
+}
+
+impl From<i32> for FieldType {
+    fn from(code: i32) -> Self {
+        match code {
+            1 => Self::Text,
+            2 => Self::Number,
+            3 => Self::SingleSelect,
+            4 => Self::MultiSelect,
+            5 => Self::DateTime,
+            7 => Self::Checkbox,
+            11 => Self::User,
+            13 => Self::Phone,
+            15 => Self::Url,
+            17 => Self::Attachment,
+            18 => Self::SingleLink,
+            19 => Self::Lookup,
+            20 => Self::Formula,
+            21 => Self::DuplexLink,
+            22 => Self::Location,
+            23 => Self::GroupChat,
+            1001 => Self::CreatedTime,
+            1002 => Self::ModifiedTime,
+            1003 => Self::CreatedUser,
+            1004 => Self::ModifiedUser,
+            1005 => Self::AutoNumber,
+            other => Self::Unknown(other),
+        }
+    }
+}
+
+impl From<FieldType> for i32 {
+    fn from(field_type: FieldType) -> Self {
+        match field_type {
+            FieldType::Text => 1,
+            FieldType::Number => 2,
+            FieldType::SingleSelect => 3,
+            FieldType::MultiSelect => 4,
+            FieldType::DateTime => 5,
+            FieldType::Checkbox => 7,
+            FieldType::User => 11,
+            FieldType::Phone => 13,
+            FieldType::Url => 15,
+            FieldType::Attachment => 17,
+            FieldType::SingleLink => 18,
+            FieldType::Lookup => 19,
+            FieldType::Formula => 20,
+            FieldType::DuplexLink => 21,
+            FieldType::Location => 22,
+            FieldType::GroupChat => 23,
+            FieldType::CreatedTime => 1001,
+            FieldType::ModifiedTime => 1002,
+            FieldType::CreatedUser => 1003,
+            FieldType::ModifiedUser => 1004,
+            FieldType::AutoNumber => 1005,
+            FieldType::Unknown(code) => code,
+        }
+    }
+}
+
+/// Field type as shown in the UI, the `ui_type` of a field; finer than [`FieldType`]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
+pub enum UiType {
+    Text,
+    Email,
+    Barcode,
+    Number,
+    Progress,
+    Currency,
+    Rating,
+    SingleSelect,
+    MultiSelect,
+    DateTime,
+    Checkbox,
+    User,
+    GroupChat,
+    Phone,
+    Url,
+    Attachment,
+    SingleLink,
+    Formula,
+    DuplexLink,
+    Location,
+    CreatedTime,
+    ModifiedTime,
+    CreatedUser,
+    ModifiedUser,
+    AutoNumber,
+    Lookup,
+    Unknown(String),
+}
+
+impl UiType {
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Text => "Text",
+            Self::Email => "Email",
+            Self::Barcode => "Barcode",
+            Self::Number => "Number",
+            Self::Progress => "Progress",
+            Self::Currency => "Currency",
+            Self::Rating => "Rating",
+            Self::SingleSelect => "SingleSelect",
+            Self::MultiSelect => "MultiSelect",
+            Self::DateTime => "DateTime",
+            Self::Checkbox => "Checkbox",
+            Self::User => "User",
+            Self::GroupChat => "GroupChat",
+            Self::Phone => "Phone",
+            Self::Url => "Url",
+            Self::Attachment => "Attachment",
+            Self::SingleLink => "SingleLink",
+            Self::Formula => "Formula",
+            Self::DuplexLink => "DuplexLink",
+            Self::Location => "Location",
+            Self::CreatedTime => "CreatedTime",
+            Self::ModifiedTime => "ModifiedTime",
+            Self::CreatedUser => "CreatedUser",
+            Self::ModifiedUser => "ModifiedUser",
+            Self::AutoNumber => "AutoNumber",
+            Self::Lookup => "Lookup",
+            Self::Unknown(ui_type) => ui_type,
+        }
+    }
+}
+
+impl From<String> for UiType {
+    fn from(ui_type: String) -> Self {
+        match ui_type.as_str() {
+            "Text" => Self::Text,
+            "Email" => Self::Email,
+            "Barcode" => Self::Barcode,
+            "Number" => Self::Number,
+            "Progress" => Self::Progress,
+            "Currency" => Self::Currency,
+            "Rating" => Self::Rating,
+            "SingleSelect" => Self::SingleSelect,
+            "MultiSelect" => Self::MultiSelect,
+            "DateTime" => Self::DateTime,
+            "Checkbox" => Self::Checkbox,
+            "User" => Self::User,
+            "GroupChat" => Self::GroupChat,
+            "Phone" => Self::Phone,
+            "Url" => Self::Url,
+            "Attachment" => Self::Attachment,
+            "SingleLink" => Self::SingleLink,
+            "Formula" => Self::Formula,
+            "DuplexLink" => Self::DuplexLink,
+            "Location" => Self::Location,
+            "CreatedTime" => Self::CreatedTime,
+            "ModifiedTime" => Self::ModifiedTime,
+            "CreatedUser" => Self::CreatedUser,
+            "ModifiedUser" => Self::ModifiedUser,
+            "AutoNumber" => Self::AutoNumber,
+            "Lookup" => Self::Lookup,
+            _ => Self::Unknown(ui_type),
+        }
+    }
+}
+
+impl From<UiType> for String {
+    fn from(ui_type: UiType) -> Self {
+        match ui_type {
+            UiType::Unknown(ui_type) => ui_type,
+            known => known.as_str().to_string(),
+        }
+    }
+}
+
+/// Settings of a field; which ones are set depends on its type
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct FieldProperty {
+    /// Options of select fields
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub options: Option<Vec<SelectOption>>,
+    /// Number format, e.g. `0.00`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub formatter: Option<String>,
+    /// Date format, e.g. `yyyy/MM/dd HH:mm`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub date_formatter: Option<String>,
+    /// Whether date fields default to the creation time
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auto_fill: Option<bool>,
+    /// Whether user and link fields accept several values
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub multiple: Option<bool>,
+    /// Linked table of link fields
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub table_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub table_name: Option<String>,
+    /// Name of the field of the linked table pointing back, for two-way links
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub back_field_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub formula_expression: Option<String>,
+    /// Currency of currency fields, e.g. `CNY`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub currency_code: Option<String>,
+    /// Settings not covered above
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SelectOption {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<i32>,
+}
+
+/// Shape of the values written to a field and an example of one, `None` for read-only
+/// fields
+pub(crate) fn write_format(
+    field_type: FieldType,
+    ui_type: Option<&UiType>,
+    property: Option<&FieldProperty>,
+) -> Option<(&'static str, Value)> {
+    // Select fields accept new option names, but existing ones make a better example
+    let mut options: Vec<&str> = property
+        .and_then(|property| property.options.as_ref())
+        .map(|options| options.iter().map(|option| option.name.as_str()).collect())
+        .unwrap_or_default();
+    if options.is_empty() {
+        options = vec!["Option A", "Option B"];
+    }
+
+    let format = match (field_type, ui_type) {
+        (FieldType::Text, Some(UiType::Email)) => ("String", json!("name@example.com")),
+        (FieldType::Text, _) => ("String", json!("text")),
+        (FieldType::Number, Some(UiType::Progress)) => ("Number", json!(0.5)),
+        (FieldType::Number, Some(UiType::Rating)) => ("Number", json!(5)),
+        (FieldType::Number, _) => ("Number", json!(10)),
+        (FieldType::SingleSelect, _) => ("String", json!(options[0])),
+        (FieldType::MultiSelect, _) => ("Array<String>", json!(options[..options.len().min(2)])),
+        (FieldType::DateTime, _) => ("Timestamp", json!(1742956800000i64)),
+        (FieldType::Checkbox, _) => ("Boolean", json!(true)),
+        (FieldType::User, _) => ("Array<{id: String}>", json!([{"id": "ou_xxx"}])),
+        (FieldType::GroupChat, _) => ("Array<{id: String}>", json!([{"id": "oc_xxx"}])),
+        (FieldType::Phone, _) => ("String", json!("13800000000")),
+        (FieldType::Url, _) => (
+            "{link: String, text: String}",
+            json!({"link": "https://example.com", "text": "example"}),
+        ),
+        (FieldType::Attachment, _) => (
+            "Array<{file_token: String}>",
+            json!([{"file_token": "boxcnxxx"}]),
+        ),
+        (FieldType::SingleLink | FieldType::DuplexLink, _) => ("Array<String>", json!(["recxxx"])),
+        (FieldType::Location, _) => ("String", json!("116.397755,39.903179")),
+        (field_type, _) if field_type.is_read_only() => return None,
+        (_, _) => ("String", json!("text")),
+    };
+    Some(format)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_type_codes_round_trip() {
+        for code in [
+            1, 2, 3, 4, 5, 7, 11, 13, 15, 17, 18, 19, 20, 21, 22, 23, 1001, 1005, 99,
+        ] {
+            assert_eq!(i32::from(FieldType::from(code)), code);
+        }
+        assert_eq!(FieldType::from(99), FieldType::Unknown(99));
+
+        let ui_type: UiType = serde_json::from_str(r#""Currency""#).unwrap();
+        assert_eq!(ui_type, UiType::Currency);
+        let ui_type: UiType = serde_json::from_str(r#""Stage""#).unwrap();
+        assert_eq!(serde_json::to_string(&ui_type).unwrap(), r#""Stage""#);
+    }
+
+    #[test]
+    fn test_write_format() {
+        let property = FieldProperty {
+            options: Some(vec![SelectOption {
+                name: "四板".to_string(),
+                id: None,
+                color: None,
+            }]),
+            ..Default::default()
+        };
+        assert_eq!(
+            write_format(FieldType::SingleSelect, None, Some(&property)),
+            Some(("String", json!("四板")))
+        );
+        assert_eq!(
+            write_format(FieldType::MultiSelect, None, None),
+            Some(("Array<String>", json!(["Option A", "Option B"])))
+        );
+        assert_eq!(
+            write_format(FieldType::Number, Some(&UiType::Progress), None),
+            Some(("Number", json!(0.5)))
+        );
+        assert_eq!(write_format(FieldType::Formula, None, None), None);
+    }
+}
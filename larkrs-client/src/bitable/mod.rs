@@ -1,9 +1,22 @@
+pub mod field;
+pub mod record;
+pub mod schema;
 pub mod table;
+pub mod value;
 
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::collections::HashMap;
 
+use crate::error::{LarkError, Result};
+use crate::page::Page;
+
+pub use field::{FieldProperty, FieldType, UiType};
+#[cfg(feature = "derive")]
+pub use larkrs_derive::{BitableRecord, BitableSelect};
+pub use record::{BitableRecord, BitableValue};
+pub use value::FieldValue;
+
 #[derive(Debug, Serialize, Deserialize, Default)]
 pub struct SearchRecordsResponse {
     pub items: Vec<Record>,
@@ -12,7 +25,19 @@ pub struct SearchRecordsResponse {
     pub total: i32,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+impl Page for SearchRecordsResponse {
+    type Item = Record;
+
+    fn next_page_token(&self) -> Option<&str> {
+        self.page_token.as_deref().filter(|_| self.has_more)
+    }
+
+    fn into_items(self) -> Vec<Record> {
+        self.items
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct Record {
     pub record_id: String,
     pub fields: HashMap<String, Value>,
@@ -26,7 +51,29 @@ pub struct Record {
     pub last_modified_time: Option<i64>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+impl Record {
+    /// Typed value of the field `name`, `None` if the record has no such field
+    pub fn field(&self, name: &str) -> Option<FieldValue> {
+        self.fields.get(name).cloned().map(FieldValue::from)
+    }
+
+    /// Every field with its typed value
+    pub fn typed_fields(&self) -> HashMap<String, FieldValue> {
+        self.fields
+            .iter()
+            .map(|(name, value)| (name.clone(), FieldValue::from(value.clone())))
+            .collect()
+    }
+
+    /// Set the field `name` to `value` in the shape it is written in, e.g. to update the
+    /// record with [`table::BitableTableClient::update_record`]
+    pub fn set_field(&mut self, name: impl Into<String>, value: impl Into<FieldValue>) {
+        self.fields
+            .insert(name.into(), value.into().to_write_value());
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UserId {
     pub user_id: Option<String>,
     pub open_id: Option<String>,
@@ -47,19 +94,14 @@ pub struct SearchRecordsCond {
 }
 
 /// Enum for filter conjunction types
-#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
 #[serde(rename_all = "lowercase")]
 pub enum FilterConjunction {
+    #[default]
     And,
     Or,
 }
 
-impl Default for FilterConjunction {
-    fn default() -> Self {
-        FilterConjunction::And
-    }
-}
-
 /// Enum for filter operator types
 #[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
 #[serde(rename_all = "camelCase")]
@@ -135,11 +177,168 @@ impl From<Value> for BatchCreateRecordsRequest {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct RecordCreate {
     pub fields: HashMap<String, Value>,
 }
 
+impl RecordCreate {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_field(mut self, name: impl Into<String>, value: impl Into<FieldValue>) -> Self {
+        self.fields
+            .insert(name.into(), value.into().to_write_value());
+        self
+    }
+}
+
+/// New values of some fields of a record; fields left out are not changed
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct RecordUpdate {
+    pub record_id: String,
+    pub fields: HashMap<String, Value>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BatchUpdateRecordsRequest {
+    pub records: Vec<RecordUpdate>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BatchDeleteRecordsRequest {
+    /// Ids of the records to delete
+    pub records: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct BatchGetRecordsRequest {
+    /// Ids of the records to get, at most 100
+    pub record_ids: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub automatic_fields: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub with_shared_url: Option<bool>,
+}
+
+impl BatchGetRecordsRequest {
+    pub fn new(record_ids: Vec<String>) -> Self {
+        Self {
+            record_ids,
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct BatchGetRecordsResponse {
+    #[serde(default)]
+    pub records: Vec<Record>,
+    /// Requested ids the app may not read
+    #[serde(default)]
+    pub forbidden_record_ids: Vec<String>,
+    /// Requested ids without a record
+    #[serde(default)]
+    pub absent_record_ids: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct DeletedRecord {
+    pub deleted: bool,
+    pub record_id: String,
+}
+
+/// What an upsert did with one row
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UpsertAction {
+    Created,
+    Updated,
+    /// A record with the same key and values already exists
+    Unchanged,
+    /// Not written, see [`UpsertReport::error`]
+    Failed,
+}
+
+/// Outcome of one row of [`table::BitableTableClient::upsert_records`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpsertResult {
+    pub record_id: String,
+    pub action: UpsertAction,
+}
+
+/// Outcome of [`table::BitableTableClient::upsert_records`]
+#[derive(Debug, Default, Serialize)]
+pub struct UpsertReport {
+    /// One entry per row, in input order
+    pub results: Vec<UpsertResult>,
+    /// Error of the batch that failed. Its rows and those of the later batches are
+    /// [`UpsertAction::Failed`]; the rows of the earlier batches were written.
+    #[serde(serialize_with = "error_text", skip_serializing_if = "Option::is_none")]
+    pub error: Option<LarkError>,
+}
+
+impl UpsertReport {
+    /// The results, or the error of the batch that failed
+    pub fn into_results(self) -> Result<Vec<UpsertResult>> {
+        match self.error {
+            Some(error) => Err(error),
+            None => Ok(self.results),
+        }
+    }
+}
+
+/// Outcome of [`table::BitableTableClient::batch_create_validated_records_json`] and
+/// [`table::BitableTableClient::create_typed`]
+#[derive(Debug, Default, Serialize)]
+pub struct CreateReport {
+    /// Validation of the records; nothing is created when it has errors. Empty for
+    /// [`table::BitableTableClient::create_typed`], which does not validate.
+    #[serde(flatten)]
+    pub validation: schema::ValidationReport,
+    /// Ids of the created records, in input order
+    pub record_ids: Vec<String>,
+    /// Error of the batch that failed. Neither its records nor those of the later batches
+    /// were created; the records of the earlier batches were, see `record_ids`.
+    #[serde(serialize_with = "error_text", skip_serializing_if = "Option::is_none")]
+    pub error: Option<LarkError>,
+}
+
+impl CreateReport {
+    /// Ids of the created records, or the validation errors or error of the batch that failed
+    pub fn into_record_ids(self) -> Result<Vec<String>> {
+        self.validation.into_records()?;
+        match self.error {
+            Some(error) => Err(error),
+            None => Ok(self.record_ids),
+        }
+    }
+}
+
+fn error_text<S: serde::Serializer>(
+    error: &Option<LarkError>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    match error {
+        Some(error) => serializer.collect_str(error),
+        None => serializer.serialize_none(),
+    }
+}
+
+/// `data` of the single-record endpoints
+#[derive(Debug, Deserialize, Default)]
+pub(crate) struct RecordData {
+    pub record: Record,
+}
+
+/// `data` of the batch create and update endpoints
+#[derive(Debug, Deserialize, Default)]
+pub(crate) struct RecordsData<T> {
+    #[serde(default)]
+    pub records: Vec<T>,
+}
+
 #[derive(Debug, Serialize, Deserialize, Default)]
 pub struct FieldsListResponse {
     pub items: Vec<Field>,
@@ -151,53 +350,320 @@ pub struct FieldsListResponse {
     pub total: i32,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+impl Page for FieldsListResponse {
+    type Item = Field;
+
+    fn next_page_token(&self) -> Option<&str> {
+        self.has_more.then_some(self.page_token.as_str())
+    }
+
+    fn into_items(self) -> Vec<Field> {
+        self.items
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct Field {
     pub field_name: String,
     pub field_id: String,
     #[serde(rename = "type")]
-    pub field_type: i32,
+    pub field_type: FieldType,
     #[serde(default)]
-    pub property: Value,
+    pub property: Option<FieldProperty>,
     #[serde(rename = "ui_type", default)]
-    pub ui_type: Option<String>,
-    #[serde(default)]
+    pub ui_type: Option<UiType>,
+    #[serde(default, deserialize_with = "description_text")]
     pub description: Option<String>,
     #[serde(default)]
     pub is_primary: Option<bool>,
 }
 
+/// Text of a field description, a string when listed and an object when created or updated
+fn description_text<'de, D: serde::Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<String>, D::Error> {
+    Ok(match Option::<Value>::deserialize(deserializer)? {
+        Some(Value::String(text)) => Some(text),
+        Some(Value::Object(object)) => object
+            .get("text")
+            .and_then(Value::as_str)
+            .map(str::to_string),
+        _ => None,
+    })
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct FieldInfo {
+    pub field_id: String,            // 字段ID
     pub field_name: String,          // 字段名称
     pub description: Option<String>, // 字段描述
     pub is_primary: Option<bool>,    // 是否为主键
-    pub ui_type: Option<String>,     // UI类型
-    pub write_type: Option<String>,  // 写入时传递的类型
+    pub ui_type: Option<UiType>,     // UI类型
+    pub write_type: Option<String>,  // 写入时传递的类型, 只读字段为空
+    pub example: Option<Value>,      // 写入值示例
+}
+
+impl From<Field> for FieldInfo {
+    fn from(field: Field) -> Self {
+        let (write_type, example) = match field::write_format(
+            field.field_type,
+            field.ui_type.as_ref(),
+            field.property.as_ref(),
+        ) {
+            Some((write_type, example)) => (Some(write_type.to_string()), Some(example)),
+            None => (None, None),
+        };
+
+        FieldInfo {
+            field_id: field.field_id,
+            field_name: field.field_name,
+            description: field.description,
+            is_primary: field.is_primary,
+            ui_type: field.ui_type,
+            write_type,
+            example,
+        }
+    }
 }
 
 impl From<FieldsListResponse> for Vec<FieldInfo> {
     fn from(response: FieldsListResponse) -> Self {
-        response
-            .items
-            .into_iter()
-            .map(|field| {
-                let write_type = match field.ui_type.as_deref() {
-                    Some("Text") => Some("String".to_string()),
-                    Some("SingleSelect") => Some("String".to_string()),
-                    Some("DateTime") => Some("Timestamp".to_string()),
-                    Some("MultiSelect") => Some("Array<String>".to_string()),
-                    _ => Some("String".to_string()),
-                };
-
-                FieldInfo {
-                    field_name: field.field_name,
-                    description: field.description,
-                    is_primary: field.is_primary,
-                    ui_type: field.ui_type,
-                    write_type,
-                }
-            })
-            .collect()
+        response.items.into_iter().map(FieldInfo::from).collect()
+    }
+}
+
+/// Definition of a field to create, or the new definition of a field to update
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FieldCreate {
+    pub field_name: String,
+    #[serde(rename = "type")]
+    pub field_type: FieldType,
+    /// Needed to tell apart fields sharing a type, e.g. currency and number fields
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ui_type: Option<UiType>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub property: Option<FieldProperty>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<FieldDescription>,
+}
+
+impl FieldCreate {
+    pub fn new(field_name: impl Into<String>, field_type: FieldType) -> Self {
+        Self {
+            field_name: field_name.into(),
+            field_type,
+            ui_type: None,
+            property: None,
+            description: None,
+        }
+    }
+
+    pub fn with_ui_type(mut self, ui_type: UiType) -> Self {
+        self.ui_type = Some(ui_type);
+        self
+    }
+
+    pub fn with_property(mut self, property: FieldProperty) -> Self {
+        self.property = Some(property);
+        self
+    }
+
+    pub fn with_description(mut self, text: impl Into<String>) -> Self {
+        self.description = Some(FieldDescription {
+            text: text.into(),
+            disable_sync: None,
+        });
+        self
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FieldDescription {
+    pub text: String,
+    /// Whether the description is kept out of the form view
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub disable_sync: Option<bool>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct DeletedField {
+    pub deleted: bool,
+    pub field_id: String,
+}
+
+/// `data` of the field create and update endpoints
+#[derive(Debug, Deserialize, Default)]
+pub(crate) struct FieldData {
+    pub field: Field,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct Table {
+    pub table_id: String,
+    pub name: String,
+    #[serde(default)]
+    pub revision: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct TablesListResponse {
+    pub items: Vec<Table>,
+    #[serde(default)]
+    pub page_token: Option<String>,
+    #[serde(default)]
+    pub has_more: bool,
+    #[serde(default)]
+    pub total: i32,
+}
+
+impl Page for TablesListResponse {
+    type Item = Table;
+
+    fn next_page_token(&self) -> Option<&str> {
+        self.page_token.as_deref().filter(|_| self.has_more)
+    }
+
+    fn into_items(self) -> Vec<Table> {
+        self.items
     }
 }
+
+/// A table to create with its fields; the first field becomes the primary field
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct TableCreate {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_view_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fields: Vec<FieldCreate>,
+}
+
+impl TableCreate {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            ..Default::default()
+        }
+    }
+
+    pub fn with_default_view_name(mut self, name: impl Into<String>) -> Self {
+        self.default_view_name = Some(name.into());
+        self
+    }
+
+    pub fn with_field(mut self, field: FieldCreate) -> Self {
+        self.fields.push(field);
+        self
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct CreatedTable {
+    pub table_id: String,
+    #[serde(default)]
+    pub default_view_id: Option<String>,
+    /// Ids of the fields, in the order of [`TableCreate::fields`]
+    #[serde(default)]
+    pub field_id_list: Vec<String>,
+}
+
+/// `data` of the batch create tables endpoint
+#[derive(Debug, Deserialize, Default)]
+pub(crate) struct TableIdsData {
+    #[serde(default)]
+    pub table_ids: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ViewType {
+    #[default]
+    Grid,
+    Kanban,
+    Gallery,
+    Gantt,
+    Form,
+    #[serde(other)]
+    Unknown,
+}
+
+impl std::str::FromStr for ViewType {
+    type Err = LarkError;
+
+    /// Parse the name of a view type that can be created; unlike decoding, `Unknown` is
+    /// never returned
+    fn from_str(name: &str) -> Result<Self> {
+        match name {
+            "grid" => Ok(Self::Grid),
+            "kanban" => Ok(Self::Kanban),
+            "gallery" => Ok(Self::Gallery),
+            "gantt" => Ok(Self::Gantt),
+            "form" => Ok(Self::Form),
+            _ => Err(LarkError::InvalidArgument(format!(
+                "unknown view type {}, expected one of: grid, kanban, gallery, gantt, form",
+                name
+            ))),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct View {
+    pub view_id: String,
+    pub view_name: String,
+    pub view_type: ViewType,
+    /// Filter, hidden fields and other settings of the view
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub property: Option<Value>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct ViewsListResponse {
+    pub items: Vec<View>,
+    #[serde(default)]
+    pub page_token: Option<String>,
+    #[serde(default)]
+    pub has_more: bool,
+    #[serde(default)]
+    pub total: i32,
+}
+
+impl Page for ViewsListResponse {
+    type Item = View;
+
+    fn next_page_token(&self) -> Option<&str> {
+        self.page_token.as_deref().filter(|_| self.has_more)
+    }
+
+    fn into_items(self) -> Vec<View> {
+        self.items
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ViewCreate {
+    pub view_name: String,
+    /// Grid when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub view_type: Option<ViewType>,
+}
+
+impl ViewCreate {
+    pub fn new(view_name: impl Into<String>) -> Self {
+        Self {
+            view_name: view_name.into(),
+            view_type: None,
+        }
+    }
+
+    pub fn with_view_type(mut self, view_type: ViewType) -> Self {
+        self.view_type = Some(view_type);
+        self
+    }
+}
+
+/// `data` of the single-view endpoints
+#[derive(Debug, Deserialize, Default)]
+pub(crate) struct ViewData {
+    pub view: View,
+}
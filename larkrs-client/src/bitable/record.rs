@@ -0,0 +1,369 @@
+//! Mapping Rust types to Bitable records.
+//!
+//! A [`BitableRecord`] converts a struct to the fields of a record and back, so that
+//! [`super::table::BitableTableClient::search_typed`] and
+//! [`super::table::BitableTableClient::create_typed`] need no hand-built JSON. With the
+//! `derive` feature it is derived, each struct field going through [`BitableValue`]:
+//!
+//! ```rust
+//! # #[cfg(feature = "derive")] {
+//! use larkrs_client::bitable::{BitableRecord, BitableSelect, value::Person};
+//!
+//! #[derive(BitableSelect)]
+//! enum Tier {
+//!     #[bitable(option = "四板")]
+//!     Fourth,
+//!     #[bitable(option = "五板")]
+//!     Fifth,
+//! }
+//!
+//! #[derive(BitableRecord)]
+//! struct Stock {
+//!     #[bitable(record_id)]
+//!     id: String,
+//!     #[bitable(field = "股票名称")]
+//!     name: String,
+//!     #[bitable(field = "日期")]
+//!     date: chrono::NaiveDate,
+//!     #[bitable(field = "梯队")]
+//!     tiers: Vec<Tier>,
+//!     #[bitable(field = "负责人")]
+//!     owners: Vec<Person>,
+//!     #[bitable(field = "备注")]
+//!     note: Option<String>,
+//! }
+//! # }
+//! ```
+//!
+//! | Rust type                                   | Bitable field                        |
+//! |---------------------------------------------|--------------------------------------|
+//! | `String`                                    | text, single select, phone, barcode  |
+//! | integers, `f64`                             | number, currency, progress, rating   |
+//! | `bool`                                      | checkbox                             |
+//! | `chrono::DateTime<Utc>`, `chrono::NaiveDate`| date, the latter in [`super::schema::DEFAULT_TIMEZONE`] |
+//! | `Vec<String>`                               | multi select, link (record ids)      |
+//! | enums deriving `BitableSelect`, in a `Vec` for multi select | select with fixed options |
+//! | `Vec<Person>`, `Vec<Group>`                 | person, group                        |
+//! | `Vec<Attachment>`, `Url`, `Location`        | attachment, URL, location            |
+//! | `Option<T>`                                 | any of the above, `None` when empty  |
+//! | [`FieldValue`], `serde_json::Value`         | anything                             |
+//!
+//! Lark leaves empty cells out of records; an absent cell reads as an empty string or
+//! list, `false` or `None`, and fails for numbers and dates. `None` values are not written.
+
+use chrono::{DateTime, NaiveDate, TimeZone, Utc};
+use std::collections::HashMap;
+
+pub use serde_json::Value;
+
+use super::Record;
+use super::schema::DEFAULT_TIMEZONE;
+use super::value::{Attachment, FieldValue, Group, Location, Person, Url};
+use crate::error::{LarkError, Result};
+
+/// A type stored as one Bitable record, usually derived, see the [module documentation](self)
+pub trait BitableRecord: Sized {
+    /// Bitable fields read and written, e.g. to limit the fields a search returns
+    fn field_names() -> Vec<&'static str>;
+
+    /// Fields to write
+    fn to_fields(&self) -> HashMap<String, Value>;
+
+    fn from_record(record: &Record) -> Result<Self>;
+}
+
+/// A type stored in one Bitable cell
+pub trait BitableValue: Sized {
+    fn to_value(&self) -> Value;
+
+    /// Read a cell, [`Value::Null`] if it is empty
+    fn from_value(value: &Value) -> Result<Self, String>;
+}
+
+/// Read the field `name` of `record`, used by derived [`BitableRecord`]s
+#[doc(hidden)]
+pub fn read_field<T: BitableValue>(record: &Record, name: &str) -> Result<T> {
+    let value = record.fields.get(name).unwrap_or(&Value::Null);
+    T::from_value(value).map_err(|message| {
+        LarkError::InvalidArgument(format!(
+            "record {}, field {}: {}",
+            record.record_id, name, message
+        ))
+    })
+}
+
+/// The value inside formula and lookup results and one-element arrays
+fn scalar(value: &Value) -> &Value {
+    match value {
+        Value::Object(object) if object.contains_key("type") && object.contains_key("value") => {
+            scalar(&object["value"])
+        }
+        Value::Array(items) if items.len() == 1 => scalar(&items[0]),
+        other => other,
+    }
+}
+
+fn expected(what: &str, value: &Value) -> String {
+    format!("expected {}, got {}", what, value)
+}
+
+impl BitableValue for String {
+    fn to_value(&self) -> Value {
+        Value::String(self.clone())
+    }
+
+    fn from_value(value: &Value) -> Result<Self, String> {
+        match value {
+            Value::Null => Ok(String::new()),
+            Value::String(text) => Ok(text.clone()),
+            // A single rich text segment
+            Value::Object(object) if let Some(Value::String(text)) = object.get("text") => {
+                Ok(text.clone())
+            }
+            other => FieldValue::from(other.clone())
+                .text()
+                .ok_or_else(|| expected("text", value)),
+        }
+    }
+}
+
+macro_rules! impl_number {
+    ($($ty:ty => $as:ident),*) => {$(
+        impl BitableValue for $ty {
+            fn to_value(&self) -> Value {
+                Value::from(*self)
+            }
+
+            fn from_value(value: &Value) -> Result<Self, String> {
+                let number = match scalar(value) {
+                    Value::Number(number) => number.$as().and_then(|n| <$ty>::try_from(n).ok()),
+                    Value::String(text) => text.trim().parse().ok(),
+                    _ => None,
+                };
+                number.ok_or_else(|| expected(stringify!($ty), value))
+            }
+        }
+    )*};
+}
+
+impl_number!(i64 => as_i64, i32 => as_i64, u64 => as_u64, u32 => as_u64);
+
+impl BitableValue for f64 {
+    fn to_value(&self) -> Value {
+        Value::from(*self)
+    }
+
+    fn from_value(value: &Value) -> Result<Self, String> {
+        let number = match scalar(value) {
+            Value::Number(number) => number.as_f64(),
+            Value::String(text) => text.trim().parse().ok(),
+            _ => None,
+        };
+        number.ok_or_else(|| expected("a number", value))
+    }
+}
+
+impl BitableValue for bool {
+    fn to_value(&self) -> Value {
+        Value::Bool(*self)
+    }
+
+    fn from_value(value: &Value) -> Result<Self, String> {
+        match scalar(value) {
+            Value::Null => Ok(false),
+            Value::Bool(checked) => Ok(*checked),
+            _ => Err(expected("true or false", value)),
+        }
+    }
+}
+
+impl BitableValue for DateTime<Utc> {
+    fn to_value(&self) -> Value {
+        Value::from(self.timestamp_millis())
+    }
+
+    fn from_value(value: &Value) -> Result<Self, String> {
+        scalar(value)
+            .as_i64()
+            .and_then(DateTime::from_timestamp_millis)
+            .ok_or_else(|| expected("a timestamp in milliseconds", value))
+    }
+}
+
+impl BitableValue for NaiveDate {
+    fn to_value(&self) -> Value {
+        let midnight = self.and_hms_opt(0, 0, 0).unwrap_or_default();
+        DEFAULT_TIMEZONE
+            .from_local_datetime(&midnight)
+            .earliest()
+            .map_or(Value::Null, |datetime| {
+                Value::from(datetime.timestamp_millis())
+            })
+    }
+
+    fn from_value(value: &Value) -> Result<Self, String> {
+        let datetime = DateTime::<Utc>::from_value(value)?;
+        Ok(datetime.with_timezone(&DEFAULT_TIMEZONE).date_naive())
+    }
+}
+
+impl<T: BitableValue> BitableValue for Option<T> {
+    fn to_value(&self) -> Value {
+        self.as_ref().map_or(Value::Null, T::to_value)
+    }
+
+    fn from_value(value: &Value) -> Result<Self, String> {
+        match value {
+            Value::Null => Ok(None),
+            Value::Array(items) if items.is_empty() => Ok(None),
+            other => T::from_value(other).map(Some),
+        }
+    }
+}
+
+impl<T: BitableValue> BitableValue for Vec<T> {
+    fn to_value(&self) -> Value {
+        Value::Array(self.iter().map(T::to_value).collect())
+    }
+
+    fn from_value(value: &Value) -> Result<Self, String> {
+        match value {
+            Value::Null => Ok(Vec::new()),
+            Value::Array(items) => items.iter().map(T::from_value).collect(),
+            // Links are read back as {"link_record_ids": [...]}
+            Value::Object(object) if let Some(ids) = object.get("link_record_ids") => {
+                Self::from_value(ids)
+            }
+            // A single select read into a list
+            other => T::from_value(other).map(|item| vec![item]),
+        }
+    }
+}
+
+/// Types written and read as their serde form
+macro_rules! impl_serde {
+    ($($ty:ty),*) => {$(
+        impl BitableValue for $ty {
+            fn to_value(&self) -> Value {
+                serde_json::to_value(self).unwrap_or_default()
+            }
+
+            fn from_value(value: &Value) -> Result<Self, String> {
+                serde_json::from_value(value.clone()).map_err(|e| e.to_string())
+            }
+        }
+    )*};
+}
+
+impl_serde!(Person, Group, Attachment, Url);
+
+impl BitableValue for Location {
+    /// Locations are written as `longitude,latitude`
+    fn to_value(&self) -> Value {
+        Value::String(self.location.clone())
+    }
+
+    fn from_value(value: &Value) -> Result<Self, String> {
+        match value {
+            Value::String(location) => Ok(Location {
+                location: location.clone(),
+                pname: None,
+                cityname: None,
+                adname: None,
+                address: None,
+                name: None,
+                full_address: None,
+            }),
+            other => serde_json::from_value(other.clone()).map_err(|e| e.to_string()),
+        }
+    }
+}
+
+impl BitableValue for FieldValue {
+    fn to_value(&self) -> Value {
+        self.to_write_value()
+    }
+
+    fn from_value(value: &Value) -> Result<Self, String> {
+        Ok(FieldValue::from(value.clone()))
+    }
+}
+
+impl BitableValue for Value {
+    fn to_value(&self) -> Value {
+        self.clone()
+    }
+
+    fn from_value(value: &Value) -> Result<Self, String> {
+        Ok(value.clone())
+    }
+}
+
+#[cfg(all(test, feature = "derive"))]
+mod tests {
+    use super::*;
+    use crate::bitable::{BitableRecord, BitableSelect};
+    use serde_json::json;
+
+    #[derive(Debug, PartialEq, BitableSelect)]
+    enum Tier {
+        #[bitable(option = "四板")]
+        Fourth,
+        #[bitable(option = "五板")]
+        Fifth,
+    }
+
+    #[derive(Debug, PartialEq, BitableRecord)]
+    struct Stock {
+        #[bitable(record_id)]
+        id: String,
+        #[bitable(field = "股票名称")]
+        name: String,
+        #[bitable(field = "日期")]
+        date: NaiveDate,
+        #[bitable(field = "梯队")]
+        tiers: Vec<Tier>,
+        #[bitable(field = "负责人")]
+        owners: Vec<Person>,
+        #[bitable(field = "涨幅")]
+        change: Option<f64>,
+        #[bitable(skip)]
+        cached: bool,
+    }
+
+    #[test]
+    fn test_derived_record() {
+        assert_eq!(
+            Stock::field_names(),
+            ["股票名称", "日期", "梯队", "负责人", "涨幅"]
+        );
+
+        let record: Record = serde_json::from_value(json!({
+            "record_id": "rec1",
+            "fields": {
+                "股票名称": [{"type": "text", "text": "太阳电缆"}],
+                "日期": 1742918400000i64,
+                "梯队": ["四板", "五板"],
+                "负责人": [{"id": "ou_1", "name": "Ann"}]
+            }
+        }))
+        .unwrap();
+        let stock = Stock::from_record(&record).unwrap();
+        assert_eq!(stock.id, "rec1");
+        assert_eq!(stock.name, "太阳电缆");
+        assert_eq!(stock.date, NaiveDate::from_ymd_opt(2025, 3, 26).unwrap());
+        assert_eq!(stock.tiers, [Tier::Fourth, Tier::Fifth]);
+        assert_eq!(stock.owners[0].id, "ou_1");
+        assert_eq!(stock.change, None);
+
+        let fields = stock.to_fields();
+        assert_eq!(fields["日期"], 1742918400000i64);
+        assert_eq!(fields["梯队"], json!(["四板", "五板"]));
+        assert!(!fields.contains_key("涨幅"));
+
+        let mut record = record;
+        record.fields.insert("梯队".to_string(), json!(["六板"]));
+        let err = Stock::from_record(&record).unwrap_err();
+        assert!(matches!(err, LarkError::InvalidArgument(msg) if msg.contains("field 梯队")));
+    }
+}
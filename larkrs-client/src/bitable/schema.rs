@@ -0,0 +1,520 @@
+//! Checking records against the fields of a table before writing them.
+//!
+//! [`TableSchema::validate`] rejects unknown and read-only fields, checks select options and
+//! coerces values into the shape each field type expects, so that a bad cell is reported
+//! with its row and field instead of failing the whole batch with one Lark error:
+//!
+//! | Field type         | Accepted besides the native shape                               |
+//! |--------------------|-----------------------------------------------------------------|
+//! | Number             | numeric strings                                                 |
+//! | DateTime           | `2025-03-26`, `20250326`, `2025-03-26 09:30[:00]`, optionally followed by a time zone such as `Asia/Shanghai`, RFC 3339, 13 digit milliseconds and `20250326` also as numbers |
+//! | Checkbox           | `true`/`false`, `yes`/`no`, `1`/`0`, `是`/`否`                  |
+//! | MultiSelect        | comma separated strings                                         |
+//! | User, GroupChat    | ids, alone or in an array                                       |
+//! | Link               | record ids, alone, comma separated or in an array               |
+//! | Url                | a plain link                                                    |
+//! | Attachment         | file tokens, alone or in an array                               |
+//! | Text               | numbers and booleans                                            |
+//! | Phone              | numbers                                                         |
+//! | Location           | `{"location": "lng,lat", ...}` as read back                     |
+
+use chrono::{DateTime, NaiveDate, NaiveDateTime, TimeZone};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::fmt;
+
+use super::{Field, FieldType, RecordCreate};
+use crate::error::{LarkError, Result};
+
+/// Time zone of dates written without one, the default of Feishu tenants
+pub const DEFAULT_TIMEZONE: Tz = chrono_tz::Asia::Shanghai;
+
+/// Fields of a table, by name
+#[derive(Debug, Clone)]
+pub struct TableSchema {
+    fields: HashMap<String, Field>,
+    timezone: Tz,
+}
+
+/// One rejected cell
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FieldError {
+    /// Index of the record in the input
+    pub row: usize,
+    pub field: String,
+    pub message: String,
+}
+
+/// Outcome of [`TableSchema::validate`]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ValidationReport {
+    /// The records with coerced values, in input order
+    pub records: Vec<RecordCreate>,
+    pub errors: Vec<FieldError>,
+}
+
+impl ValidationReport {
+    pub fn is_valid(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    /// The coerced records, or an [`LarkError::InvalidArgument`] listing every error
+    pub fn into_records(self) -> Result<Vec<RecordCreate>> {
+        if self.is_valid() {
+            Ok(self.records)
+        } else {
+            Err(LarkError::InvalidArgument(self.to_string()))
+        }
+    }
+}
+
+impl fmt::Display for ValidationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let errors: Vec<String> = self
+            .errors
+            .iter()
+            .map(|e| format!("row {}, field {}: {}", e.row, e.field, e.message))
+            .collect();
+        write!(f, "{}", errors.join("; "))
+    }
+}
+
+impl TableSchema {
+    pub fn new(fields: Vec<Field>) -> Self {
+        Self {
+            fields: fields
+                .into_iter()
+                .map(|field| (field.field_name.clone(), field))
+                .collect(),
+            timezone: DEFAULT_TIMEZONE,
+        }
+    }
+
+    /// Time zone of dates written without one (default: [`DEFAULT_TIMEZONE`])
+    pub fn with_timezone(mut self, timezone: Tz) -> Self {
+        self.timezone = timezone;
+        self
+    }
+
+    pub fn field(&self, name: &str) -> Option<&Field> {
+        self.fields.get(name)
+    }
+
+    /// Check and coerce every cell of `records`
+    pub fn validate(&self, records: Vec<HashMap<String, Value>>) -> ValidationReport {
+        let mut report = ValidationReport::default();
+        for (row, fields) in records.into_iter().enumerate() {
+            let mut record = RecordCreate::new();
+            // Report the fields of a row in a stable order
+            let mut fields: Vec<_> = fields.into_iter().collect();
+            fields.sort_by(|a, b| a.0.cmp(&b.0));
+
+            for (name, value) in fields {
+                let coerced = match self.fields.get(&name) {
+                    Some(field) => self.coerce(field, value),
+                    None => Err("unknown field".to_string()),
+                };
+                match coerced {
+                    Ok(value) => {
+                        record.fields.insert(name, value);
+                    }
+                    Err(message) => report.errors.push(FieldError {
+                        row,
+                        field: name,
+                        message,
+                    }),
+                }
+            }
+            report.records.push(record);
+        }
+        report
+    }
+
+    /// Coerce `value` into the shape `field` expects, or explain why it cannot be written
+    pub fn coerce(&self, field: &Field, value: Value) -> Result<Value, String> {
+        if value.is_null() {
+            return Ok(value);
+        }
+        if field.field_type.is_read_only() {
+            return Err("field is read-only".to_string());
+        }
+
+        match field.field_type {
+            FieldType::Text => match value {
+                Value::Number(_) | Value::Bool(_) => Ok(Value::String(value.to_string())),
+                // Text fields also take rich text segments
+                Value::String(_) | Value::Array(_) => Ok(value),
+                _ => Err(expected("a string", &value)),
+            },
+            FieldType::Phone => match value {
+                Value::Number(_) => Ok(Value::String(value.to_string())),
+                Value::String(_) => Ok(value),
+                _ => Err(expected("a phone number", &value)),
+            },
+            FieldType::Location => {
+                // Locations are read back as {"location": "lng,lat", "address": ...}
+                let location = value.get("location").unwrap_or(&value);
+                location
+                    .as_str()
+                    .and_then(parse_location)
+                    .map(Value::String)
+                    .ok_or_else(|| expected("a \"longitude,latitude\" location", &value))
+            }
+            FieldType::Number => match &value {
+                Value::Number(_) => Ok(value),
+                Value::String(text) => parse_number(text)
+                    .map(Value::Number)
+                    .ok_or_else(|| expected("a number", &value)),
+                _ => Err(expected("a number", &value)),
+            },
+            FieldType::SingleSelect => match value {
+                Value::String(option) => {
+                    self.check_option(field, &option)?;
+                    Ok(Value::String(option))
+                }
+                _ => Err(expected("an option name", &value)),
+            },
+            FieldType::MultiSelect => {
+                let options =
+                    string_list(&value).ok_or_else(|| expected("option names", &value))?;
+                for option in &options {
+                    self.check_option(field, option)?;
+                }
+                Ok(json!(options))
+            }
+            FieldType::DateTime => {
+                // Numbers follow the rule of digit strings, so that 20250326 is not in 1970
+                let text = match &value {
+                    Value::Number(number) if number.is_u64() => number.to_string(),
+                    Value::String(text) => text.clone(),
+                    _ => return Err(expected("a date", &value)),
+                };
+                parse_datetime(&text, self.timezone)
+                    .map(Value::from)
+                    .ok_or_else(|| expected("a date", &value))
+            }
+            FieldType::Checkbox => match &value {
+                Value::Bool(_) => Ok(value),
+                Value::Number(n) if n.as_i64() == Some(0) || n.as_i64() == Some(1) => {
+                    Ok(Value::Bool(n.as_i64() == Some(1)))
+                }
+                Value::String(text) => parse_bool(text)
+                    .map(Value::Bool)
+                    .ok_or_else(|| expected("true or false", &value)),
+                _ => Err(expected("true or false", &value)),
+            },
+            FieldType::User | FieldType::GroupChat => {
+                objects_with(&value, "id").ok_or_else(|| expected("ids", &value))
+            }
+            FieldType::Attachment => {
+                objects_with(&value, "file_token").ok_or_else(|| expected("file tokens", &value))
+            }
+            FieldType::SingleLink | FieldType::DuplexLink => {
+                // Links are read back as {"link_record_ids": [...]}
+                let ids = value.get("link_record_ids").unwrap_or(&value);
+                string_list(ids)
+                    .map(|ids| json!(ids))
+                    .ok_or_else(|| expected("record ids", &value))
+            }
+            FieldType::Url => match &value {
+                Value::String(link) => Ok(json!({"link": link, "text": link})),
+                Value::Object(object) if object.contains_key("link") => Ok(value),
+                _ => Err(expected("a link", &value)),
+            },
+            _ => Ok(value),
+        }
+    }
+
+    fn check_option(&self, field: &Field, option: &str) -> Result<(), String> {
+        let options = field
+            .property
+            .as_ref()
+            .and_then(|property| property.options.as_ref())
+            .filter(|options| !options.is_empty());
+        match options {
+            Some(options) if !options.iter().any(|o| o.name == option) => Err(format!(
+                "unknown option {}, expected one of: {}",
+                option,
+                options
+                    .iter()
+                    .map(|o| o.name.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )),
+            _ => Ok(()),
+        }
+    }
+}
+
+fn expected(what: &str, value: &Value) -> String {
+    format!("expected {}, got {}", what, value)
+}
+
+fn parse_number(text: &str) -> Option<serde_json::Number> {
+    let text = text.trim();
+    match text.parse::<i64>() {
+        Ok(number) => Some(number.into()),
+        Err(_) => text
+            .parse::<f64>()
+            .ok()
+            .and_then(serde_json::Number::from_f64),
+    }
+}
+
+fn parse_bool(text: &str) -> Option<bool> {
+    match text.trim().to_lowercase().as_str() {
+        "true" | "yes" | "y" | "1" | "是" => Some(true),
+        "false" | "no" | "n" | "0" | "否" => Some(false),
+        _ => None,
+    }
+}
+
+/// Milliseconds since the Unix epoch of a date written as text, in `timezone` unless the
+/// text names a zone or an offset
+fn parse_datetime(text: &str, timezone: Tz) -> Option<i64> {
+    let text = text.trim();
+    // Digits are a compact date such as 20250326 or a 13 digit timestamp, anything else
+    // is more likely a mistake than a date in 1970
+    if !text.is_empty() && text.bytes().all(|byte| byte.is_ascii_digit()) {
+        return match text.len() {
+            8 => NaiveDate::parse_from_str(text, "%Y%m%d")
+                .ok()
+                .and_then(|date| date.and_hms_opt(0, 0, 0))
+                .and_then(|naive| timezone.from_local_datetime(&naive).earliest())
+                .map(|datetime| datetime.timestamp_millis()),
+            13 => text.parse().ok(),
+            _ => None,
+        };
+    }
+    if let Ok(datetime) = DateTime::parse_from_rfc3339(text) {
+        return Some(datetime.timestamp_millis());
+    }
+
+    let (text, timezone) = match text.rsplit_once(' ') {
+        Some((rest, zone)) if let Ok(zone) = zone.parse::<Tz>() => (rest.trim_end(), zone),
+        _ => (text, timezone),
+    };
+    let naive = [
+        "%Y-%m-%d %H:%M:%S",
+        "%Y-%m-%d %H:%M",
+        "%Y-%m-%dT%H:%M:%S",
+        "%Y-%m-%dT%H:%M",
+        "%Y/%m/%d %H:%M:%S",
+        "%Y/%m/%d %H:%M",
+    ]
+    .iter()
+    .find_map(|format| NaiveDateTime::parse_from_str(text, format).ok())
+    .or_else(|| {
+        ["%Y-%m-%d", "%Y/%m/%d"]
+            .iter()
+            .find_map(|format| NaiveDate::parse_from_str(text, format).ok())
+            .and_then(|date| date.and_hms_opt(0, 0, 0))
+    })?;
+
+    timezone
+        .from_local_datetime(&naive)
+        .earliest()
+        .map(|datetime| datetime.timestamp_millis())
+}
+
+/// `longitude,latitude` of a location written as two comma separated numbers
+fn parse_location(text: &str) -> Option<String> {
+    let (longitude, latitude) = text.split_once(',')?;
+    let (longitude, latitude) = (longitude.trim(), latitude.trim());
+    longitude.parse::<f64>().ok()?;
+    latitude.parse::<f64>().ok()?;
+    Some(format!("{},{}", longitude, latitude))
+}
+
+/// Strings of an array, or the comma separated parts of a string
+fn string_list(value: &Value) -> Option<Vec<String>> {
+    match value {
+        Value::String(text) => Some(
+            text.split([',', '，'])
+                .map(str::trim)
+                .filter(|part| !part.is_empty())
+                .map(str::to_string)
+                .collect(),
+        ),
+        Value::Array(items) => items
+            .iter()
+            .map(|item| item.as_str().map(str::to_string))
+            .collect(),
+        _ => None,
+    }
+}
+
+/// Array of objects holding `key`, built from bare ids if needed
+fn objects_with(value: &Value, key: &str) -> Option<Value> {
+    let items = match value {
+        Value::Array(items) => items.clone(),
+        other => vec![other.clone()],
+    };
+    items
+        .into_iter()
+        .map(|item| match item {
+            Value::String(id) => Some(json!({ key: id })),
+            Value::Object(object) if object.contains_key(key) => Some(Value::Object(object)),
+            _ => None,
+        })
+        .collect::<Option<Vec<_>>>()
+        .map(Value::Array)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn schema() -> TableSchema {
+        let fields = json!([
+            {"field_name": "名称", "field_id": "f1", "type": 1},
+            {"field_name": "数量", "field_id": "f2", "type": 2},
+            {"field_name": "日期", "field_id": "f3", "type": 5},
+            {"field_name": "完成", "field_id": "f4", "type": 7},
+            {"field_name": "梯队", "field_id": "f5", "type": 4,
+             "property": {"options": [{"name": "四板"}, {"name": "五板"}]}},
+            {"field_name": "负责人", "field_id": "f6", "type": 11},
+            {"field_name": "关联", "field_id": "f7", "type": 18},
+            {"field_name": "编号", "field_id": "f8", "type": 1005},
+            {"field_name": "电话", "field_id": "f9", "type": 13},
+            {"field_name": "位置", "field_id": "f10", "type": 22}
+        ]);
+        TableSchema::new(serde_json::from_value(fields).unwrap())
+    }
+
+    #[test]
+    fn test_coercion() {
+        let row = json!({
+            "名称": 600000,
+            "数量": "12.5",
+            "日期": "2025-03-26",
+            "完成": "是",
+            "梯队": "四板, 五板",
+            "负责人": "ou_1",
+            "关联": {"link_record_ids": ["rec1"]}
+        });
+        let report = schema().validate(vec![serde_json::from_value(row).unwrap()]);
+        assert!(report.is_valid(), "{}", report);
+
+        let fields = &report.records[0].fields;
+        assert_eq!(fields["名称"], "600000");
+        assert_eq!(fields["数量"], 12.5);
+        // Midnight in Asia/Shanghai
+        assert_eq!(fields["日期"], 1742918400000i64);
+        assert_eq!(fields["完成"], true);
+        assert_eq!(fields["梯队"], json!(["四板", "五板"]));
+        assert_eq!(fields["负责人"], json!([{"id": "ou_1"}]));
+        assert_eq!(fields["关联"], json!(["rec1"]));
+
+        let rows = vec![
+            serde_json::from_value(json!({"日期": 20250326})).unwrap(),
+            serde_json::from_value(json!({"日期": 1742952600000i64})).unwrap(),
+        ];
+        let report = schema().validate(rows);
+        assert!(report.is_valid(), "{}", report);
+        assert_eq!(report.records[0].fields["日期"], 1742918400000i64);
+        assert_eq!(report.records[1].fields["日期"], 1742952600000i64);
+    }
+
+    #[test]
+    fn test_phone_and_location() {
+        let rows = vec![
+            serde_json::from_value(
+                json!({"电话": 13800138000u64, "位置": "116.397755, 39.903179"}),
+            )
+            .unwrap(),
+            // A location as read back from Lark
+            serde_json::from_value(json!({
+                "电话": "+86 138 0013 8000",
+                "位置": {"location": "116.397755,39.903179", "address": "天安门"}
+            }))
+            .unwrap(),
+        ];
+        let report = schema().validate(rows);
+        assert!(report.is_valid(), "{}", report);
+        assert_eq!(report.records[0].fields["电话"], "13800138000");
+        assert_eq!(report.records[0].fields["位置"], "116.397755,39.903179");
+        assert_eq!(report.records[1].fields["电话"], "+86 138 0013 8000");
+        assert_eq!(report.records[1].fields["位置"], "116.397755,39.903179");
+
+        let rows = vec![
+            serde_json::from_value(json!({"电话": true, "位置": 42})).unwrap(),
+            serde_json::from_value(json!({"电话": ["138"], "位置": ["116.4", "39.9"]})).unwrap(),
+            serde_json::from_value(json!({"位置": "天安门"})).unwrap(),
+            serde_json::from_value(json!({"位置": {"location": "116.4"}})).unwrap(),
+        ];
+        let report = schema().validate(rows);
+        let errors: Vec<_> = report
+            .errors
+            .iter()
+            .map(|e| (e.row, e.field.as_str()))
+            .collect();
+        assert_eq!(
+            errors,
+            [
+                (0, "位置"),
+                (0, "电话"),
+                (1, "位置"),
+                (1, "电话"),
+                (2, "位置"),
+                (3, "位置")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_datetime_formats() {
+        let utc = chrono_tz::UTC;
+        assert_eq!(
+            parse_datetime("2025-03-26 09:30 Asia/Shanghai", utc),
+            Some(1742952600000)
+        );
+        assert_eq!(
+            parse_datetime("2025-03-26T01:30:00Z", DEFAULT_TIMEZONE),
+            Some(1742952600000)
+        );
+        assert_eq!(parse_datetime("2025/03/26 01:30", utc), Some(1742952600000));
+        assert_eq!(parse_datetime("1742952600000", utc), Some(1742952600000));
+        assert_eq!(
+            parse_datetime("20250326", DEFAULT_TIMEZONE),
+            Some(1742918400000)
+        );
+        assert_eq!(parse_datetime("20251326", utc), None);
+        assert_eq!(parse_datetime("1742952600", utc), None);
+        assert_eq!(parse_datetime("next tuesday", utc), None);
+    }
+
+    #[test]
+    fn test_error_report() {
+        let rows = vec![
+            serde_json::from_value(json!({"名称": "a", "颜色": "red"})).unwrap(),
+            serde_json::from_value(json!({"数量": "many", "梯队": ["六板"], "编号": "1"})).unwrap(),
+            // Seconds rather than milliseconds
+            serde_json::from_value(json!({"日期": 1742952600})).unwrap(),
+        ];
+        let report = schema().validate(rows);
+        let errors: Vec<_> = report
+            .errors
+            .iter()
+            .map(|e| (e.row, e.field.as_str()))
+            .collect();
+        assert_eq!(
+            errors,
+            [
+                (0, "颜色"),
+                (1, "数量"),
+                (1, "梯队"),
+                (1, "编号"),
+                (2, "日期")
+            ]
+        );
+        assert!(report.errors[2].message.contains("四板, 五板"));
+        assert!(matches!(
+            report.into_records(),
+            Err(LarkError::InvalidArgument(msg)) if msg.contains("row 1, field 编号: field is read-only")
+        ));
+    }
+}
@@ -1,39 +1,49 @@
-#![allow(dead_code)]
-
-use crate::LarkApiResponse;
-use crate::auth::FeishuTokenManager;
-use crate::bitable::{FieldsListResponse, SearchRecordsResponse};
-use anyhow::{Result, anyhow};
-use reqwest::Client;
-use serde_json::Value;
-use thiserror::Error;
+use crate::bitable::{
+    BatchDeleteRecordsRequest, BatchGetRecordsRequest, BatchGetRecordsResponse,
+    BatchUpdateRecordsRequest, BitableRecord, CreateReport, CreatedTable, DeletedField,
+    DeletedRecord, Field, FieldCreate, FieldData, FieldType, FieldsListResponse, Filter,
+    FilterCondition, FilterConjunction, FilterOperator, Record, RecordCreate, RecordData,
+    RecordUpdate, RecordsData, SearchRecordsCond, SearchRecordsResponse, Table, TableCreate,
+    TableIdsData, TablesListResponse, UpsertAction, UpsertReport, UpsertResult, View, ViewCreate,
+    ViewData, ViewType, ViewsListResponse,
+};
+use crate::client::LarkClient;
+use crate::error::{LarkError, Result};
+use crate::http::ApiRequest;
+use crate::page::Paginator;
+use serde_json::{Value, json};
+use std::collections::{HashMap, HashSet};
 
 use super::BatchCreateRecordsRequest;
+use super::schema::{TableSchema, ValidationReport};
 
-#[derive(Error, Debug)]
-pub enum BitableApiError {
-    #[error("Network error: {0}")]
-    NetworkError(#[from] reqwest::Error),
-
-    #[error("JSON serialization error: {0}")]
-    SerdeError(#[from] serde_json::Error),
+/// Records sent per batch create or update call by [`BitableTableClient::upsert_records`],
+/// [`BitableTableClient::create_typed`] and
+/// [`BitableTableClient::batch_create_validated_records_json`]
+const BATCH_SIZE: usize = 500;
 
-    #[error("API error: {message} (code: {code})")]
-    ApiError { code: i32, message: String },
-}
+/// Conditions of one search filter of [`BitableTableClient::upsert_records`]
+const FILTER_SIZE: usize = 50;
 
 pub struct BitableTableClient {
-    token_manager: FeishuTokenManager,
+    client: LarkClient,
 }
 
 impl BitableTableClient {
+    /// Create a client configured from the `FEISHU_*` environment variables
+    ///
+    /// # Panics
+    ///
+    /// Panics if the HTTP client cannot be initialized, see [`LarkClient::from_env`].
     pub fn new() -> Self {
-        Self {
-            token_manager: FeishuTokenManager::new(),
-        }
+        Self::with_client(LarkClient::from_env().expect("Failed to create Lark client"))
+    }
+
+    pub fn with_client(client: LarkClient) -> Self {
+        Self { client }
     }
 
-    /// Search records in a Bitable table
+    /// Search records in a Bitable table, first page only
     ///
     /// See: https://open.feishu.cn/document/uAjLw4CM/ukTMukTMukTM/reference/bitable-v1/app-table-record/search
     pub async fn get_records_list(
@@ -42,33 +52,33 @@ impl BitableTableClient {
         table_id: &str,
         request: super::SearchRecordsCond,
     ) -> Result<SearchRecordsResponse> {
-        let url = format!(
-            "https://open.feishu.cn/open-apis/bitable/v1/apps/{}/tables/{}/records/search",
-            app_token, table_id
-        );
+        let request = search_records_request(app_token, table_id, &request)?;
+        self.client.send(request).await
+    }
 
-        let token = self.token_manager.get_token().await?;
-        let response = Client::new()
-            .post(&url)
-            .header("Authorization", format!("Bearer {}", token))
-            .header("Content-Type", "application/json; charset=utf-8")
-            .json(&request)
-            .send()
-            .await
-            .map_err(|e| anyhow!(e).context("Failed to send request for searching records"))?
-            .json::<LarkApiResponse<SearchRecordsResponse>>()
+    /// Search records in a Bitable table, following every page
+    pub async fn get_records_list_all(
+        &self,
+        app_token: &str,
+        table_id: &str,
+        request: super::SearchRecordsCond,
+    ) -> Result<Vec<Record>> {
+        self.get_records_list_stream(app_token, table_id, request)?
+            .with_page_size(500)
+            .all()
             .await
-            .map_err(|e| anyhow!(e).context("Failed to parse search records response"))?;
-
-        if !response.is_success() {
-            let err = BitableApiError::ApiError {
-                code: response.code,
-                message: response.msg.clone(),
-            };
-            return Err(anyhow!(err).context(format!("API returned error code: {}", response.code)));
-        }
+    }
 
-        Ok(response.data)
+    /// Search records in a Bitable table, as a stream fetching pages on demand (page size:
+    /// up to 500)
+    pub fn get_records_list_stream(
+        &self,
+        app_token: &str,
+        table_id: &str,
+        request: super::SearchRecordsCond,
+    ) -> Result<Paginator<SearchRecordsResponse>> {
+        let request = search_records_request(app_token, table_id, &request)?;
+        Ok(Paginator::new(self.client.clone(), request))
     }
 
     /// Batch create multiple records in a Bitable table
@@ -80,46 +90,31 @@ impl BitableTableClient {
         table_id: &str,
         request: BatchCreateRecordsRequest,
     ) -> Result<()> {
-        if app_token.is_empty() || table_id.is_empty() {
-            return Err(anyhow!("app_token and table_id cannot be empty"));
-        }
+        self.create_records(app_token, table_id, request).await?;
+        Ok(())
+    }
+
+    async fn create_records(
+        &self,
+        app_token: &str,
+        table_id: &str,
+        request: BatchCreateRecordsRequest,
+    ) -> Result<Vec<Record>> {
+        check_table_args(app_token, table_id)?;
         if request.records.is_empty() {
-            return Err(anyhow!("No records provided for batch creation"));
+            return Err(LarkError::InvalidArgument(
+                "No records provided for batch creation".to_string(),
+            ));
         }
 
-        let url = format!(
-            "https://open.feishu.cn/open-apis/bitable/v1/apps/{}/tables/{}/records/batch_create",
+        let request = ApiRequest::post(format!(
+            "/open-apis/bitable/v1/apps/{}/tables/{}/records/batch_create",
             app_token, table_id
-        );
-        let token = self
-            .token_manager
-            .get_token()
-            .await
-            .map_err(|e| anyhow!(e).context("Failed to obtain authentication token"))?;
-
-        let resp = Client::new()
-            .post(&url)
-            .header("Authorization", format!("Bearer {}", token))
-            .header("Content-Type", "application/json; charset=utf-8")
-            .json(&request)
-            .send()
-            .await
-            .map_err(|e| anyhow!(e).context("Failed to send request for batch creating records"))?
-            .json::<LarkApiResponse<Value>>()
-            .await
-            .map_err(|e| anyhow!(e).context("Failed to parse batch create records response"))?;
+        ))
+        .json(&request)?;
 
-        match resp.is_success() {
-            true => Ok(()),
-            false => Err(anyhow!(BitableApiError::ApiError {
-                code: resp.code,
-                message: resp.msg.clone(),
-            })
-            .context(format!(
-                "API returned error code: {} - {}",
-                resp.code, resp.msg
-            ))),
-        }
+        let data: RecordsData<Record> = self.client.send(request).await?;
+        Ok(data.records)
     }
 
     pub async fn batch_create_records_json(
@@ -128,88 +123,946 @@ impl BitableTableClient {
         table_id: &str,
         records_json: &str,
     ) -> Result<()> {
-        if app_token.is_empty() || table_id.is_empty() {
-            return Err(anyhow!("app_token and table_id cannot be empty"));
-        }
+        check_table_args(app_token, table_id)?;
 
         // 先尝试解析JSON字符串
-        let value: Value = serde_json::from_str(records_json)
-            .map_err(|e| anyhow!("Failed to parse JSON string: {}", e))?;
+        let value: Value = serde_json::from_str(records_json)?;
 
         // 使用From trait将Value转换为BatchCreateRecordsRequest
         let request = BatchCreateRecordsRequest::from(value);
 
         if request.records.is_empty() {
-            return Err(anyhow!("No valid records found in the provided JSON"));
+            return Err(LarkError::InvalidArgument(
+                "No valid records found in the provided JSON".to_string(),
+            ));
         }
 
         self.batch_create_records(app_token, table_id, request)
             .await
     }
 
+    /// Fields of a table, to check records against before writing them
+    pub async fn get_table_schema(&self, app_token: &str, table_id: &str) -> Result<TableSchema> {
+        let fields = self.get_fields_list_all(app_token, table_id).await?;
+        Ok(TableSchema::new(fields))
+    }
+
+    /// Check and coerce records given as a JSON array of objects against the fields of the
+    /// table, without writing anything, see [`TableSchema::validate`]
+    pub async fn validate_records_json(
+        &self,
+        app_token: &str,
+        table_id: &str,
+        records_json: &str,
+    ) -> Result<ValidationReport> {
+        let rows = parse_rows(records_json)?;
+        let schema = self.get_table_schema(app_token, table_id).await?;
+        Ok(schema.validate(rows))
+    }
+
+    /// Like [`Self::batch_create_records_json`], but validates and coerces the records first.
+    /// Nothing is created when the returned report has validation errors; otherwise the
+    /// records are created in batches of 500. If a batch fails the later ones are not sent,
+    /// and the report holds the error next to the ids of the records already created.
+    pub async fn batch_create_validated_records_json(
+        &self,
+        app_token: &str,
+        table_id: &str,
+        records_json: &str,
+    ) -> Result<CreateReport> {
+        let validation = self
+            .validate_records_json(app_token, table_id, records_json)
+            .await?;
+        let mut report = CreateReport::default();
+        if validation.is_valid() {
+            for chunk in validation.records.chunks(BATCH_SIZE) {
+                let request = BatchCreateRecordsRequest {
+                    records: chunk.to_vec(),
+                };
+                match self.create_records(app_token, table_id, request).await {
+                    Ok(created) => report
+                        .record_ids
+                        .extend(created.into_iter().map(|record| record.record_id)),
+                    Err(e) => {
+                        report.error = Some(e);
+                        break;
+                    }
+                }
+            }
+        }
+        report.validation = validation;
+        Ok(report)
+    }
+
+    /// Get one record by id
+    ///
+    /// See: https://open.feishu.cn/document/server-docs/docs/bitable-v1/app-table-record/get
+    pub async fn get_record(
+        &self,
+        app_token: &str,
+        table_id: &str,
+        record_id: &str,
+    ) -> Result<Record> {
+        let request = ApiRequest::get(record_path(app_token, table_id, record_id)?);
+        let data: RecordData = self.client.send(request).await?;
+        Ok(data.record)
+    }
+
+    /// Get up to 100 records by id; ids without a readable record are reported in the
+    /// response instead of failing the call
+    ///
+    /// See: https://open.feishu.cn/document/docs/bitable-v1/app-table-record/batch_get
+    pub async fn batch_get_records(
+        &self,
+        app_token: &str,
+        table_id: &str,
+        request: BatchGetRecordsRequest,
+    ) -> Result<BatchGetRecordsResponse> {
+        if request.record_ids.is_empty() {
+            return Err(LarkError::InvalidArgument(
+                "No record ids provided".to_string(),
+            ));
+        }
+        let request = ApiRequest::post(records_path(app_token, table_id, "batch_get")?)
+            .json(&request)?
+            .idempotent();
+
+        self.client.send(request).await
+    }
+
+    /// Change some fields of a record and return the updated record
+    ///
+    /// See: https://open.feishu.cn/document/server-docs/docs/bitable-v1/app-table-record/update
+    pub async fn update_record(
+        &self,
+        app_token: &str,
+        table_id: &str,
+        record_id: &str,
+        fields: HashMap<String, Value>,
+    ) -> Result<Record> {
+        let request = ApiRequest::put(record_path(app_token, table_id, record_id)?)
+            .json(&serde_json::json!({ "fields": fields }))?;
+        let data: RecordData = self.client.send(request).await?;
+        Ok(data.record)
+    }
+
+    /// Update several records at once; the whole batch fails if one record is invalid
+    ///
+    /// See: https://open.feishu.cn/document/server-docs/docs/bitable-v1/app-table-record/batch_update
+    pub async fn batch_update_records(
+        &self,
+        app_token: &str,
+        table_id: &str,
+        request: BatchUpdateRecordsRequest,
+    ) -> Result<Vec<Record>> {
+        if request.records.is_empty() {
+            return Err(LarkError::InvalidArgument(
+                "No records provided for batch update".to_string(),
+            ));
+        }
+        let request =
+            ApiRequest::post(records_path(app_token, table_id, "batch_update")?).json(&request)?;
+        let data: RecordsData<Record> = self.client.send(request).await?;
+        Ok(data.records)
+    }
+
+    /// Delete one record
+    ///
+    /// See: https://open.feishu.cn/document/server-docs/docs/bitable-v1/app-table-record/delete
+    pub async fn delete_record(
+        &self,
+        app_token: &str,
+        table_id: &str,
+        record_id: &str,
+    ) -> Result<DeletedRecord> {
+        let request = ApiRequest::delete(record_path(app_token, table_id, record_id)?);
+        self.client.send(request).await
+    }
+
+    /// Delete several records at once
+    ///
+    /// See: https://open.feishu.cn/document/server-docs/docs/bitable-v1/app-table-record/batch_delete
+    pub async fn batch_delete_records(
+        &self,
+        app_token: &str,
+        table_id: &str,
+        request: BatchDeleteRecordsRequest,
+    ) -> Result<Vec<DeletedRecord>> {
+        if request.records.is_empty() {
+            return Err(LarkError::InvalidArgument(
+                "No record ids provided for batch deletion".to_string(),
+            ));
+        }
+        let request =
+            ApiRequest::post(records_path(app_token, table_id, "batch_delete")?).json(&request)?;
+        let data: RecordsData<DeletedRecord> = self.client.send(request).await?;
+        Ok(data.records)
+    }
+
+    /// Create or update records, matching existing ones on the values of `key_fields`.
+    ///
+    /// Rows are checked and coerced against the table fields first, as by
+    /// [`TableSchema::validate`]. Existing records are looked up with `is` filters on the
+    /// first text, number, single select or phone key field, 50 values per search, or with
+    /// a search over the whole table when no key field has such a type. A row matching a
+    /// record is updated when one of its fields differs, and left alone otherwise; rows
+    /// without a match are created. Person, group and attachment cells are compared on
+    /// their ids, links on their record ids, URLs on their link and locations on their
+    /// coordinates; numbers compare by value, and `false` and empty values equal the
+    /// missing cells Lark leaves them as.
+    ///
+    /// Updates, then creates, are sent in batches of 500. If a batch fails the later ones
+    /// are not sent, and the report holds the error next to the rows already written. The
+    /// report has one entry per row, in input order.
+    ///
+    /// Every row must hold all key fields, and no two rows may share the same key. When
+    /// several records share a key, the first one found is updated.
+    pub async fn upsert_records(
+        &self,
+        app_token: &str,
+        table_id: &str,
+        key_fields: &[&str],
+        records: Vec<HashMap<String, Value>>,
+    ) -> Result<UpsertReport> {
+        check_table_args(app_token, table_id)?;
+        if key_fields.is_empty() {
+            return Err(LarkError::InvalidArgument(
+                "No key fields provided for upsert".to_string(),
+            ));
+        }
+        if records.is_empty() {
+            return Err(LarkError::InvalidArgument(
+                "No records provided for upsert".to_string(),
+            ));
+        }
+
+        let schema = self.get_table_schema(app_token, table_id).await?;
+        let mut key_types = Vec::with_capacity(key_fields.len());
+        for name in key_fields {
+            let field = schema.field(name).ok_or_else(|| {
+                LarkError::InvalidArgument(format!("key field {} is not in the table", name))
+            })?;
+            key_types.push(field.field_type);
+        }
+        let field_type = |name: &str| {
+            schema
+                .field(name)
+                .map_or(FieldType::Text, |field| field.field_type)
+        };
+        let records: Vec<HashMap<String, Value>> = schema
+            .validate(records)
+            .into_records()?
+            .into_iter()
+            .map(|record| record.fields)
+            .collect();
+
+        let mut keys = HashSet::new();
+        for (index, fields) in records.iter().enumerate() {
+            let key = record_key(fields, key_fields, &key_types).ok_or_else(|| {
+                LarkError::InvalidArgument(format!(
+                    "record {} has no value for key fields {}",
+                    index,
+                    key_fields.join(", ")
+                ))
+            })?;
+            if !keys.insert(key) {
+                return Err(LarkError::InvalidArgument(format!(
+                    "record {} has the same key as an earlier record",
+                    index
+                )));
+            }
+        }
+
+        // Only the key fields and the fields being written are needed to compare
+        let mut field_names: Vec<String> = key_fields.iter().map(|name| name.to_string()).collect();
+        for name in records.iter().flat_map(HashMap::keys) {
+            if !field_names.contains(name) {
+                field_names.push(name.clone());
+            }
+        }
+        let filters = match key_types
+            .iter()
+            .position(|field_type| filterable(*field_type))
+        {
+            Some(position) => {
+                let values: Vec<String> = keys.iter().map(|key| key[position].clone()).collect();
+                values
+                    .chunks(FILTER_SIZE)
+                    .map(|chunk| {
+                        Some(Filter {
+                            conjunction: FilterConjunction::Or,
+                            conditions: chunk
+                                .iter()
+                                .map(|value| FilterCondition {
+                                    field_name: key_fields[position].to_string(),
+                                    operator: FilterOperator::Is,
+                                    value: vec![value.clone()],
+                                })
+                                .collect(),
+                        })
+                    })
+                    .collect()
+            }
+            None => vec![None],
+        };
+        let mut existing = HashMap::new();
+        for filter in filters {
+            let cond = SearchRecordsCond {
+                filter,
+                field_names: Some(field_names.clone()),
+                ..Default::default()
+            };
+            for record in self.get_records_list_all(app_token, table_id, cond).await? {
+                if let Some(key) = record_key(&record.fields, key_fields, &key_types) {
+                    existing.entry(key).or_insert(record);
+                }
+            }
+        }
+
+        let mut report = UpsertReport {
+            results: Vec::with_capacity(records.len()),
+            error: None,
+        };
+        let mut creates = Vec::new();
+        let mut updates = Vec::new();
+        for (index, fields) in records.into_iter().enumerate() {
+            let key = record_key(&fields, key_fields, &key_types).unwrap_or_default();
+            match existing.get(&key) {
+                Some(record) => {
+                    let unchanged = fields.iter().all(|(name, value)| {
+                        let field_type = field_type(name);
+                        cell_key(field_type, record.fields.get(name))
+                            == cell_key(field_type, Some(value))
+                    });
+                    let action = if unchanged {
+                        UpsertAction::Unchanged
+                    } else {
+                        updates.push((
+                            index,
+                            RecordUpdate {
+                                record_id: record.record_id.clone(),
+                                fields,
+                            },
+                        ));
+                        UpsertAction::Updated
+                    };
+                    report.results.push(UpsertResult {
+                        record_id: record.record_id.clone(),
+                        action,
+                    });
+                }
+                None => {
+                    creates.push((index, RecordCreate { fields }));
+                    report.results.push(UpsertResult {
+                        record_id: String::new(),
+                        action: UpsertAction::Created,
+                    });
+                }
+            }
+        }
+
+        for chunk in updates.chunks_mut(BATCH_SIZE) {
+            let (indexes, records) = take_chunk(chunk);
+            if report.error.is_none() {
+                let request = BatchUpdateRecordsRequest { records };
+                match self
+                    .batch_update_records(app_token, table_id, request)
+                    .await
+                {
+                    Ok(_) => continue,
+                    Err(e) => report.error = Some(e),
+                }
+            }
+            for index in indexes {
+                report.results[index].action = UpsertAction::Failed;
+            }
+        }
+        for chunk in creates.chunks_mut(BATCH_SIZE) {
+            let (indexes, records) = take_chunk(chunk);
+            if report.error.is_none() {
+                let request = BatchCreateRecordsRequest { records };
+                match self.create_records(app_token, table_id, request).await {
+                    Ok(created) => {
+                        for (index, record) in indexes.into_iter().zip(created) {
+                            report.results[index].record_id = record.record_id;
+                        }
+                        continue;
+                    }
+                    Err(e) => report.error = Some(e),
+                }
+            }
+            for index in indexes {
+                report.results[index].action = UpsertAction::Failed;
+            }
+        }
+
+        Ok(report)
+    }
+
+    /// [`Self::upsert_records`] with rows given as a JSON array of objects, like
+    /// [`Self::batch_create_records_json`]
+    pub async fn upsert_records_json(
+        &self,
+        app_token: &str,
+        table_id: &str,
+        key_fields: &[&str],
+        records_json: &str,
+    ) -> Result<UpsertReport> {
+        let records = parse_rows(records_json)?;
+        self.upsert_records(app_token, table_id, key_fields, records)
+            .await
+    }
+
+    /// Search every record of a Bitable table into `T`; unless the request names fields,
+    /// only the fields of `T` are returned
+    pub async fn search_typed<T: BitableRecord>(
+        &self,
+        app_token: &str,
+        table_id: &str,
+        mut request: SearchRecordsCond,
+    ) -> Result<Vec<T>> {
+        if request.field_names.is_none() {
+            request.field_names = Some(T::field_names().into_iter().map(String::from).collect());
+        }
+        self.get_records_list_all(app_token, table_id, request)
+            .await?
+            .iter()
+            .map(T::from_record)
+            .collect()
+    }
+
+    /// Create one record per item, in batches of 500. If a batch fails the later ones are
+    /// not sent, and the report holds the error next to the ids of the records already
+    /// created; the items are not validated, so its validation part stays empty.
+    pub async fn create_typed<T: BitableRecord>(
+        &self,
+        app_token: &str,
+        table_id: &str,
+        records: &[T],
+    ) -> Result<CreateReport> {
+        check_table_args(app_token, table_id)?;
+        if records.is_empty() {
+            return Err(LarkError::InvalidArgument(
+                "No records provided for batch creation".to_string(),
+            ));
+        }
+
+        let mut report = CreateReport::default();
+        for chunk in records.chunks(BATCH_SIZE) {
+            let records = chunk
+                .iter()
+                .map(|record| RecordCreate {
+                    fields: record.to_fields(),
+                })
+                .collect();
+            match self
+                .create_records(app_token, table_id, BatchCreateRecordsRequest { records })
+                .await
+            {
+                Ok(created) => report
+                    .record_ids
+                    .extend(created.into_iter().map(|record| record.record_id)),
+                Err(e) => {
+                    report.error = Some(e);
+                    break;
+                }
+            }
+        }
+        Ok(report)
+    }
+
+    /// List the fields of a Bitable table, first page only
+    ///
+    /// See: https://open.feishu.cn/document/server-docs/docs/bitable-v1/app-table-field/list
     pub async fn get_fields_list(
         &self,
         app_token: &str,
         table_id: &str,
     ) -> Result<FieldsListResponse> {
-        if app_token.is_empty() || table_id.is_empty() {
-            return Err(anyhow!("app_token and table_id cannot be empty"));
-        }
+        check_table_args(app_token, table_id)?;
+        self.client
+            .send(fields_list_request(app_token, table_id))
+            .await
+    }
 
-        let token = self.token_manager.get_token().await?;
+    /// List every field of a Bitable table
+    pub async fn get_fields_list_all(&self, app_token: &str, table_id: &str) -> Result<Vec<Field>> {
+        self.get_fields_list_stream(app_token, table_id)?
+            .with_page_size(100)
+            .all()
+            .await
+    }
 
-        let url = format!(
-            "https://open.feishu.cn/open-apis/bitable/v1/apps/{}/tables/{}/fields",
+    /// List the fields of a Bitable table, as a stream fetching pages on demand (page size:
+    /// up to 100)
+    pub fn get_fields_list_stream(
+        &self,
+        app_token: &str,
+        table_id: &str,
+    ) -> Result<Paginator<FieldsListResponse>> {
+        check_table_args(app_token, table_id)?;
+        Ok(Paginator::new(
+            self.client.clone(),
+            fields_list_request(app_token, table_id),
+        ))
+    }
+
+    /// Add a field to a table
+    ///
+    /// See: https://open.feishu.cn/document/server-docs/docs/bitable-v1/app-table-field/create
+    pub async fn create_field(
+        &self,
+        app_token: &str,
+        table_id: &str,
+        field: FieldCreate,
+    ) -> Result<Field> {
+        check_table_args(app_token, table_id)?;
+        let request = ApiRequest::post(format!(
+            "/open-apis/bitable/v1/apps/{}/tables/{}/fields",
             app_token, table_id
-        );
+        ))
+        .json(&field)?;
+
+        let data: FieldData = self.client.send(request).await?;
+        Ok(data.field)
+    }
+
+    /// Replace the definition of a field, e.g. to rename it or change its options. Settings
+    /// left out of `field` are reset.
+    ///
+    /// See: https://open.feishu.cn/document/server-docs/docs/bitable-v1/app-table-field/update
+    pub async fn update_field(
+        &self,
+        app_token: &str,
+        table_id: &str,
+        field_id: &str,
+        field: FieldCreate,
+    ) -> Result<Field> {
+        let request = ApiRequest::put(field_path(app_token, table_id, field_id)?).json(&field)?;
+        let data: FieldData = self.client.send(request).await?;
+        Ok(data.field)
+    }
+
+    /// Delete a field and its values; the primary field cannot be deleted
+    ///
+    /// See: https://open.feishu.cn/document/server-docs/docs/bitable-v1/app-table-field/delete
+    pub async fn delete_field(
+        &self,
+        app_token: &str,
+        table_id: &str,
+        field_id: &str,
+    ) -> Result<DeletedField> {
+        let request = ApiRequest::delete(field_path(app_token, table_id, field_id)?);
+        self.client.send(request).await
+    }
+
+    /// List the tables of a Bitable app, first page only
+    ///
+    /// See: https://open.feishu.cn/document/server-docs/docs/bitable-v1/app-table/list
+    pub async fn get_tables_list(&self, app_token: &str) -> Result<TablesListResponse> {
+        let request = ApiRequest::get(tables_path(app_token, "")?);
+        self.client.send(request).await
+    }
 
-        let response = Client::new()
-            .get(&url)
-            .header("Authorization", format!("Bearer {}", token))
-            .header("Content-Type", "application/json; charset=utf-8")
-            .send()
+    /// List every table of a Bitable app
+    pub async fn get_tables_list_all(&self, app_token: &str) -> Result<Vec<Table>> {
+        self.get_tables_list_stream(app_token)?
+            .with_page_size(100)
+            .all()
             .await
-            .map_err(|e| anyhow!(e).context("Failed to send request for getting fields list"))?
-            .json::<LarkApiResponse<FieldsListResponse>>()
+    }
+
+    /// List the tables of a Bitable app, as a stream fetching pages on demand (page size: up
+    /// to 100)
+    pub fn get_tables_list_stream(&self, app_token: &str) -> Result<Paginator<TablesListResponse>> {
+        let request = ApiRequest::get(tables_path(app_token, "")?);
+        Ok(Paginator::new(self.client.clone(), request))
+    }
+
+    /// Create a table with its fields and a default view
+    ///
+    /// See: https://open.feishu.cn/document/server-docs/docs/bitable-v1/app-table/create
+    pub async fn create_table(&self, app_token: &str, table: TableCreate) -> Result<CreatedTable> {
+        check_name(&table.name, "table name")?;
+        let request =
+            ApiRequest::post(tables_path(app_token, "")?).json(&json!({ "table": table }))?;
+        self.client.send(request).await
+    }
+
+    /// Create empty tables, returning their ids in the order of `names`
+    ///
+    /// See: https://open.feishu.cn/document/server-docs/docs/bitable-v1/app-table/batch_create
+    pub async fn batch_create_tables(
+        &self,
+        app_token: &str,
+        names: &[&str],
+    ) -> Result<Vec<String>> {
+        if names.is_empty() {
+            return Err(LarkError::InvalidArgument(
+                "No table names provided".to_string(),
+            ));
+        }
+        for name in names {
+            check_name(name, "table name")?;
+        }
+        let tables: Vec<Value> = names.iter().map(|name| json!({ "name": name })).collect();
+        let request = ApiRequest::post(tables_path(app_token, "batch_create")?)
+            .json(&json!({ "tables": tables }))?;
+
+        let data: TableIdsData = self.client.send(request).await?;
+        Ok(data.table_ids)
+    }
+
+    /// See: https://open.feishu.cn/document/server-docs/docs/bitable-v1/app-table/patch
+    pub async fn rename_table(&self, app_token: &str, table_id: &str, name: &str) -> Result<()> {
+        check_table_args(app_token, table_id)?;
+        check_name(name, "table name")?;
+        let request =
+            ApiRequest::patch(tables_path(app_token, table_id)?).json(&json!({ "name": name }))?;
+        self.client.send::<Value>(request).await?;
+        Ok(())
+    }
+
+    /// Delete a table with its records; the last table of an app cannot be deleted
+    ///
+    /// See: https://open.feishu.cn/document/server-docs/docs/bitable-v1/app-table/delete
+    pub async fn delete_table(&self, app_token: &str, table_id: &str) -> Result<()> {
+        check_table_args(app_token, table_id)?;
+        let request = ApiRequest::delete(tables_path(app_token, table_id)?);
+        self.client.send::<Value>(request).await?;
+        Ok(())
+    }
+
+    /// See: https://open.feishu.cn/document/server-docs/docs/bitable-v1/app-table/batch_delete
+    pub async fn batch_delete_tables(&self, app_token: &str, table_ids: &[&str]) -> Result<()> {
+        if table_ids.is_empty() {
+            return Err(LarkError::InvalidArgument(
+                "No table ids provided".to_string(),
+            ));
+        }
+        let request = ApiRequest::post(tables_path(app_token, "batch_delete")?)
+            .json(&json!({ "table_ids": table_ids }))?;
+        self.client.send::<Value>(request).await?;
+        Ok(())
+    }
+
+    /// List the views of a table, first page only
+    ///
+    /// See: https://open.feishu.cn/document/server-docs/docs/bitable-v1/app-table-view/list
+    pub async fn get_views_list(
+        &self,
+        app_token: &str,
+        table_id: &str,
+    ) -> Result<ViewsListResponse> {
+        let request = ApiRequest::get(view_path(app_token, table_id, "")?);
+        self.client.send(request).await
+    }
+
+    /// List every view of a table
+    pub async fn get_views_list_all(&self, app_token: &str, table_id: &str) -> Result<Vec<View>> {
+        self.get_views_list_stream(app_token, table_id)?
+            .with_page_size(100)
+            .all()
             .await
-            .map_err(|e| anyhow!(e).context("Failed to parse fields list response"))?;
+    }
 
-        match response.is_success() {
-            true => Ok(response.data),
-            false => Err(anyhow!(BitableApiError::ApiError {
-                code: response.code,
-                message: response.msg.clone(),
-            })
-            .context(format!(
-                "API returned error code: {} - {}",
-                response.code, response.msg
-            ))),
+    /// List the views of a table, as a stream fetching pages on demand (page size: up to 100)
+    pub fn get_views_list_stream(
+        &self,
+        app_token: &str,
+        table_id: &str,
+    ) -> Result<Paginator<ViewsListResponse>> {
+        let request = ApiRequest::get(view_path(app_token, table_id, "")?);
+        Ok(Paginator::new(self.client.clone(), request))
+    }
+
+    /// See: https://open.feishu.cn/document/server-docs/docs/bitable-v1/app-table-view/get
+    pub async fn get_view(&self, app_token: &str, table_id: &str, view_id: &str) -> Result<View> {
+        check_name(view_id, "view_id")?;
+        let request = ApiRequest::get(view_path(app_token, table_id, view_id)?);
+        let data: ViewData = self.client.send(request).await?;
+        Ok(data.view)
+    }
+
+    /// See: https://open.feishu.cn/document/server-docs/docs/bitable-v1/app-table-view/create
+    pub async fn create_view(
+        &self,
+        app_token: &str,
+        table_id: &str,
+        view: ViewCreate,
+    ) -> Result<View> {
+        check_name(&view.view_name, "view name")?;
+        if view.view_type == Some(ViewType::Unknown) {
+            return Err(LarkError::InvalidArgument(
+                "view type unknown cannot be created".to_string(),
+            ));
+        }
+        let request = ApiRequest::post(view_path(app_token, table_id, "")?).json(&view)?;
+        let data: ViewData = self.client.send(request).await?;
+        Ok(data.view)
+    }
+
+    /// See: https://open.feishu.cn/document/server-docs/docs/bitable-v1/app-table-view/patch
+    pub async fn rename_view(
+        &self,
+        app_token: &str,
+        table_id: &str,
+        view_id: &str,
+        name: &str,
+    ) -> Result<View> {
+        check_name(view_id, "view_id")?;
+        check_name(name, "view name")?;
+        let request = ApiRequest::patch(view_path(app_token, table_id, view_id)?)
+            .json(&json!({ "view_name": name }))?;
+        let data: ViewData = self.client.send(request).await?;
+        Ok(data.view)
+    }
+
+    /// Delete a view; the last view of a table cannot be deleted
+    ///
+    /// See: https://open.feishu.cn/document/server-docs/docs/bitable-v1/app-table-view/delete
+    pub async fn delete_view(&self, app_token: &str, table_id: &str, view_id: &str) -> Result<()> {
+        check_name(view_id, "view_id")?;
+        let request = ApiRequest::delete(view_path(app_token, table_id, view_id)?);
+        self.client.send::<Value>(request).await?;
+        Ok(())
+    }
+}
+
+fn search_records_request(
+    app_token: &str,
+    table_id: &str,
+    cond: &super::SearchRecordsCond,
+) -> Result<ApiRequest> {
+    check_table_args(app_token, table_id)?;
+    Ok(ApiRequest::post(format!(
+        "/open-apis/bitable/v1/apps/{}/tables/{}/records/search",
+        app_token, table_id
+    ))
+    .json(cond)?
+    .idempotent())
+}
+
+fn fields_list_request(app_token: &str, table_id: &str) -> ApiRequest {
+    ApiRequest::get(format!(
+        "/open-apis/bitable/v1/apps/{}/tables/{}/fields",
+        app_token, table_id
+    ))
+}
+
+/// Path of a record collection endpoint such as `batch_get`
+fn records_path(app_token: &str, table_id: &str, action: &str) -> Result<String> {
+    check_table_args(app_token, table_id)?;
+    Ok(format!(
+        "/open-apis/bitable/v1/apps/{}/tables/{}/records/{}",
+        app_token, table_id, action
+    ))
+}
+
+fn record_path(app_token: &str, table_id: &str, record_id: &str) -> Result<String> {
+    if record_id.is_empty() {
+        return Err(LarkError::InvalidArgument(
+            "record_id cannot be empty".to_string(),
+        ));
+    }
+    records_path(app_token, table_id, record_id)
+}
+
+/// Rows of a JSON array of objects
+fn parse_rows(records_json: &str) -> Result<Vec<HashMap<String, Value>>> {
+    let rows: Vec<HashMap<String, Value>> =
+        BatchCreateRecordsRequest::from(serde_json::from_str::<Value>(records_json)?)
+            .records
+            .into_iter()
+            .map(|record| record.fields)
+            .collect();
+    if rows.is_empty() {
+        return Err(LarkError::InvalidArgument(
+            "No valid records found in the provided JSON".to_string(),
+        ));
+    }
+    Ok(rows)
+}
+
+/// Values of `key_fields` in `fields`, or `None` if one of them is empty
+fn record_key(
+    fields: &HashMap<String, Value>,
+    key_fields: &[&str],
+    key_types: &[FieldType],
+) -> Option<Vec<String>> {
+    key_fields
+        .iter()
+        .zip(key_types)
+        .map(|(name, field_type)| {
+            Some(cell_key(*field_type, fields.get(*name))).filter(|key| !key.is_empty())
+        })
+        .collect()
+}
+
+/// Comparable text of a cell, so that a written value such as `"ab"` equals the rich text
+/// `[{"type": "text", "text": "a"}, {"type": "text", "text": "b"}]` returned by search, the
+/// ids written to a person, attachment or link field equal the objects read back, `10.0`
+/// equals `10`, and `false` and empty values equal the missing cells Lark leaves them as
+fn cell_key(field_type: FieldType, value: Option<&Value>) -> String {
+    let key = match field_type {
+        FieldType::User
+        | FieldType::GroupChat
+        | FieldType::CreatedUser
+        | FieldType::ModifiedUser => "id",
+        FieldType::Attachment => "file_token",
+        FieldType::SingleLink | FieldType::DuplexLink => "link_record_ids",
+        FieldType::Url => "link",
+        FieldType::Location => "location",
+        _ => "text",
+    };
+    match value {
+        None | Some(Value::Null) | Some(Value::Bool(false)) => String::new(),
+        Some(Value::String(text)) => text.clone(),
+        Some(Value::Number(number)) => number
+            .as_f64()
+            .map_or_else(|| number.to_string(), |float| float.to_string()),
+        // Text segments join like FieldValue::text, other lists are comma separated
+        Some(Value::Array(items)) => {
+            let separator = if items.iter().all(|item| item.get("text").is_some()) {
+                ""
+            } else {
+                ","
+            };
+            items
+                .iter()
+                .map(|item| cell_key(field_type, Some(item)))
+                .collect::<Vec<_>>()
+                .join(separator)
         }
+        Some(Value::Object(object)) if let Some(inner) = object.get(key) => {
+            cell_key(field_type, Some(inner))
+        }
+        Some(other) => other.to_string(),
+    }
+}
+
+/// Whether search can match cells of `field_type` with `is` and their text
+fn filterable(field_type: FieldType) -> bool {
+    matches!(
+        field_type,
+        FieldType::Text | FieldType::Number | FieldType::SingleSelect | FieldType::Phone
+    )
+}
+
+/// Indexes and records of a chunk of a batch, leaving defaults behind
+fn take_chunk<T: Default>(chunk: &mut [(usize, T)]) -> (Vec<usize>, Vec<T>) {
+    chunk
+        .iter_mut()
+        .map(|(index, record)| (*index, std::mem::take(record)))
+        .unzip()
+}
+
+/// Path of the tables of an app, or of `rest` below it such as a table id or `batch_create`
+fn tables_path(app_token: &str, rest: &str) -> Result<String> {
+    check_name(app_token, "app_token")?;
+    let path = format!("/open-apis/bitable/v1/apps/{}/tables", app_token);
+    Ok(if rest.is_empty() {
+        path
+    } else {
+        format!("{}/{}", path, rest)
+    })
+}
+
+fn field_path(app_token: &str, table_id: &str, field_id: &str) -> Result<String> {
+    check_table_args(app_token, table_id)?;
+    check_name(field_id, "field_id")?;
+    Ok(format!(
+        "/open-apis/bitable/v1/apps/{}/tables/{}/fields/{}",
+        app_token, table_id, field_id
+    ))
+}
+
+/// Path of the views of a table, or of one view if `view_id` is not empty
+fn view_path(app_token: &str, table_id: &str, view_id: &str) -> Result<String> {
+    check_table_args(app_token, table_id)?;
+    let path = format!(
+        "/open-apis/bitable/v1/apps/{}/tables/{}/views",
+        app_token, table_id
+    );
+    Ok(if view_id.is_empty() {
+        path
+    } else {
+        format!("{}/{}", path, view_id)
+    })
+}
+
+fn check_name(value: &str, what: &str) -> Result<()> {
+    if value.trim().is_empty() {
+        return Err(LarkError::InvalidArgument(format!(
+            "{} cannot be empty",
+            what
+        )));
+    }
+    Ok(())
+}
+
+fn check_table_args(app_token: &str, table_id: &str) -> Result<()> {
+    if app_token.is_empty() || table_id.is_empty() {
+        return Err(LarkError::InvalidArgument(
+            "app_token and table_id cannot be empty".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+impl Default for BitableTableClient {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::bitable::field::SelectOption;
     use crate::bitable::{
-        Filter, FilterCondition, FilterConjunction, FilterOperator, SearchRecordsCond,
+        FieldProperty, FieldType, FieldValue, Filter, FilterCondition, FilterConjunction,
+        FilterOperator,
     };
+    use crate::{ErrorKind, LarkConfig};
+    use larkrs_mock::{MockLarkServer, field};
+
+    async fn mock_table() -> (MockLarkServer, BitableTableClient) {
+        let server = MockLarkServer::start().await;
+        server.state().add_table(
+            "app_token",
+            "tbl",
+            vec![
+                field("股票名称", 1, "Text"),
+                field("题材概念", 1, "Text"),
+                field("日期", 5, "DateTime"),
+                field("梯队", 4, "MultiSelect"),
+                field("战法", 3, "SingleSelect"),
+            ],
+        );
+        let client =
+            LarkClient::new(LarkConfig::new("id", "secret").with_base_url(server.uri())).unwrap();
+        (server, BitableTableClient::with_client(client))
+    }
 
     #[tokio::test]
     async fn test_get_records_list() {
-        dotenvy::dotenv().ok();
-
-        let client = BitableTableClient::new();
+        let (server, client) = mock_table().await;
+        server.state().add_records(
+            "app_token",
+            "tbl",
+            vec![
+                json!({"股票名称": "a", "战法": "战法A"}),
+                json!({"股票名称": "b", "战法": "战法B"}),
+            ],
+        );
 
-        let app_token = "xxx";
-        let table_id = "xxx";
-        let view_id = "xxx";
         let result = client
             .get_records_list(
-                &app_token,
-                &table_id,
+                "app_token",
+                "tbl",
                 SearchRecordsCond {
-                    view_id: view_id.to_string(),
+                    view_id: "vew".to_string(),
                     filter: Some(Filter {
                         conditions: vec![FilterCondition {
                             field_name: "战法".to_string(),
@@ -221,20 +1074,33 @@ mod tests {
                     ..Default::default()
                 },
             )
-            .await;
-        assert!(result.is_ok());
-
-        println!("Result: {:#?}", result.unwrap());
+            .await
+            .unwrap();
+        assert_eq!(result.total, 1);
+        assert_eq!(result.items[0].fields["股票名称"], "a");
     }
 
     #[tokio::test]
-    async fn test_batch_create_records() {
-        dotenvy::dotenv().ok();
+    async fn test_get_records_list_all() {
+        let (server, client) = mock_table().await;
+        let records = (0..45)
+            .map(|i| json!({"股票名称": i.to_string()}))
+            .collect();
+        server.state().add_records("app_token", "tbl", records);
 
-        let client = BitableTableClient::new();
+        let records = client
+            .get_records_list_stream("app_token", "tbl", SearchRecordsCond::default())
+            .unwrap()
+            .with_page_size(20)
+            .all()
+            .await
+            .unwrap();
+        assert_eq!(records.len(), 45);
+    }
 
-        let app_token = "xxxx";
-        let table_id = "xxxx";
+    #[tokio::test]
+    async fn test_batch_create_records() {
+        let (server, client) = mock_table().await;
 
         let records_json = r#"[
             {
@@ -244,27 +1110,851 @@ mod tests {
                 "梯队": ["xxxx"]
             }
         ]"#;
-        let result = client
-            .batch_create_records_json(app_token, table_id, records_json)
-            .await;
-        assert!(result.is_ok());
+        client
+            .batch_create_records_json("app_token", "tbl", records_json)
+            .await
+            .unwrap();
+
+        let records = server.state().records("app_token", "tbl");
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0]["fields"]["日期"], 1743129600000i64);
+    }
+
+    #[tokio::test]
+    async fn test_write_values_read_back() {
+        let (server, client) = mock_table().await;
+        server.state().add_table(
+            "app_token",
+            "places",
+            vec![
+                field("名称", 1, "Text"),
+                field("关联", 18, "SingleLink"),
+                field("位置", 22, "Location"),
+            ],
+        );
+        // Cells as Lark returns them
+        let ids = server.state().add_records(
+            "app_token",
+            "places",
+            vec![json!({
+                "名称": "a",
+                "关联": {"link_record_ids": ["recA"]},
+                "位置": {"location": "116.397755,39.903179", "name": "天安门"}
+            })],
+        );
+        let mut record = client
+            .get_record("app_token", "places", &ids[0])
+            .await
+            .unwrap();
+        let link = record.field("关联").unwrap();
+        let location = record.field("位置").unwrap();
+        assert!(matches!(link, FieldValue::Link(_)));
+        assert!(matches!(location, FieldValue::Location(_)));
+
+        let request = BatchCreateRecordsRequest {
+            records: vec![
+                RecordCreate::new()
+                    .with_field("名称", "b")
+                    .with_field("关联", link.clone())
+                    .with_field("位置", location.clone()),
+            ],
+        };
+        client
+            .batch_create_records("app_token", "places", request)
+            .await
+            .unwrap();
+        let records = server.state().records("app_token", "places");
+        assert_eq!(records[1]["fields"]["关联"], json!(["recA"]));
+        assert_eq!(records[1]["fields"]["位置"], "116.397755,39.903179");
 
-        println!("Result: {:#?}", result.unwrap());
+        record.set_field("关联", link);
+        record.set_field("位置", location);
+        client
+            .update_record("app_token", "places", &ids[0], record.fields)
+            .await
+            .unwrap();
+        let records = server.state().records("app_token", "places");
+        assert_eq!(records[0]["fields"]["关联"], json!(["recA"]));
+        assert_eq!(records[0]["fields"]["位置"], "116.397755,39.903179");
     }
 
     #[tokio::test]
     async fn test_get_fields_list() {
-        dotenvy::dotenv().ok();
+        let (_server, client) = mock_table().await;
+
+        let result = client.get_fields_list("app_token", "tbl").await.unwrap();
+
+        let fields: Vec<crate::bitable::FieldInfo> = result.into();
+        assert_eq!(fields.len(), 5);
+        assert_eq!(fields[2].write_type.as_deref(), Some("Timestamp"));
+        assert_eq!(fields[3].write_type.as_deref(), Some("Array<String>"));
+        assert_eq!(fields[3].example, Some(json!(["Option A", "Option B"])));
+    }
+
+    #[tokio::test]
+    async fn test_unknown_table() {
+        let (_server, client) = mock_table().await;
+
+        let err = client
+            .get_fields_list("app_token", "missing")
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), Some(1254004));
+    }
+
+    #[tokio::test]
+    async fn test_batch_create_validated_records() {
+        let (server, client) = mock_table().await;
+
+        let invalid = r#"[{"股票名称": "a", "日期": "soon"}, {"股票名": "b"}]"#;
+        let report = client
+            .batch_create_validated_records_json("app_token", "tbl", invalid)
+            .await
+            .unwrap();
+        assert_eq!(report.validation.errors.len(), 2);
+        assert_eq!(
+            (
+                report.validation.errors[1].row,
+                report.validation.errors[1].field.as_str()
+            ),
+            (1, "股票名")
+        );
+        assert!(report.record_ids.is_empty());
+        assert!(server.state().records("app_token", "tbl").is_empty());
+
+        let valid = r#"[{"股票名称": "a", "日期": "2025-03-26", "梯队": "四板,五板"}]"#;
+        let report = client
+            .batch_create_validated_records_json("app_token", "tbl", valid)
+            .await
+            .unwrap();
+        assert!(report.validation.is_valid());
+        assert_eq!(report.record_ids.len(), 1);
+        let records = server.state().records("app_token", "tbl");
+        assert_eq!(records[0]["fields"]["日期"], 1742918400000i64);
+        assert_eq!(records[0]["fields"]["梯队"], json!(["四板", "五板"]));
+
+        // More records than Lark takes per call are sent in batches
+        let rows: Vec<Value> = (0..501)
+            .map(|i| json!({"股票名称": i.to_string()}))
+            .collect();
+        let rows = serde_json::to_string(&rows).unwrap();
+        let report = client
+            .batch_create_validated_records_json("app_token", "tbl", &rows)
+            .await
+            .unwrap();
+        assert_eq!(report.into_record_ids().unwrap().len(), 501);
+        assert_eq!(server.state().records("app_token", "tbl").len(), 502);
+    }
+
+    #[tokio::test]
+    async fn test_batch_create_validated_records_reports_failed_batch() {
+        let server = MockLarkServer::start().await;
+        server.state().add_table(
+            "app_token",
+            "tbl",
+            vec![field("股票名称", 1, "Text"), field("负责人", 11, "User")],
+        );
+        let client =
+            LarkClient::new(LarkConfig::new("id", "secret").with_base_url(server.uri())).unwrap();
+
+        // The second batch holds an id Lark cannot resolve to a user
+        let mut rows: Vec<Value> = (0..500)
+            .map(|i| json!({"股票名称": i.to_string()}))
+            .collect();
+        rows.push(json!({"股票名称": "x", "负责人": "Ann"}));
+        let rows = serde_json::to_string(&rows).unwrap();
+        let report = BitableTableClient::with_client(client)
+            .batch_create_validated_records_json("app_token", "tbl", &rows)
+            .await
+            .unwrap();
+
+        assert_eq!(report.record_ids.len(), 500);
+        assert_eq!(
+            report.error.as_ref().and_then(LarkError::code),
+            Some(1254066)
+        );
+        assert_eq!(server.state().records("app_token", "tbl").len(), 500);
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["record_ids"].as_array().unwrap().len(), 500);
+        assert!(json["error"].as_str().unwrap().contains("1254066"));
+    }
+
+    #[tokio::test]
+    async fn test_record_crud() {
+        let (server, client) = mock_table().await;
+        let ids = server.state().add_records(
+            "app_token",
+            "tbl",
+            vec![json!({"股票名称": "a", "题材概念": "x"})],
+        );
+
+        let record = client
+            .get_record("app_token", "tbl", &ids[0])
+            .await
+            .unwrap();
+        assert_eq!(record.fields["股票名称"], "a");
+
+        let fields = HashMap::from([("股票名称".to_string(), json!("b"))]);
+        let record = client
+            .update_record("app_token", "tbl", &ids[0], fields)
+            .await
+            .unwrap();
+        assert_eq!(record.fields["股票名称"], "b");
+        // Fields left out keep their value
+        assert_eq!(record.fields["题材概念"], "x");
+
+        let deleted = client
+            .delete_record("app_token", "tbl", &ids[0])
+            .await
+            .unwrap();
+        assert!(deleted.deleted);
+        let err = client
+            .get_record("app_token", "tbl", &ids[0])
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), Some(1254043));
+    }
+
+    #[tokio::test]
+    async fn test_typed_fields() {
+        use crate::bitable::{FieldValue, RecordCreate};
+
+        let (_server, client) = mock_table().await;
+        let record = RecordCreate::new()
+            .with_field("股票名称", "太阳电缆")
+            .with_field("日期", FieldValue::DateTime(1742956800000))
+            .with_field("梯队", FieldValue::MultiSelect(vec!["四板".to_string()]));
+        client
+            .batch_create_records(
+                "app_token",
+                "tbl",
+                BatchCreateRecordsRequest {
+                    records: vec![record],
+                },
+            )
+            .await
+            .unwrap();
+
+        let records = client
+            .get_records_list_all("app_token", "tbl", Default::default())
+            .await
+            .unwrap();
+        let record = &records[0];
+        assert_eq!(record.field("日期").unwrap().as_i64(), Some(1742956800000));
+        assert_eq!(
+            record.field("梯队").unwrap().as_options(),
+            Some(vec!["四板"])
+        );
+        assert!(record.field("战法").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_batch_get_update_delete() {
+        let (server, client) = mock_table().await;
+        let ids = server.state().add_records(
+            "app_token",
+            "tbl",
+            vec![json!({"股票名称": "a"}), json!({"股票名称": "b"})],
+        );
+
+        let response = client
+            .batch_get_records(
+                "app_token",
+                "tbl",
+                BatchGetRecordsRequest::new(vec![ids[0].clone(), "recmissing".to_string()]),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.records.len(), 1);
+        assert_eq!(response.absent_record_ids, ["recmissing"]);
+
+        let updates = ids
+            .iter()
+            .map(|id| RecordUpdate {
+                record_id: id.clone(),
+                fields: HashMap::from([("战法".to_string(), json!("战法C"))]),
+            })
+            .collect();
+        let records = client
+            .batch_update_records(
+                "app_token",
+                "tbl",
+                BatchUpdateRecordsRequest { records: updates },
+            )
+            .await
+            .unwrap();
+        assert!(
+            records
+                .iter()
+                .all(|record| record.fields["战法"] == "战法C")
+        );
+
+        let deleted = client
+            .batch_delete_records(
+                "app_token",
+                "tbl",
+                BatchDeleteRecordsRequest {
+                    records: ids.clone(),
+                },
+            )
+            .await
+            .unwrap();
+        assert_eq!(deleted.len(), 2);
+        assert!(server.state().records("app_token", "tbl").is_empty());
+
+        let err = client
+            .batch_delete_records(
+                "app_token",
+                "tbl",
+                BatchDeleteRecordsRequest { records: vec![] },
+            )
+            .await
+            .unwrap_err();
+        assert!(matches!(err, LarkError::InvalidArgument(_)));
+    }
+
+    #[tokio::test]
+    async fn test_upsert_records() {
+        let (server, client) = mock_table().await;
+        // Search returns text cells as rich text segments
+        server.state().add_records(
+            "app_token",
+            "tbl",
+            vec![
+                json!({"股票名称": [{"type": "text", "text": "太阳电缆"}], "日期": 1742956800000i64, "梯队": ["四板"]}),
+                json!({"股票名称": [{"type": "text", "text": "海洋电缆"}], "日期": 1742956800000i64, "梯队": ["三板"]}),
+            ],
+        );
+
+        let rows = r#"[
+            {"股票名称": "太阳电缆", "日期": 1742956800000, "梯队": ["四板"]},
+            {"股票名称": "海洋电缆", "日期": 1742956800000, "梯队": ["四板"]},
+            {"股票名称": "太阳电缆", "日期": 1743043200000, "梯队": ["五板"]}
+        ]"#;
+        let results = client
+            .upsert_records_json("app_token", "tbl", &["股票名称", "日期"], rows)
+            .await
+            .unwrap()
+            .into_results()
+            .unwrap();
+        let actions: Vec<_> = results.iter().map(|result| result.action).collect();
+        assert_eq!(
+            actions,
+            [
+                UpsertAction::Unchanged,
+                UpsertAction::Updated,
+                UpsertAction::Created
+            ]
+        );
+        assert!(results.iter().all(|result| !result.record_id.is_empty()));
+
+        let records = server.state().records("app_token", "tbl");
+        assert_eq!(records.len(), 3);
+        assert_eq!(records[1]["fields"]["梯队"], json!(["四板"]));
+
+        // Rerunning the same import changes nothing
+        let results = client
+            .upsert_records_json("app_token", "tbl", &["股票名称", "日期"], rows)
+            .await
+            .unwrap()
+            .into_results()
+            .unwrap();
+        assert!(
+            results
+                .iter()
+                .all(|result| result.action == UpsertAction::Unchanged)
+        );
+
+        let duplicate =
+            r#"[{"股票名称": "a", "日期": "2025-03-26"}, {"股票名称": "a", "日期": "2025-03-26"}]"#;
+        let err = client
+            .upsert_records_json("app_token", "tbl", &["股票名称", "日期"], duplicate)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, LarkError::InvalidArgument(msg) if msg.contains("same key")));
+
+        let err = client
+            .upsert_records_json("app_token", "tbl", &["代码"], rows)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, LarkError::InvalidArgument(msg) if msg.contains("代码")));
+
+        // Keys are looked up 50 per search
+        let rows: Vec<Value> = (0..120)
+            .map(|i| json!({"股票名称": format!("股票{}", i), "日期": "2025-03-26"}))
+            .collect();
+        let rows = serde_json::to_string(&rows).unwrap();
+        for expected in [UpsertAction::Created, UpsertAction::Unchanged] {
+            let results = client
+                .upsert_records_json("app_token", "tbl", &["股票名称", "日期"], &rows)
+                .await
+                .unwrap()
+                .into_results()
+                .unwrap();
+            assert!(results.iter().all(|result| result.action == expected));
+        }
+        assert_eq!(server.state().records("app_token", "tbl").len(), 123);
+    }
+
+    #[tokio::test]
+    async fn test_upsert_compares_ids() {
+        let (server, client) = mock_table().await;
+        server.state().add_table(
+            "app_token",
+            "tasks",
+            vec![
+                field("名称", 1, "Text"),
+                field("负责人", 11, "User"),
+                field("关联", 18, "SingleLink"),
+                field("附件", 17, "Attachment"),
+                field("位置", 22, "Location"),
+            ],
+        );
+        // Cells as search returns them
+        server.state().add_records(
+            "app_token",
+            "tasks",
+            vec![json!({
+                "名称": [{"type": "text", "text": "a"}],
+                "负责人": [{"id": "ou_1", "name": "Ann", "email": "ann@example.com"}],
+                "关联": {"link_record_ids": ["recA", "recB"]},
+                "附件": [{"file_token": "box1", "name": "a.png", "size": 1}],
+                "位置": {"location": "116.397755,39.903179", "name": "天安门"}
+            })],
+        );
+
+        let rows = r#"[{
+            "名称": "a",
+            "负责人": ["ou_1"],
+            "关联": "recA,recB",
+            "附件": "box1",
+            "位置": "116.397755,39.903179"
+        }]"#;
+        let results = client
+            .upsert_records_json("app_token", "tasks", &["名称"], rows)
+            .await
+            .unwrap()
+            .into_results()
+            .unwrap();
+        assert_eq!(results[0].action, UpsertAction::Unchanged);
+
+        let rows = r#"[{"名称": "a", "负责人": [{"id": "ou_2"}]}]"#;
+        let results = client
+            .upsert_records_json("app_token", "tasks", &["名称"], rows)
+            .await
+            .unwrap()
+            .into_results()
+            .unwrap();
+        assert_eq!(results[0].action, UpsertAction::Updated);
+        let records = server.state().records("app_token", "tasks");
+        assert_eq!(records[0]["fields"]["负责人"], json!([{"id": "ou_2"}]));
+    }
+
+    #[tokio::test]
+    async fn test_upsert_compares_values() {
+        let (server, client) = mock_table().await;
+        server.state().add_table(
+            "app_token",
+            "stock",
+            vec![
+                field("名称", 1, "Text"),
+                field("数量", 2, "Number"),
+                field("完成", 7, "Checkbox"),
+                field("备注", 1, "Text"),
+            ],
+        );
+        // Rich text read back in two segments, with the unchecked box and empty note left out
+        server.state().add_records(
+            "app_token",
+            "stock",
+            vec![json!({
+                "名称": [{"type": "text", "text": "太阳"}, {"type": "text", "text": "电缆"}],
+                "数量": 10
+            })],
+        );
+
+        // Text segments join without a separator
+        let rows = r#"[{"名称": "太阳电缆"}]"#;
+        let results = client
+            .upsert_records_json("app_token", "stock", &["名称"], rows)
+            .await
+            .unwrap()
+            .into_results()
+            .unwrap();
+        assert_eq!(results[0].action, UpsertAction::Unchanged);
+
+        // Numbers compare by value
+        let rows = r#"[{"名称": "太阳电缆", "数量": 10.0}]"#;
+        let results = client
+            .upsert_records_json("app_token", "stock", &["名称"], rows)
+            .await
+            .unwrap()
+            .into_results()
+            .unwrap();
+        assert_eq!(results[0].action, UpsertAction::Unchanged);
+
+        // False and empty values equal missing cells
+        let rows = r#"[{"名称": "太阳电缆", "完成": false, "备注": ""}]"#;
+        let results = client
+            .upsert_records_json("app_token", "stock", &["名称"], rows)
+            .await
+            .unwrap()
+            .into_results()
+            .unwrap();
+        assert_eq!(results[0].action, UpsertAction::Unchanged);
+        assert_eq!(server.state().records("app_token", "stock").len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_upsert_reports_failed_batch() {
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        let table = "/open-apis/bitable/v1/apps/app/tables/tbl";
+        let ok = |data: Value| {
+            ResponseTemplate::new(200)
+                .set_body_json(json!({"code": 0, "msg": "success", "data": data}))
+        };
+        Mock::given(method("POST"))
+            .and(path("/open-apis/auth/v3/tenant_access_token/internal"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "code": 0,
+                "msg": "ok",
+                "tenant_access_token": "t-1",
+                "expire": 7200
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path(format!("{}/fields", table)))
+            .respond_with(ok(json!({
+                "items": [
+                    {"field_id": "fld1", "field_name": "名称", "type": 1},
+                    {"field_id": "fld2", "field_name": "数量", "type": 2}
+                ],
+                "has_more": false,
+                "total": 2
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path(format!("{}/records/search", table)))
+            .respond_with(ok(json!({
+                "items": [{"record_id": "rec1", "fields": {"名称": "a", "数量": 1}}],
+                "has_more": false,
+                "total": 1
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path(format!("{}/records/batch_update", table)))
+            .respond_with(ok(json!({
+                "records": [{"record_id": "rec1", "fields": {"名称": "a", "数量": 2}}]
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path(format!("{}/records/batch_create", table)))
+            .respond_with(
+                ResponseTemplate::new(400)
+                    .set_body_json(json!({"code": 1254001, "msg": "WrongRequestBody"})),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client =
+            LarkClient::new(LarkConfig::new("id", "secret").with_base_url(server.uri())).unwrap();
+        let report = BitableTableClient::with_client(client)
+            .upsert_records_json(
+                "app",
+                "tbl",
+                &["名称"],
+                r#"[{"名称": "a", "数量": 2}, {"名称": "b", "数量": 3}]"#,
+            )
+            .await
+            .unwrap();
+
+        // The update went through before the create failed
+        assert_eq!(report.results[0].record_id, "rec1");
+        assert_eq!(report.results[0].action, UpsertAction::Updated);
+        assert_eq!(report.results[1].action, UpsertAction::Failed);
+        assert_eq!(
+            report.error.as_ref().and_then(LarkError::code),
+            Some(1254001)
+        );
+        let json = serde_json::to_value(&report).unwrap();
+        assert!(json["error"].as_str().unwrap().contains("WrongRequestBody"));
+    }
 
-        let client = BitableTableClient::new();
+    #[tokio::test]
+    async fn test_table_management() {
+        let (server, client) = mock_table().await;
+
+        let table = TableCreate::new("涨停复盘")
+            .with_default_view_name("全部")
+            .with_field(FieldCreate::new("股票名称", FieldType::Text))
+            .with_field(
+                FieldCreate::new("梯队", FieldType::MultiSelect).with_property(FieldProperty {
+                    options: Some(vec![SelectOption {
+                        name: "四板".to_string(),
+                        id: None,
+                        color: None,
+                    }]),
+                    ..Default::default()
+                }),
+            );
+        let created = client.create_table("app_token", table).await.unwrap();
+        assert_eq!(created.field_id_list.len(), 2);
+        let fields = client
+            .get_fields_list_all("app_token", &created.table_id)
+            .await
+            .unwrap();
+        assert_eq!(fields[1].field_type, FieldType::MultiSelect);
+
+        let ids = client
+            .batch_create_tables("app_token", &["日报", "周报"])
+            .await
+            .unwrap();
+        client
+            .rename_table("app_token", &ids[0], "每日复盘")
+            .await
+            .unwrap();
+        let tables = client.get_tables_list_all("app_token").await.unwrap();
+        let names: Vec<&str> = tables.iter().map(|table| table.name.as_str()).collect();
+        assert_eq!(names, ["tbl", "涨停复盘", "每日复盘", "周报"]);
+
+        let err = client
+            .batch_create_tables("app_token", &["周报"])
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), Some(1254013));
+
+        client.delete_table("app_token", &ids[0]).await.unwrap();
+        client
+            .batch_delete_tables("app_token", &[created.table_id.as_str(), ids[1].as_str()])
+            .await
+            .unwrap();
+        assert_eq!(server.state().tables("app_token").len(), 1);
+
+        let err = client
+            .create_table("app_token", TableCreate::new(" "))
+            .await;
+        assert!(matches!(err, Err(LarkError::InvalidArgument(_))));
+    }
+
+    #[tokio::test]
+    async fn test_field_and_view_management() {
+        let (server, client) = mock_table().await;
+        server
+            .state()
+            .add_records("app_token", "tbl", vec![json!({"题材概念": "电力"})]);
+
+        let field = client
+            .create_field(
+                "app_token",
+                "tbl",
+                FieldCreate::new("涨幅", FieldType::Number).with_description("收盘涨幅"),
+            )
+            .await
+            .unwrap();
+        assert_eq!(field.field_type, FieldType::Number);
+        assert_eq!(field.description.as_deref(), Some("收盘涨幅"));
+        let err = client
+            .create_field(
+                "app_token",
+                "tbl",
+                FieldCreate::new("涨幅", FieldType::Text),
+            )
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), Some(1254014));
+
+        let fields = client
+            .get_fields_list_all("app_token", "tbl")
+            .await
+            .unwrap();
+        let concept = fields
+            .iter()
+            .find(|field| field.field_name == "题材概念")
+            .unwrap();
+        let updated = client
+            .update_field(
+                "app_token",
+                "tbl",
+                &concept.field_id,
+                FieldCreate::new("概念", FieldType::Text),
+            )
+            .await
+            .unwrap();
+        assert_eq!(updated.field_id, concept.field_id);
+        assert_eq!(
+            server.state().records("app_token", "tbl")[0]["fields"]["概念"],
+            "电力"
+        );
+
+        let deleted = client
+            .delete_field("app_token", "tbl", &field.field_id)
+            .await
+            .unwrap();
+        assert!(deleted.deleted);
+        let err = client
+            .delete_field("app_token", "tbl", &field.field_id)
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::NotFound);
+
+        let view = client
+            .create_view(
+                "app_token",
+                "tbl",
+                ViewCreate::new("看板").with_view_type(ViewType::Kanban),
+            )
+            .await
+            .unwrap();
+        assert_eq!(view.view_type, ViewType::Kanban);
+        assert!("kanbn".parse::<ViewType>().is_err());
+        let err = client
+            .create_view(
+                "app_token",
+                "tbl",
+                ViewCreate::new("未知").with_view_type(ViewType::Unknown),
+            )
+            .await
+            .unwrap_err();
+        assert!(matches!(err, LarkError::InvalidArgument(_)));
+        let renamed = client
+            .rename_view("app_token", "tbl", &view.view_id, "梯队看板")
+            .await
+            .unwrap();
+        assert_eq!(renamed.view_name, "梯队看板");
+        let views = client.get_views_list_all("app_token", "tbl").await.unwrap();
+        assert_eq!(views.len(), 2);
+
+        client
+            .delete_view("app_token", "tbl", &view.view_id)
+            .await
+            .unwrap();
+        let err = client
+            .get_view("app_token", "tbl", &view.view_id)
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::NotFound);
+    }
+
+    #[cfg(feature = "derive")]
+    #[tokio::test]
+    async fn test_typed_records() {
+        use crate::bitable::{BitableRecord, BitableSelect};
+
+        #[derive(Debug, PartialEq, BitableSelect)]
+        enum Tier {
+            #[bitable(option = "四板")]
+            Fourth,
+            #[bitable(option = "五板")]
+            Fifth,
+        }
+
+        #[derive(Debug, PartialEq, BitableRecord)]
+        struct Stock {
+            #[bitable(record_id)]
+            id: String,
+            #[bitable(field = "股票名称")]
+            name: String,
+            #[bitable(field = "日期")]
+            date: chrono::NaiveDate,
+            #[bitable(field = "梯队")]
+            tiers: Vec<Tier>,
+        }
 
-        let app_token = "xxxx";
-        let table_id = "xxxx";
+        let (server, client) = mock_table().await;
+        let date = chrono::NaiveDate::from_ymd_opt(2025, 3, 26).unwrap();
+        let stocks = [
+            Stock {
+                id: String::new(),
+                name: "太阳电缆".to_string(),
+                date,
+                tiers: vec![Tier::Fourth],
+            },
+            Stock {
+                id: String::new(),
+                name: "海洋电缆".to_string(),
+                date,
+                tiers: vec![Tier::Fourth, Tier::Fifth],
+            },
+        ];
+        let created = client
+            .create_typed("app_token", "tbl", &stocks)
+            .await
+            .unwrap()
+            .into_record_ids()
+            .unwrap();
+        assert_eq!(created.len(), 2);
+        let records = server.state().records("app_token", "tbl");
+        assert_eq!(records[0]["fields"]["日期"], json!(1742918400000i64));
+
+        let found: Vec<Stock> = client
+            .search_typed("app_token", "tbl", SearchRecordsCond::default())
+            .await
+            .unwrap();
+        assert_eq!(found.len(), 2);
+        assert_eq!(found[0].id, created[0]);
+        assert_eq!(found[1].name, "海洋电缆");
+        assert_eq!(found[1].date, date);
+        assert_eq!(found[1].tiers, [Tier::Fourth, Tier::Fifth]);
+
+        let err = client
+            .create_typed::<Stock>("app_token", "tbl", &[])
+            .await
+            .unwrap_err();
+        assert!(matches!(err, LarkError::InvalidArgument(_)));
+    }
 
-        let result = client.get_fields_list(app_token, table_id).await;
-        assert!(result.is_ok());
+    #[cfg(feature = "derive")]
+    #[tokio::test]
+    async fn test_create_typed_reports_failed_batch() {
+        use crate::bitable::BitableRecord;
 
-        let fields: Vec<crate::bitable::FieldInfo> = result.unwrap().into();
-        println!("Simplified Field Infos: {:#?}", fields);
+        #[derive(Debug, BitableRecord)]
+        struct Row {
+            #[bitable(field = "股票名称")]
+            name: String,
+            #[bitable(field = "负责人")]
+            owner: Value,
+        }
+
+        let server = MockLarkServer::start().await;
+        server.state().add_table(
+            "app_token",
+            "tbl",
+            vec![field("股票名称", 1, "Text"), field("负责人", 11, "User")],
+        );
+        let client =
+            LarkClient::new(LarkConfig::new("id", "secret").with_base_url(server.uri())).unwrap();
+
+        // The second batch holds an id Lark cannot resolve to a user
+        let mut rows: Vec<Row> = (0..500)
+            .map(|i| Row {
+                name: i.to_string(),
+                owner: json!([{"id": "ou_1"}]),
+            })
+            .collect();
+        rows.push(Row {
+            name: "x".to_string(),
+            owner: json!([{"id": "Ann"}]),
+        });
+        let report = BitableTableClient::with_client(client)
+            .create_typed("app_token", "tbl", &rows)
+            .await
+            .unwrap();
+
+        assert_eq!(report.record_ids.len(), 500);
+        assert_eq!(
+            report.error.as_ref().and_then(LarkError::code),
+            Some(1254066)
+        );
+        assert_eq!(server.state().records("app_token", "tbl").len(), 500);
     }
 }
@@ -0,0 +1,496 @@
+//! Typed values of Bitable cells.
+//!
+//! [`FieldValue`] is decoded from the JSON of a cell by its shape, since the JSON alone does
+//! not carry the field type: a string is read as [`FieldValue::Text`] whether the field is
+//! a text, single select, phone or auto number field, and a number as
+//! [`FieldValue::Number`] even in a date field. Those variants exist to build values to
+//! write, and serialize to the same JSON. A cell whose typed form would not serialize back
+//! to the exact same JSON is kept as [`FieldValue::Other`], so decoding and encoding a
+//! cell never changes it.
+//!
+//! Links and locations are read in another shape than they are written in; use
+//! [`FieldValue::to_write_value`] rather than the serialized form to write them.
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Number, Value};
+
+/// Value of one Bitable cell, see the [module documentation](self)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(from = "Value", into = "Value")]
+pub enum FieldValue {
+    Null,
+    /// Plain text, as written to text, email and barcode fields
+    Text(String),
+    /// Text, mention and link segments, as text fields are read back
+    RichText(Vec<TextSegment>),
+    /// Number, currency, progress and rating fields
+    Number(Number),
+    SingleSelect(String),
+    MultiSelect(Vec<String>),
+    /// Milliseconds since the Unix epoch, also created and modified time fields
+    DateTime(i64),
+    Checkbox(bool),
+    /// Person, created by and modified by fields
+    Person(Vec<Person>),
+    Group(Vec<Group>),
+    Phone(String),
+    Url(Url),
+    Attachment(Vec<Attachment>),
+    /// Linked records (one-way and two-way links) as read back; links are written as an
+    /// array of record ids, see [`FieldValue::to_write_value`]
+    Link(Link),
+    Location(Location),
+    /// Result of a formula or lookup field
+    Formula(Formula),
+    AutoNumber(String),
+    /// Any other JSON
+    Other(Value),
+}
+
+/// One segment of a rich text cell
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TextSegment {
+    /// `text`, `mention` or `url`
+    #[serde(rename = "type")]
+    pub segment_type: String,
+    pub text: String,
+    /// Link of `url` segments
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub link: Option<String>,
+    /// Other attributes, e.g. `mentionType` and `token` of mentions
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl TextSegment {
+    pub fn text(text: impl Into<String>) -> Self {
+        Self {
+            segment_type: "text".to_string(),
+            text: text.into(),
+            link: None,
+            extra: Map::new(),
+        }
+    }
+}
+
+/// A user; only `id` is needed to write
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Person {
+    /// Open id (`ou_...`) by default, or the id type of the `user_id_type` parameter
+    pub id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub en_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub avatar_url: Option<String>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl Person {
+    pub fn new(id: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            name: None,
+            en_name: None,
+            email: None,
+            avatar_url: None,
+            extra: Map::new(),
+        }
+    }
+}
+
+/// A chat group; only `id` is needed to write
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Group {
+    /// Chat id, `oc_...`
+    pub id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub avatar_url: Option<String>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl Group {
+    pub fn new(id: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            name: None,
+            avatar_url: None,
+            extra: Map::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Url {
+    pub link: String,
+    /// Text shown instead of the link
+    pub text: String,
+}
+
+/// An uploaded file; only `file_token` is needed to write
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Attachment {
+    pub file_token: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size: Option<u64>,
+    /// MIME type
+    #[serde(rename = "type", default, skip_serializing_if = "Option::is_none")]
+    pub mime_type: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tmp_url: Option<String>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl Attachment {
+    pub fn new(file_token: impl Into<String>) -> Self {
+        Self {
+            file_token: file_token.into(),
+            name: None,
+            size: None,
+            mime_type: None,
+            url: None,
+            tmp_url: None,
+            extra: Map::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Link {
+    pub link_record_ids: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Location {
+    /// `longitude,latitude`
+    pub location: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pname: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cityname: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub adname: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub address: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub full_address: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Formula {
+    /// Field type of the result
+    #[serde(rename = "type")]
+    pub value_type: i32,
+    pub value: Box<FieldValue>,
+}
+
+impl FieldValue {
+    /// Plain text of text, select, phone, URL, auto number and number cells; multi-valued
+    /// cells are joined with `,`
+    pub fn text(&self) -> Option<String> {
+        match self {
+            Self::Text(text)
+            | Self::SingleSelect(text)
+            | Self::Phone(text)
+            | Self::AutoNumber(text) => Some(text.clone()),
+            Self::RichText(segments) => Some(segments.iter().map(|s| s.text.as_str()).collect()),
+            Self::Number(number) => Some(number.to_string()),
+            Self::MultiSelect(options) => Some(options.join(",")),
+            Self::Url(url) => Some(url.text.clone()),
+            Self::Formula(formula) => formula.value.text(),
+            Self::Other(Value::Array(items)) => {
+                let texts: Option<Vec<String>> = items
+                    .iter()
+                    .map(|item| Self::from(item.clone()).text())
+                    .collect();
+                texts.map(|texts| texts.join(","))
+            }
+            _ => None,
+        }
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Self::Number(number) => number.as_f64(),
+            Self::DateTime(millis) => Some(*millis as f64),
+            _ => None,
+        }
+    }
+
+    /// Integer value, e.g. the timestamp of a date cell
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            Self::Number(number) => number.as_i64(),
+            Self::DateTime(millis) => Some(*millis),
+            _ => None,
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Self::Checkbox(checked) => Some(*checked),
+            _ => None,
+        }
+    }
+
+    /// Options of a multi select cell, or the option of a single select cell
+    pub fn as_options(&self) -> Option<Vec<&str>> {
+        match self {
+            Self::MultiSelect(options) => Some(options.iter().map(String::as_str).collect()),
+            Self::SingleSelect(option) | Self::Text(option) => Some(vec![option.as_str()]),
+            _ => None,
+        }
+    }
+
+    pub fn as_persons(&self) -> Option<&[Person]> {
+        match self {
+            Self::Person(persons) => Some(persons),
+            _ => None,
+        }
+    }
+
+    pub fn as_groups(&self) -> Option<&[Group]> {
+        match self {
+            Self::Group(groups) => Some(groups),
+            _ => None,
+        }
+    }
+
+    pub fn as_attachments(&self) -> Option<&[Attachment]> {
+        match self {
+            Self::Attachment(attachments) => Some(attachments),
+            _ => None,
+        }
+    }
+
+    /// Ids of linked records
+    pub fn as_record_ids(&self) -> Option<&[String]> {
+        match self {
+            Self::Link(link) => Some(&link.link_record_ids),
+            _ => None,
+        }
+    }
+
+    pub fn as_url(&self) -> Option<&Url> {
+        match self {
+            Self::Url(url) => Some(url),
+            _ => None,
+        }
+    }
+
+    pub fn as_location(&self) -> Option<&Location> {
+        match self {
+            Self::Location(location) => Some(location),
+            _ => None,
+        }
+    }
+
+    pub fn is_null(&self) -> bool {
+        matches!(self, Self::Null)
+    }
+
+    /// JSON to write the value: links as an array of record ids and locations as
+    /// `longitude,latitude`, the other values as they serialize
+    pub fn to_write_value(&self) -> Value {
+        match self {
+            Self::Link(link) => Value::from(link.link_record_ids.clone()),
+            Self::Location(location) => Value::String(location.location.clone()),
+            other => Value::from(other.clone()),
+        }
+    }
+
+    /// Decode `value` by its shape, without checking that the result encodes back to it
+    fn from_shape(value: &Value) -> Option<Self> {
+        fn parse<T: serde::de::DeserializeOwned>(value: &Value) -> Option<T> {
+            serde_json::from_value(value.clone()).ok()
+        }
+
+        match value {
+            Value::Null => Some(Self::Null),
+            Value::Bool(checked) => Some(Self::Checkbox(*checked)),
+            Value::Number(number) => Some(Self::Number(number.clone())),
+            Value::String(text) => Some(Self::Text(text.clone())),
+            Value::Array(items) => {
+                let first = items.first()?;
+                if first.is_string() {
+                    parse(value).map(Self::MultiSelect)
+                } else if first.get("file_token").is_some() {
+                    parse(value).map(Self::Attachment)
+                } else if first.get("text").is_some() && first.get("type").is_some() {
+                    parse(value).map(Self::RichText)
+                } else if first["id"].as_str().is_some_and(|id| id.starts_with("oc_")) {
+                    parse(value).map(Self::Group)
+                } else if first.get("id").is_some() {
+                    parse(value).map(Self::Person)
+                } else {
+                    None
+                }
+            }
+            Value::Object(object) => {
+                if object.contains_key("link_record_ids") {
+                    parse(value).map(Self::Link)
+                } else if object.contains_key("location") {
+                    parse(value).map(Self::Location)
+                } else if object.contains_key("link") && object.contains_key("text") {
+                    parse(value).map(Self::Url)
+                } else if object.contains_key("type") && object.contains_key("value") {
+                    parse(value).map(Self::Formula)
+                } else {
+                    None
+                }
+            }
+        }
+    }
+}
+
+impl From<Value> for FieldValue {
+    fn from(value: Value) -> Self {
+        match Self::from_shape(&value) {
+            Some(typed) if Value::from(typed.clone()) == value => typed,
+            _ => Self::Other(value),
+        }
+    }
+}
+
+impl From<FieldValue> for Value {
+    fn from(value: FieldValue) -> Self {
+        fn to_value<T: Serialize>(value: T) -> Value {
+            serde_json::to_value(value).unwrap_or_default()
+        }
+
+        match value {
+            FieldValue::Null => Value::Null,
+            FieldValue::Text(text)
+            | FieldValue::SingleSelect(text)
+            | FieldValue::Phone(text)
+            | FieldValue::AutoNumber(text) => Value::String(text),
+            FieldValue::RichText(segments) => to_value(segments),
+            FieldValue::Number(number) => Value::Number(number),
+            FieldValue::MultiSelect(options) => to_value(options),
+            FieldValue::DateTime(millis) => Value::from(millis),
+            FieldValue::Checkbox(checked) => Value::Bool(checked),
+            FieldValue::Person(persons) => to_value(persons),
+            FieldValue::Group(groups) => to_value(groups),
+            FieldValue::Url(url) => to_value(url),
+            FieldValue::Attachment(attachments) => to_value(attachments),
+            FieldValue::Link(link) => to_value(link),
+            FieldValue::Location(location) => to_value(location),
+            FieldValue::Formula(formula) => to_value(formula),
+            FieldValue::Other(value) => value,
+        }
+    }
+}
+
+impl From<&str> for FieldValue {
+    fn from(text: &str) -> Self {
+        Self::Text(text.to_string())
+    }
+}
+
+impl From<String> for FieldValue {
+    fn from(text: String) -> Self {
+        Self::Text(text)
+    }
+}
+
+impl From<i64> for FieldValue {
+    fn from(number: i64) -> Self {
+        Self::Number(number.into())
+    }
+}
+
+impl From<f64> for FieldValue {
+    fn from(number: f64) -> Self {
+        Number::from_f64(number).map_or(Self::Null, Self::Number)
+    }
+}
+
+impl From<bool> for FieldValue {
+    fn from(checked: bool) -> Self {
+        Self::Checkbox(checked)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_round_trip_every_kind() {
+        let cells = [
+            json!(null),
+            json!("太阳电缆"),
+            json!([{"type": "text", "text": "see "}, {"type": "url", "text": "docs", "link": "https://a.b"},
+                   {"type": "mention", "text": "@Ann", "mentionType": "User", "token": "ou_1"}]),
+            json!(12.5),
+            json!(1742956800000i64),
+            json!(["四板", "五板"]),
+            json!(true),
+            json!([{"id": "ou_1", "name": "Ann", "en_name": "Ann", "email": "ann@a.b", "avatar_url": "https://a.b/1"}]),
+            json!([{"id": "oc_1", "name": "Ops", "avatar_url": "https://a.b/2"}]),
+            json!({"link": "https://a.b", "text": "site"}),
+            json!([{"file_token": "box1", "name": "a.png", "size": 10, "type": "image/png",
+                    "url": "https://a.b/f", "tmp_url": "https://a.b/t"}]),
+            json!({"link_record_ids": ["rec1", "rec2"]}),
+            json!({"location": "116.39,39.90", "pname": "北京市", "cityname": "北京市",
+                   "adname": "东城区", "address": "东长安街", "name": "天安门", "full_address": "北京市东城区"}),
+            json!({"type": 1, "value": [{"type": "text", "text": "ok"}]}),
+            json!([]),
+            json!({"unknown": 1}),
+            // Typed decoding would drop the null email, so the cell is kept as is
+            json!([{"id": "ou_2", "email": null}]),
+        ];
+
+        for cell in cells {
+            let value: FieldValue = serde_json::from_value(cell.clone()).unwrap();
+            assert_eq!(serde_json::to_value(&value).unwrap(), cell);
+        }
+    }
+
+    #[test]
+    fn test_decoded_variants() {
+        let value =
+            FieldValue::from(json!([{"type": "text", "text": "a"}, {"type": "text", "text": "b"}]));
+        assert_eq!(value.text().as_deref(), Some("ab"));
+
+        let value = FieldValue::from(json!([{"id": "ou_1", "name": "Ann"}]));
+        assert_eq!(value.as_persons().unwrap()[0].name.as_deref(), Some("Ann"));
+        let value = FieldValue::from(json!([{"id": "oc_1"}]));
+        assert_eq!(value.as_groups().unwrap()[0].id, "oc_1");
+
+        let value = FieldValue::from(json!({"link_record_ids": ["rec1"]}));
+        assert_eq!(value.as_record_ids().unwrap(), ["rec1"]);
+
+        let value = FieldValue::from(json!({"type": 2, "value": [3]}));
+        assert!(matches!(&value, FieldValue::Formula(f) if f.value_type == 2));
+        assert_eq!(value.text().as_deref(), Some("3"));
+
+        assert_eq!(
+            Value::from(FieldValue::DateTime(1742956800000)),
+            json!(1742956800000i64)
+        );
+        assert_eq!(
+            Value::from(FieldValue::SingleSelect("A".into())),
+            json!("A")
+        );
+        assert_eq!(
+            Value::from(FieldValue::Person(vec![Person::new("ou_1")])),
+            json!([{"id": "ou_1"}])
+        );
+    }
+}
@@ -0,0 +1,573 @@
+//! Synchronous wrappers of the API clients, enabled by the `blocking` feature.
+//!
+//! Each blocking client drives its async counterpart on a private current-thread tokio
+//! runtime, shared by every client derived from the same [`LarkClient`], so callers need
+//! no runtime of their own. Like `reqwest::blocking`, these clients must not be used from
+//! within an async runtime; async code should use [`crate::LarkClient`] directly.
+//!
+//! ```rust,no_run
+//! use larkrs_client::blocking::LarkClient;
+//!
+//! # #[cfg(feature = "im")]
+//! fn main() -> larkrs_client::Result<()> {
+//!     let client = LarkClient::from_env()?;
+//!     for chat in client.chat().get_chat_group_list()? {
+//!         println!("{}: {}", chat.chat_id, chat.name);
+//!     }
+//!     Ok(())
+//! }
+//! # #[cfg(not(feature = "im"))]
+//! # fn main() {}
+//! ```
+//!
+//! The `*_stream` methods have no blocking counterpart, use the `*_all` variants instead.
+
+use reqwest::Method;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use std::future::Future;
+use std::sync::Arc;
+use tokio::runtime::Runtime;
+
+use crate::LarkApiResponse;
+use crate::config::LarkConfig;
+use crate::error::{LarkError, Result};
+
+/// Blocking counterpart of [`crate::LarkClient`]
+#[derive(Clone)]
+pub struct LarkClient {
+    inner: crate::LarkClient,
+    runtime: Arc<Runtime>,
+}
+
+impl LarkClient {
+    pub fn new(config: LarkConfig) -> Result<Self> {
+        Self::from_async(crate::LarkClient::new(config)?)
+    }
+
+    /// Build a client from the `FEISHU_*` environment variables, see [`LarkConfig::from_env`].
+    pub fn from_env() -> Result<Self> {
+        Self::new(LarkConfig::from_env())
+    }
+
+    /// Wrap an async client, keeping its auth mode, retry policy and token cache
+    pub fn from_async(inner: crate::LarkClient) -> Result<Self> {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .map_err(|e| LarkError::Config(format!("cannot start tokio runtime: {}", e)))?;
+
+        Ok(Self {
+            inner,
+            runtime: Arc::new(runtime),
+        })
+    }
+
+    /// The wrapped async client
+    pub fn inner(&self) -> &crate::LarkClient {
+        &self.inner
+    }
+
+    /// Get a valid tenant access token, see [`crate::auth::FeishuTokenManager::get_token`]
+    pub fn tenant_access_token(&self) -> Result<String> {
+        self.block_on(self.inner.token_manager().get_token())
+    }
+
+    /// Call any endpoint, see [`crate::LarkClient::call`]
+    pub fn call<T: DeserializeOwned + Default>(
+        &self,
+        method: Method,
+        path: &str,
+        body: Option<&Value>,
+    ) -> Result<LarkApiResponse<T>> {
+        self.block_on(self.inner.call(method, path, body))
+    }
+
+    #[cfg(feature = "bitable")]
+    pub fn bitable(&self) -> BitableTableClient {
+        BitableTableClient {
+            inner: self.inner.bitable(),
+            client: self.clone(),
+        }
+    }
+
+    #[cfg(feature = "im")]
+    pub fn chat(&self) -> ChatClient {
+        ChatClient {
+            inner: self.inner.chat(),
+            client: self.clone(),
+        }
+    }
+
+    #[cfg(feature = "wiki")]
+    pub fn wiki(&self) -> WikiClient {
+        WikiClient {
+            inner: self.inner.wiki(),
+            client: self.clone(),
+        }
+    }
+
+    fn block_on<F: Future>(&self, future: F) -> F::Output {
+        self.runtime.block_on(future)
+    }
+}
+
+/// Blocking counterpart of [`crate::bitable::table::BitableTableClient`]
+#[cfg(feature = "bitable")]
+pub struct BitableTableClient {
+    inner: crate::bitable::table::BitableTableClient,
+    client: LarkClient,
+}
+
+#[cfg(feature = "bitable")]
+impl BitableTableClient {
+    pub fn get_records_list(
+        &self,
+        app_token: &str,
+        table_id: &str,
+        request: crate::bitable::SearchRecordsCond,
+    ) -> Result<crate::bitable::SearchRecordsResponse> {
+        self.client
+            .block_on(self.inner.get_records_list(app_token, table_id, request))
+    }
+
+    pub fn get_records_list_all(
+        &self,
+        app_token: &str,
+        table_id: &str,
+        request: crate::bitable::SearchRecordsCond,
+    ) -> Result<Vec<crate::bitable::Record>> {
+        self.client.block_on(
+            self.inner
+                .get_records_list_all(app_token, table_id, request),
+        )
+    }
+
+    pub fn batch_create_records(
+        &self,
+        app_token: &str,
+        table_id: &str,
+        request: crate::bitable::BatchCreateRecordsRequest,
+    ) -> Result<()> {
+        self.client.block_on(
+            self.inner
+                .batch_create_records(app_token, table_id, request),
+        )
+    }
+
+    pub fn batch_create_records_json(
+        &self,
+        app_token: &str,
+        table_id: &str,
+        records_json: &str,
+    ) -> Result<()> {
+        self.client.block_on(self.inner.batch_create_records_json(
+            app_token,
+            table_id,
+            records_json,
+        ))
+    }
+
+    pub fn get_table_schema(
+        &self,
+        app_token: &str,
+        table_id: &str,
+    ) -> Result<crate::bitable::schema::TableSchema> {
+        self.client
+            .block_on(self.inner.get_table_schema(app_token, table_id))
+    }
+
+    pub fn validate_records_json(
+        &self,
+        app_token: &str,
+        table_id: &str,
+        records_json: &str,
+    ) -> Result<crate::bitable::schema::ValidationReport> {
+        self.client.block_on(
+            self.inner
+                .validate_records_json(app_token, table_id, records_json),
+        )
+    }
+
+    pub fn batch_create_validated_records_json(
+        &self,
+        app_token: &str,
+        table_id: &str,
+        records_json: &str,
+    ) -> Result<crate::bitable::CreateReport> {
+        self.client
+            .block_on(self.inner.batch_create_validated_records_json(
+                app_token,
+                table_id,
+                records_json,
+            ))
+    }
+
+    pub fn get_record(
+        &self,
+        app_token: &str,
+        table_id: &str,
+        record_id: &str,
+    ) -> Result<crate::bitable::Record> {
+        self.client
+            .block_on(self.inner.get_record(app_token, table_id, record_id))
+    }
+
+    pub fn batch_get_records(
+        &self,
+        app_token: &str,
+        table_id: &str,
+        request: crate::bitable::BatchGetRecordsRequest,
+    ) -> Result<crate::bitable::BatchGetRecordsResponse> {
+        self.client
+            .block_on(self.inner.batch_get_records(app_token, table_id, request))
+    }
+
+    pub fn update_record(
+        &self,
+        app_token: &str,
+        table_id: &str,
+        record_id: &str,
+        fields: std::collections::HashMap<String, Value>,
+    ) -> Result<crate::bitable::Record> {
+        self.client.block_on(
+            self.inner
+                .update_record(app_token, table_id, record_id, fields),
+        )
+    }
+
+    pub fn batch_update_records(
+        &self,
+        app_token: &str,
+        table_id: &str,
+        request: crate::bitable::BatchUpdateRecordsRequest,
+    ) -> Result<Vec<crate::bitable::Record>> {
+        self.client.block_on(
+            self.inner
+                .batch_update_records(app_token, table_id, request),
+        )
+    }
+
+    pub fn delete_record(
+        &self,
+        app_token: &str,
+        table_id: &str,
+        record_id: &str,
+    ) -> Result<crate::bitable::DeletedRecord> {
+        self.client
+            .block_on(self.inner.delete_record(app_token, table_id, record_id))
+    }
+
+    pub fn batch_delete_records(
+        &self,
+        app_token: &str,
+        table_id: &str,
+        request: crate::bitable::BatchDeleteRecordsRequest,
+    ) -> Result<Vec<crate::bitable::DeletedRecord>> {
+        self.client.block_on(
+            self.inner
+                .batch_delete_records(app_token, table_id, request),
+        )
+    }
+
+    pub fn upsert_records(
+        &self,
+        app_token: &str,
+        table_id: &str,
+        key_fields: &[&str],
+        records: Vec<std::collections::HashMap<String, Value>>,
+    ) -> Result<crate::bitable::UpsertReport> {
+        self.client.block_on(
+            self.inner
+                .upsert_records(app_token, table_id, key_fields, records),
+        )
+    }
+
+    pub fn upsert_records_json(
+        &self,
+        app_token: &str,
+        table_id: &str,
+        key_fields: &[&str],
+        records_json: &str,
+    ) -> Result<crate::bitable::UpsertReport> {
+        self.client.block_on(self.inner.upsert_records_json(
+            app_token,
+            table_id,
+            key_fields,
+            records_json,
+        ))
+    }
+
+    pub fn search_typed<T: crate::bitable::BitableRecord>(
+        &self,
+        app_token: &str,
+        table_id: &str,
+        request: crate::bitable::SearchRecordsCond,
+    ) -> Result<Vec<T>> {
+        self.client
+            .block_on(self.inner.search_typed(app_token, table_id, request))
+    }
+
+    pub fn create_typed<T: crate::bitable::BitableRecord>(
+        &self,
+        app_token: &str,
+        table_id: &str,
+        records: &[T],
+    ) -> Result<crate::bitable::CreateReport> {
+        self.client
+            .block_on(self.inner.create_typed(app_token, table_id, records))
+    }
+
+    pub fn get_fields_list(
+        &self,
+        app_token: &str,
+        table_id: &str,
+    ) -> Result<crate::bitable::FieldsListResponse> {
+        self.client
+            .block_on(self.inner.get_fields_list(app_token, table_id))
+    }
+
+    pub fn get_fields_list_all(
+        &self,
+        app_token: &str,
+        table_id: &str,
+    ) -> Result<Vec<crate::bitable::Field>> {
+        self.client
+            .block_on(self.inner.get_fields_list_all(app_token, table_id))
+    }
+
+    pub fn create_field(
+        &self,
+        app_token: &str,
+        table_id: &str,
+        field: crate::bitable::FieldCreate,
+    ) -> Result<crate::bitable::Field> {
+        self.client
+            .block_on(self.inner.create_field(app_token, table_id, field))
+    }
+
+    pub fn update_field(
+        &self,
+        app_token: &str,
+        table_id: &str,
+        field_id: &str,
+        field: crate::bitable::FieldCreate,
+    ) -> Result<crate::bitable::Field> {
+        self.client.block_on(
+            self.inner
+                .update_field(app_token, table_id, field_id, field),
+        )
+    }
+
+    pub fn delete_field(
+        &self,
+        app_token: &str,
+        table_id: &str,
+        field_id: &str,
+    ) -> Result<crate::bitable::DeletedField> {
+        self.client
+            .block_on(self.inner.delete_field(app_token, table_id, field_id))
+    }
+
+    pub fn get_tables_list(&self, app_token: &str) -> Result<crate::bitable::TablesListResponse> {
+        self.client.block_on(self.inner.get_tables_list(app_token))
+    }
+
+    pub fn get_tables_list_all(&self, app_token: &str) -> Result<Vec<crate::bitable::Table>> {
+        self.client
+            .block_on(self.inner.get_tables_list_all(app_token))
+    }
+
+    pub fn create_table(
+        &self,
+        app_token: &str,
+        table: crate::bitable::TableCreate,
+    ) -> Result<crate::bitable::CreatedTable> {
+        self.client
+            .block_on(self.inner.create_table(app_token, table))
+    }
+
+    pub fn batch_create_tables(&self, app_token: &str, names: &[&str]) -> Result<Vec<String>> {
+        self.client
+            .block_on(self.inner.batch_create_tables(app_token, names))
+    }
+
+    pub fn rename_table(&self, app_token: &str, table_id: &str, name: &str) -> Result<()> {
+        self.client
+            .block_on(self.inner.rename_table(app_token, table_id, name))
+    }
+
+    pub fn delete_table(&self, app_token: &str, table_id: &str) -> Result<()> {
+        self.client
+            .block_on(self.inner.delete_table(app_token, table_id))
+    }
+
+    pub fn batch_delete_tables(&self, app_token: &str, table_ids: &[&str]) -> Result<()> {
+        self.client
+            .block_on(self.inner.batch_delete_tables(app_token, table_ids))
+    }
+
+    pub fn get_views_list(
+        &self,
+        app_token: &str,
+        table_id: &str,
+    ) -> Result<crate::bitable::ViewsListResponse> {
+        self.client
+            .block_on(self.inner.get_views_list(app_token, table_id))
+    }
+
+    pub fn get_views_list_all(
+        &self,
+        app_token: &str,
+        table_id: &str,
+    ) -> Result<Vec<crate::bitable::View>> {
+        self.client
+            .block_on(self.inner.get_views_list_all(app_token, table_id))
+    }
+
+    pub fn get_view(
+        &self,
+        app_token: &str,
+        table_id: &str,
+        view_id: &str,
+    ) -> Result<crate::bitable::View> {
+        self.client
+            .block_on(self.inner.get_view(app_token, table_id, view_id))
+    }
+
+    pub fn create_view(
+        &self,
+        app_token: &str,
+        table_id: &str,
+        view: crate::bitable::ViewCreate,
+    ) -> Result<crate::bitable::View> {
+        self.client
+            .block_on(self.inner.create_view(app_token, table_id, view))
+    }
+
+    pub fn rename_view(
+        &self,
+        app_token: &str,
+        table_id: &str,
+        view_id: &str,
+        name: &str,
+    ) -> Result<crate::bitable::View> {
+        self.client
+            .block_on(self.inner.rename_view(app_token, table_id, view_id, name))
+    }
+
+    pub fn delete_view(&self, app_token: &str, table_id: &str, view_id: &str) -> Result<()> {
+        self.client
+            .block_on(self.inner.delete_view(app_token, table_id, view_id))
+    }
+}
+
+/// Blocking counterpart of [`crate::bot::chat::ChatClient`]
+#[cfg(feature = "im")]
+pub struct ChatClient {
+    inner: crate::bot::chat::ChatClient,
+    client: LarkClient,
+}
+
+#[cfg(feature = "im")]
+impl ChatClient {
+    pub fn send_message(&self, request: crate::bot::SendMessageRequest) -> Result<Value> {
+        self.client.block_on(self.inner.send_message(request))
+    }
+
+    pub fn send_text_message(&self, chat_id: &str, text: &str) -> Result<Value> {
+        self.client
+            .block_on(self.inner.send_text_message(chat_id, text))
+    }
+
+    pub fn send_markdown_message(
+        &self,
+        chat_id: &str,
+        title: &str,
+        content: &str,
+    ) -> Result<Value> {
+        self.client
+            .block_on(self.inner.send_markdown_message(chat_id, title, content))
+    }
+
+    pub fn get_chat_group_list(&self) -> Result<Vec<crate::bot::ChatInfoItem>> {
+        self.client.block_on(self.inner.get_chat_group_list())
+    }
+
+    pub fn get_chat_group_list_all(&self) -> Result<Vec<crate::bot::ChatInfo>> {
+        self.client.block_on(self.inner.get_chat_group_list_all())
+    }
+}
+
+/// Blocking counterpart of [`crate::wiki::client::WikiClient`]
+#[cfg(feature = "wiki")]
+pub struct WikiClient {
+    inner: crate::wiki::client::WikiClient,
+    client: LarkClient,
+}
+
+#[cfg(feature = "wiki")]
+impl WikiClient {
+    pub fn get_wiki_node(
+        &self,
+        space_id: &str,
+        node_token: &str,
+    ) -> Result<crate::wiki::WikiNodeResponse> {
+        self.client
+            .block_on(self.inner.get_wiki_node(space_id, node_token))
+    }
+
+    pub fn get_wiki_content(&self, node_token: &str) -> Result<crate::wiki::WikiContentResponse> {
+        self.client
+            .block_on(self.inner.get_wiki_content(node_token))
+    }
+
+    pub fn list_wiki_nodes(&self, space_id: &str) -> Result<crate::wiki::WikiListResponse> {
+        self.client.block_on(self.inner.list_wiki_nodes(space_id))
+    }
+
+    pub fn list_wiki_nodes_all(
+        &self,
+        space_id: &str,
+    ) -> Result<Vec<crate::wiki::WikiNodeResponse>> {
+        self.client
+            .block_on(self.inner.list_wiki_nodes_all(space_id))
+    }
+
+    pub fn parse_wiki_url(&self, url: &str) -> Result<(String, String)> {
+        self.inner.parse_wiki_url(url)
+    }
+}
+
+#[cfg(all(test, feature = "bitable", feature = "im"))]
+mod tests {
+    use super::*;
+    use larkrs_mock::MockLarkServer;
+
+    #[test]
+    fn test_blocking_calls_without_runtime() {
+        // The mock runs on its own runtime, the test thread itself has none
+        let mock_runtime = Runtime::new().unwrap();
+        let server = mock_runtime.block_on(MockLarkServer::start());
+        server.state().add_chat("oc_1", "blocking");
+        server
+            .state()
+            .add_table("app", "tbl", vec![larkrs_mock::field("Name", 1, "Text")]);
+
+        let client =
+            LarkClient::new(LarkConfig::new("id", "secret").with_base_url(server.uri())).unwrap();
+
+        let chats = client.chat().get_chat_group_list().unwrap();
+        assert_eq!(chats[0].name, "blocking");
+        client.chat().send_text_message("oc_1", "hello").unwrap();
+        assert_eq!(server.state().messages().len(), 1);
+
+        let fields = client.bitable().get_fields_list_all("app", "tbl").unwrap();
+        assert_eq!(fields[0].field_name, "Name");
+
+        let tables = client.bitable().get_tables_list("app").unwrap();
+        assert_eq!(tables.items[0].table_id, "tbl");
+        let views = client.bitable().get_views_list("app", "tbl").unwrap();
+        assert_eq!(views.items.len(), 1);
+    }
+}
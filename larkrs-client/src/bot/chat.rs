@@ -1,65 +1,38 @@
-#![allow(dead_code)]
-
-use crate::LarkApiResponse;
-use crate::auth::FeishuTokenManager;
-use anyhow::{Result, anyhow};
-use reqwest::Client;
+use crate::client::LarkClient;
+use crate::error::Result;
+use crate::http::ApiRequest;
 use serde_json::Value;
-use thiserror::Error;
-
-use super::{ChatInfoItem, ChatListResponse, SendMessageRequest};
-
-#[derive(Error, Debug)]
-pub enum ChatApiError {
-    #[error("Network error: {0}")]
-    NetworkError(#[from] reqwest::Error),
 
-    #[error("JSON serialization error: {0}")]
-    SerdeError(#[from] serde_json::Error),
-
-    #[error("API error: {message} (code: {code})")]
-    ApiError { code: i32, message: String },
-}
+use super::{ChatInfo, ChatInfoItem, ChatListResponse, SendMessageRequest};
+use crate::page::Paginator;
 
 pub struct ChatClient {
-    token_manager: FeishuTokenManager,
+    client: LarkClient,
 }
 
 impl ChatClient {
+    /// Create a client configured from the `FEISHU_*` environment variables
+    ///
+    /// # Panics
+    ///
+    /// Panics if the HTTP client cannot be initialized, see [`LarkClient::from_env`].
     pub fn new() -> Self {
-        Self {
-            token_manager: FeishuTokenManager::new(),
-        }
+        Self::with_client(LarkClient::from_env().expect("Failed to create Lark client"))
+    }
+
+    pub fn with_client(client: LarkClient) -> Self {
+        Self { client }
     }
 
     /// Send a message to a chat
     ///
     /// See: https://open.feishu.cn/document/server-docs/im-v1/message/create
     pub async fn send_message(&self, request: SendMessageRequest) -> Result<Value> {
-        let token = self.token_manager.get_token().await?;
-
-        let url = "https://open.feishu.cn/open-apis/im/v1/messages?receive_id_type=chat_id";
+        let request = ApiRequest::post("/open-apis/im/v1/messages")
+            .query("receive_id_type", "chat_id")
+            .json(&request)?;
 
-        let resp = Client::new()
-            .post(url)
-            .header("Authorization", format!("Bearer {}", token))
-            .header("Content-Type", "application/json")
-            .json(&request)
-            .send()
-            .await
-            .map_err(|e| anyhow!(e).context("Failed to send request for sending message"))?
-            .json::<LarkApiResponse<Value>>()
-            .await
-            .map_err(|e| anyhow!(e).context("Failed to parse send message response"))?;
-
-        match resp.is_success() {
-            true => Ok(resp.data),
-            false => Err(anyhow!(ChatApiError::ApiError {
-                code: resp.code,
-                message: resp.msg.clone(),
-            })
-            .context(format!("API returned error code: {}", resp.code))),
-        }
+        self.client.send(request).await
     }
 
     pub async fn send_text_message(&self, chat_id: &str, text: &str) -> Result<Value> {
@@ -87,76 +60,115 @@ impl ChatClient {
         self.send_message(request).await
     }
 
-    /// Get a list of chats
+    /// Get a list of chats, first page only
     ///
     /// See: https://open.feishu.cn/document/server-docs/im-v1/chat/list
     pub async fn get_chat_group_list(&self) -> Result<Vec<ChatInfoItem>> {
-        let token = self.token_manager.get_token().await?;
-
-        // Using reqwest's built-in query parameter handling
-        let resp = Client::new()
-            .get("https://open.feishu.cn/open-apis/im/v1/chats")
-            .query(&[("page_size", "20"), ("sort_type", "ByCreateTimeAsc")])
-            .header("Authorization", format!("Bearer {}", token))
-            .header("Content-Type", "application/json; charset=utf-8")
-            .send()
-            .await
-            .map_err(|e| anyhow!(e).context("Failed to send request for getting chat list"))?
-            .json::<LarkApiResponse<ChatListResponse>>()
+        let request = ApiRequest::get("/open-apis/im/v1/chats")
+            .query("page_size", 20)
+            .query("sort_type", "ByCreateTimeAsc");
+
+        let response: ChatListResponse = self.client.send(request).await?;
+        Ok(response.into())
+    }
+
+    /// Get every chat the bot is in, with full chat information
+    pub async fn get_chat_group_list_all(&self) -> Result<Vec<ChatInfo>> {
+        self.get_chat_group_list_stream()
+            .with_page_size(100)
+            .all()
             .await
-            .map_err(|e| anyhow!(e).context("Failed to parse chat list response"))?;
-
-        match resp.is_success() {
-            true => Ok(resp.data.into()),
-            false => Err(anyhow!(ChatApiError::ApiError {
-                code: resp.code,
-                message: resp.msg.clone(),
-            })
-            .context(format!("API returned error code: {}", resp.code))),
-        }
+    }
+
+    /// Get the chats the bot is in, as a stream fetching pages on demand (page size: up to
+    /// 100)
+    pub fn get_chat_group_list_stream(&self) -> Paginator<ChatListResponse> {
+        let request =
+            ApiRequest::get("/open-apis/im/v1/chats").query("sort_type", "ByCreateTimeAsc");
+        Paginator::new(self.client.clone(), request)
+    }
+}
+
+impl Default for ChatClient {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::LarkConfig;
+    use larkrs_mock::MockLarkServer;
+
+    async fn mock_chat() -> (MockLarkServer, ChatClient) {
+        let server = MockLarkServer::start().await;
+        server.state().add_chat("oc_1", "test");
+        let client =
+            LarkClient::new(LarkConfig::new("id", "secret").with_base_url(server.uri())).unwrap();
+        (server, ChatClient::with_client(client))
+    }
 
     #[tokio::test]
     async fn test_get_chat_group_list() {
-        dotenvy::dotenv().ok();
+        let (server, client) = mock_chat().await;
+        for i in 2..=150 {
+            server.state().add_chat(&format!("oc_{}", i), "test");
+        }
 
-        let client = ChatClient::new();
-        let result = client.get_chat_group_list().await;
+        let chats = client.get_chat_group_list().await.unwrap();
+        assert_eq!(chats.len(), 20);
+        assert_eq!(chats[0].chat_id, "oc_1");
 
-        println!("Result: {:?}", result);
+        let chats = client.get_chat_group_list_all().await.unwrap();
+        assert_eq!(chats.len(), 150);
+        assert_eq!(chats[149].chat_id, "oc_150");
     }
 
     #[tokio::test]
     async fn test_send_text_message() {
-        dotenvy::dotenv().ok();
+        let (server, client) = mock_chat().await;
 
-        let client = ChatClient::new();
-        let chat_id = std::env::var("CHAT_ID").unwrap();
         let result = client
-            .send_text_message(&chat_id, "Test message from Rust API")
-            .await;
-
-        println!("Send message result: {:?}", result);
+            .send_text_message("oc_1", "Test message from Rust API")
+            .await
+            .unwrap();
+        assert!(result["message_id"].is_string());
+
+        let messages = server.state().messages();
+        assert_eq!(messages[0]["msg_type"], "text");
+        assert_eq!(
+            messages[0]["body"]["content"],
+            r#"{"text":"Test message from Rust API"}"#
+        );
     }
 
     #[tokio::test]
     async fn test_send_markdown_message() {
-        dotenvy::dotenv().ok();
-
-        let client = ChatClient::new();
-        let chat_id = std::env::var("CHAT_ID").unwrap();
+        let (server, client) = mock_chat().await;
 
         let markdown_content = "# 股票市场实时数据\n\n**今日热门股票列表**\n\n- **阿里巴巴 (BABA)**: ¥78.45 📈 +2.3%\n- **腾讯控股 (0700.HK)**: ¥321.80 📉 -1.5%\n- **美团 (3690.HK)**: ¥125.60 📈 +3.7%\n- **京东 (JD)**: ¥142.30 📈 +0.8%\n- **百度 (BIDU)**: ¥112.75 📉 -2.1%\n- **小米集团 (1810.HK)**: ¥12.86 📈 +4.2%\n- **拼多多 (PDD)**: ¥89.35 📈 +5.6%\n\n> 数据更新时间: 2025-04-01 19:30:00";
 
-        let result = client
-            .send_markdown_message(&chat_id, "我是一个标题", markdown_content)
-            .await;
+        client
+            .send_markdown_message("oc_1", "我是一个标题", markdown_content)
+            .await
+            .unwrap();
 
-        println!("Send markdown message result: {:?}", result);
+        let messages = server.state().messages();
+        assert_eq!(messages[0]["msg_type"], "post");
+        let content: Value =
+            serde_json::from_str(messages[0]["body"]["content"].as_str().unwrap()).unwrap();
+        assert_eq!(content["zh_cn"]["title"], "我是一个标题");
+    }
+
+    #[tokio::test]
+    async fn test_send_to_foreign_chat() {
+        let (_server, client) = mock_chat().await;
+
+        let err = client
+            .send_text_message("oc_other", "hi")
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), Some(230002));
     }
 }
@@ -1,5 +1,7 @@
 use serde::{Deserialize, Serialize};
 
+use crate::page::Page;
+
 pub mod chat;
 
 #[derive(Debug, Serialize, Deserialize, Default)]
@@ -11,6 +13,18 @@ pub struct ChatListResponse {
     pub has_more: bool,
 }
 
+impl Page for ChatListResponse {
+    type Item = ChatInfo;
+
+    fn next_page_token(&self) -> Option<&str> {
+        self.page_token.as_deref().filter(|_| self.has_more)
+    }
+
+    fn into_items(self) -> Vec<ChatInfo> {
+        self.items
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ChatInfo {
     pub chat_id: String,
@@ -35,14 +49,16 @@ pub struct ChatInfoItem {
 
 impl From<ChatListResponse> for Vec<ChatInfoItem> {
     fn from(response: ChatListResponse) -> Self {
-        response
-            .items
-            .into_iter()
-            .map(|chat| ChatInfoItem {
-                chat_id: chat.chat_id,
-                name: chat.name,
-            })
-            .collect()
+        response.items.into_iter().map(ChatInfoItem::from).collect()
+    }
+}
+
+impl From<ChatInfo> for ChatInfoItem {
+    fn from(chat: ChatInfo) -> Self {
+        ChatInfoItem {
+            chat_id: chat.chat_id,
+            name: chat.name,
+        }
     }
 }
 
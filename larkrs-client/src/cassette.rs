@@ -0,0 +1,324 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use crate::error::{LarkError, Result};
+use crate::http::{ApiRequest, RawResponse};
+
+/// Keys whose values are replaced with [`REDACTED`] before an interaction is written
+const SECRET_KEYS: [&str; 8] = [
+    "app_secret",
+    "app_ticket",
+    "tenant_access_token",
+    "app_access_token",
+    "access_token",
+    "refresh_token",
+    "client_secret",
+    "tenant_key",
+];
+
+const REDACTED: &str = "[REDACTED]";
+
+/// Whether a [`Cassette`] talks to the server or answers from its file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CassetteMode {
+    /// Send requests and append every request/response pair to the file
+    Record,
+    /// Answer requests from the file without any network access
+    Replay,
+}
+
+/// Recorded HTTP interactions, to replay Lark responses deterministically in tests.
+///
+/// Install a cassette with [`crate::LarkConfig::with_cassette`]. Request and response
+/// bodies are stored with tokens and secrets scrubbed, and the `Authorization` header is
+/// never stored. In replay mode, each request is answered by the first unused interaction
+/// with the same method, path, query and body, and a request without a match fails.
+///
+/// ```rust,no_run
+/// # fn example() -> larkrs_client::Result<()> {
+/// use larkrs_client::{LarkClient, LarkConfig, cassette::Cassette};
+/// use std::sync::Arc;
+///
+/// // Records when LARKRS_CASSETTE=record, replays otherwise
+/// let cassette = Arc::new(Cassette::from_env("tests/cassettes/chats.json")?);
+/// let client = LarkClient::new(LarkConfig::from_env().with_cassette(cassette))?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct Cassette {
+    path: PathBuf,
+    mode: CassetteMode,
+    state: Mutex<CassetteState>,
+}
+
+#[derive(Debug, Default)]
+struct CassetteState {
+    interactions: Vec<Interaction>,
+    /// Interactions already used to answer a request during replay
+    used: Vec<bool>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Interaction {
+    request: RecordedRequest,
+    response: RecordedResponse,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct RecordedRequest {
+    method: String,
+    path: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    query: Vec<(String, String)>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    body: Option<Value>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct RecordedResponse {
+    status: u16,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    log_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    retry_after_secs: Option<u64>,
+    /// JSON body, or the raw text of a non-JSON body
+    body: Value,
+}
+
+impl Cassette {
+    /// Start a new recording, replacing the file when the first interaction is written
+    pub fn record(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            mode: CassetteMode::Record,
+            state: Mutex::new(CassetteState::default()),
+        }
+    }
+
+    /// Load a recording to replay
+    pub fn replay(path: impl Into<PathBuf>) -> Result<Self> {
+        let path = path.into();
+        let content = fs::read_to_string(&path).map_err(|e| {
+            LarkError::Config(format!("cannot read cassette {}: {}", path.display(), e))
+        })?;
+        let interactions: Vec<Interaction> = serde_json::from_str(&content)?;
+
+        Ok(Self {
+            path,
+            mode: CassetteMode::Replay,
+            state: Mutex::new(CassetteState {
+                used: vec![false; interactions.len()],
+                interactions,
+            }),
+        })
+    }
+
+    /// Record when the `LARKRS_CASSETTE` environment variable is `record`, replay otherwise
+    pub fn from_env(path: impl Into<PathBuf>) -> Result<Self> {
+        match std::env::var("LARKRS_CASSETTE").as_deref() {
+            Ok("record") => Ok(Self::record(path)),
+            _ => Self::replay(path),
+        }
+    }
+
+    pub fn mode(&self) -> CassetteMode {
+        self.mode
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Number of recorded interactions not used by a request yet
+    pub fn remaining(&self) -> usize {
+        let state = self.state.lock().unwrap();
+        state.used.iter().filter(|used| !**used).count()
+    }
+
+    /// Answer `request` from the recording
+    pub(crate) fn replay_request(&self, request: &ApiRequest) -> Result<RawResponse> {
+        let recorded = RecordedRequest::from(request);
+        let mut state = self.state.lock().unwrap();
+        let CassetteState { interactions, used } = &mut *state;
+
+        let index = interactions
+            .iter()
+            .zip(used.iter())
+            .position(|(interaction, used)| !used && interaction.request == recorded)
+            .ok_or_else(|| {
+                LarkError::Config(format!(
+                    "cassette {} has no unused interaction for {} {} (query: {:?})",
+                    self.path.display(),
+                    recorded.method,
+                    recorded.path,
+                    recorded.query
+                ))
+            })?;
+        used[index] = true;
+
+        let response = &interactions[index].response;
+        Ok(RawResponse {
+            status: response.status,
+            log_id: response.log_id.clone(),
+            retry_after: response.retry_after_secs.map(Duration::from_secs),
+            body: match &response.body {
+                Value::String(text) => text.clone(),
+                body => body.to_string(),
+            },
+        })
+    }
+
+    /// Append an interaction and rewrite the file
+    pub(crate) fn record_response(&self, request: &ApiRequest, response: &RawResponse) {
+        let interaction = Interaction {
+            request: RecordedRequest::from(request),
+            response: RecordedResponse {
+                status: response.status,
+                log_id: response.log_id.clone(),
+                retry_after_secs: response.retry_after.map(|d| d.as_secs()),
+                body: serde_json::from_str(&response.body)
+                    .map(scrub)
+                    .unwrap_or_else(|_| Value::String(response.body.clone())),
+            },
+        };
+
+        let mut state = self.state.lock().unwrap();
+        state.interactions.push(interaction);
+        state.used.push(true);
+        if let Err(e) = self.save(&state.interactions) {
+            tracing::warn!(path = %self.path.display(), error = %e, "Failed to write cassette");
+        }
+    }
+
+    fn save(&self, interactions: &[Interaction]) -> std::io::Result<()> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(&self.path, serde_json::to_vec_pretty(interactions)?)
+    }
+}
+
+impl From<&ApiRequest> for RecordedRequest {
+    fn from(request: &ApiRequest) -> Self {
+        let query = request
+            .query
+            .iter()
+            .map(|(key, value)| match SECRET_KEYS.contains(&key.as_str()) {
+                true => (key.clone(), REDACTED.to_string()),
+                false => (key.clone(), value.clone()),
+            })
+            .collect();
+
+        Self {
+            method: request.method.to_string(),
+            path: request.path.clone(),
+            query,
+            body: request.body.clone().map(scrub),
+        }
+    }
+}
+
+/// Replace the values of [`SECRET_KEYS`] at any depth
+fn scrub(value: Value) -> Value {
+    match value {
+        Value::Object(map) => Value::Object(
+            map.into_iter()
+                .map(|(key, value)| match SECRET_KEYS.contains(&key.as_str()) {
+                    true => (key, Value::String(REDACTED.to_string())),
+                    false => (key, scrub(value)),
+                })
+                .collect(),
+        ),
+        Value::Array(items) => Value::Array(items.into_iter().map(scrub).collect()),
+        other => other,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "im")]
+    use crate::{LarkClient, LarkConfig};
+    #[cfg(feature = "im")]
+    use larkrs_mock::MockLarkServer;
+    #[cfg(feature = "im")]
+    use std::sync::Arc;
+
+    #[cfg(feature = "im")]
+    fn client(base_url: &str, cassette: Arc<Cassette>) -> LarkClient {
+        let config = LarkConfig::new("cli_cassette", "super-secret")
+            .with_base_url(base_url)
+            .with_cassette(cassette);
+        LarkClient::new(config).unwrap()
+    }
+
+    #[cfg(feature = "im")]
+    #[tokio::test]
+    async fn test_record_then_replay() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("chats.json");
+
+        let server = MockLarkServer::start().await;
+        server.state().add_chat("oc_1", "recorded");
+        let recorder = Arc::new(Cassette::record(&path));
+        let chats = client(&server.uri(), recorder)
+            .chat()
+            .get_chat_group_list()
+            .await
+            .unwrap();
+        assert_eq!(chats[0].name, "recorded");
+        drop(server);
+
+        let content = fs::read_to_string(&path).unwrap();
+        assert!(!content.contains("super-secret"));
+        assert!(!content.contains("t-mock"));
+
+        // The server is gone, every response comes from the file
+        let cassette = Arc::new(Cassette::replay(&path).unwrap());
+        let chats = client("http://127.0.0.1:9", cassette.clone())
+            .chat()
+            .get_chat_group_list()
+            .await
+            .unwrap();
+        assert_eq!(chats[0].name, "recorded");
+        assert_eq!(cassette.remaining(), 0);
+    }
+
+    #[cfg(feature = "im")]
+    #[tokio::test]
+    async fn test_unmatched_request_fails() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("empty.json");
+        fs::write(&path, "[]").unwrap();
+
+        let cassette = Arc::new(Cassette::replay(&path).unwrap());
+        let err = client("http://127.0.0.1:9", cassette)
+            .chat()
+            .send_text_message("oc_1", "hello")
+            .await
+            .unwrap_err();
+        assert!(matches!(err, LarkError::Config(msg) if msg.contains("no unused interaction")));
+    }
+
+    #[test]
+    fn test_scrub_nested_secrets() {
+        let value = serde_json::json!({
+            "app_id": "cli_1",
+            "app_secret": "s",
+            "data": [{"access_token": "u-1", "name": "kept"}]
+        });
+        assert_eq!(
+            scrub(value),
+            serde_json::json!({
+                "app_id": "cli_1",
+                "app_secret": REDACTED,
+                "data": [{"access_token": REDACTED, "name": "kept"}]
+            })
+        );
+    }
+}
@@ -0,0 +1,395 @@
+use reqwest::{Client, Method};
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use std::sync::Arc;
+use std::time::Instant;
+use tracing::{Instrument, field, info_span};
+
+use crate::LarkApiResponse;
+use crate::auth::marketplace::MarketplaceTokenManager;
+use crate::auth::user::UserTokenManager;
+use crate::auth::{FeishuTokenManager, INVALID_TOKEN_CODES};
+#[cfg(feature = "bitable")]
+use crate::bitable::table::BitableTableClient;
+#[cfg(feature = "im")]
+use crate::bot::chat::ChatClient;
+use crate::config::{AppType, LarkConfig};
+use crate::error::{LarkError, Result};
+use crate::http::{ApiRequest, RawResponse, Transport};
+use crate::retry::RetryPolicy;
+#[cfg(feature = "wiki")]
+use crate::wiki::client::WikiClient;
+
+/// Identity API calls are made with
+#[derive(Clone, Default)]
+pub enum AuthMode {
+    /// Tenant access token of the app, i.e. act as the bot
+    #[default]
+    Tenant,
+    /// User access token, i.e. act as the user who authorized the app
+    User(Arc<UserTokenManager>),
+    /// Tenant access token of the tenant with this `tenant_key` (marketplace apps)
+    TenantKey(String),
+}
+
+/// Entry point of the library.
+///
+/// A `LarkClient` owns the configuration, one pooled HTTP client and one token cache.
+/// It is cheap to clone, and every API client derived from it shares the same state.
+#[derive(Clone)]
+pub struct LarkClient {
+    transport: Arc<Transport>,
+    token_manager: Arc<FeishuTokenManager>,
+    marketplace: Arc<MarketplaceTokenManager>,
+    retry: RetryPolicy,
+    auth: AuthMode,
+}
+
+impl LarkClient {
+    pub fn new(config: LarkConfig) -> Result<Self> {
+        let http = config.http_client()?;
+
+        let retry = config.retry.clone();
+        let transport = Arc::new(Transport::new(Arc::new(config), http));
+        let token_manager = Arc::new(FeishuTokenManager::with_transport(transport.clone()));
+        let marketplace = Arc::new(MarketplaceTokenManager::with_transport(transport.clone()));
+
+        Ok(Self {
+            transport,
+            token_manager,
+            marketplace,
+            retry,
+            auth: AuthMode::Tenant,
+        })
+    }
+
+    /// Build a client from the `FEISHU_*` environment variables, see [`LarkConfig::from_env`].
+    pub fn from_env() -> Result<Self> {
+        Self::new(LarkConfig::from_env())
+    }
+
+    pub fn config(&self) -> &LarkConfig {
+        self.transport.config()
+    }
+
+    pub fn http(&self) -> &Client {
+        self.transport.client()
+    }
+
+    /// Override the retry policy for this client and the API clients derived from it.
+    ///
+    /// Clones keep sharing the HTTP pool and token cache, so this can be used to give a
+    /// single API client its own policy: `client.clone().with_retry_policy(p).bitable()`.
+    pub fn with_retry_policy(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    pub fn retry_policy(&self) -> &RetryPolicy {
+        &self.retry
+    }
+
+    /// A client that calls APIs as the user whose tokens `user` manages
+    pub fn as_user(&self, user: Arc<UserTokenManager>) -> Self {
+        Self {
+            auth: AuthMode::User(user),
+            ..self.clone()
+        }
+    }
+
+    /// A client that calls APIs in the tenant identified by `tenant_key` (marketplace apps)
+    pub fn for_tenant(&self, tenant_key: impl Into<String>) -> Self {
+        Self {
+            auth: AuthMode::TenantKey(tenant_key.into()),
+            ..self.clone()
+        }
+    }
+
+    /// A client that calls APIs with the tenant access token of the app
+    pub fn as_tenant(&self) -> Self {
+        Self {
+            auth: AuthMode::Tenant,
+            ..self.clone()
+        }
+    }
+
+    pub fn auth_mode(&self) -> &AuthMode {
+        &self.auth
+    }
+
+    /// Create a manager for the OAuth authorization-code flow sharing this client's HTTP pool
+    pub fn user_token_manager(&self) -> UserTokenManager {
+        UserTokenManager::with_transport(self.transport.clone())
+    }
+
+    /// Token manager of marketplace apps, feed it `app_ticket` events with
+    /// [`MarketplaceTokenManager::handle_event`]
+    pub fn marketplace(&self) -> &MarketplaceTokenManager {
+        &self.marketplace
+    }
+
+    pub fn token_manager(&self) -> &FeishuTokenManager {
+        &self.token_manager
+    }
+
+    #[cfg(feature = "bitable")]
+    pub fn bitable(&self) -> BitableTableClient {
+        BitableTableClient::with_client(self.clone())
+    }
+
+    #[cfg(feature = "im")]
+    pub fn chat(&self) -> ChatClient {
+        ChatClient::with_client(self.clone())
+    }
+
+    #[cfg(feature = "wiki")]
+    pub fn wiki(&self) -> WikiClient {
+        WikiClient::with_client(self.clone())
+    }
+
+    /// Call any endpoint, e.g. one not wrapped by this crate, and keep the response
+    /// metadata such as the `x-tt-logid`.
+    ///
+    /// `path` is relative to the configured domain, e.g. `/open-apis/contact/v3/users/ou_xxx`.
+    pub async fn call<T: DeserializeOwned + Default>(
+        &self,
+        method: Method,
+        path: &str,
+        body: Option<&Value>,
+    ) -> Result<LarkApiResponse<T>> {
+        let mut request = ApiRequest::new(method, path);
+        request.body = body.cloned();
+        self.send_request(request).await?.into_response()
+    }
+
+    /// Send `request` with the access token of the current auth mode and return `data`.
+    ///
+    /// If Lark reports the cached token as invalid (revoked or rotated before its local
+    /// expiry), the token is refreshed and the request is replayed once.
+    pub(crate) async fn send<T: DeserializeOwned + Default>(
+        &self,
+        request: ApiRequest,
+    ) -> Result<T> {
+        self.send_request(request).await?.into_data()
+    }
+
+    /// Send `request` inside a `lark.request` span recording the endpoint, the final Lark
+    /// code, the `x-tt-logid` and the latency, retries and token replays included
+    async fn send_request(&self, request: ApiRequest) -> Result<RawResponse> {
+        let span = info_span!(
+            "lark.request",
+            method = %request.method,
+            endpoint = %request.path,
+            code = field::Empty,
+            status = field::Empty,
+            log_id = field::Empty,
+            latency_ms = field::Empty,
+        );
+        let started = Instant::now();
+        let outcome = self.execute(&request).instrument(span.clone()).await;
+
+        span.record("latency_ms", started.elapsed().as_millis() as u64);
+        let _entered = span.enter();
+        match &outcome {
+            Ok(response) => {
+                span.record("status", response.status);
+                if let Some(log_id) = &response.log_id {
+                    span.record("log_id", field::display(log_id));
+                }
+                match response.code() {
+                    Some(code) => {
+                        span.record("code", code);
+                        if code != 0 {
+                            tracing::warn!(code, "Lark request failed");
+                        }
+                    }
+                    None => {
+                        tracing::warn!(status = response.status, "Lark response is not an envelope")
+                    }
+                }
+            }
+            Err(e) => tracing::warn!(error = %e, "Lark request failed"),
+        }
+        outcome
+    }
+
+    async fn execute(&self, request: &ApiRequest) -> Result<RawResponse> {
+        let token = self.access_token().await?;
+        let mut response = self
+            .retry
+            .execute(&self.transport, request, Some(&token))
+            .await?;
+
+        if let Some(code) = response.code()
+            && INVALID_TOKEN_CODES.contains(&code)
+        {
+            tracing::warn!(
+                code,
+                path = %request.path,
+                "Access token rejected, refreshing and replaying"
+            );
+            let token = match &self.auth {
+                AuthMode::Tenant => self.token_manager.refresh_rejected(&token).await?,
+                AuthMode::User(user) => user.refresh_rejected(&token).await?,
+                AuthMode::TenantKey(tenant_key) => {
+                    self.marketplace
+                        .refresh_rejected(tenant_key, &token)
+                        .await?
+                }
+            };
+            response = self
+                .retry
+                .execute(&self.transport, request, Some(&token))
+                .await?;
+        }
+
+        Ok(response)
+    }
+
+    async fn access_token(&self) -> Result<String> {
+        match &self.auth {
+            AuthMode::Tenant if self.config().app_type == AppType::Marketplace => {
+                Err(LarkError::Config(
+                    "marketplace apps need a tenant_key, use LarkClient::for_tenant".to_string(),
+                ))
+            }
+            AuthMode::Tenant => self.token_manager.get_token().await,
+            AuthMode::User(user) => user.get_token().await,
+            AuthMode::TenantKey(tenant_key) => self.marketplace.get_tenant_token(tenant_key).await,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "im")]
+    use serde_json::json;
+    #[cfg(feature = "im")]
+    use wiremock::matchers::{header, method, path};
+    #[cfg(feature = "im")]
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[test]
+    fn test_sub_clients_share_token_cache() {
+        let client = LarkClient::new(LarkConfig::new("id", "secret")).unwrap();
+        let other = client.clone();
+
+        assert!(std::ptr::eq(client.token_manager(), other.token_manager()));
+        assert_eq!(client.config().base_url, crate::config::FEISHU_BASE_URL);
+    }
+
+    #[cfg(feature = "im")]
+    async fn token_server() -> MockServer {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/open-apis/auth/v3/tenant_access_token/internal"))
+            .respond_with(token_response("t-revoked"))
+            .up_to_n_times(1)
+            .with_priority(1)
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/open-apis/auth/v3/tenant_access_token/internal"))
+            .respond_with(token_response("t-fresh"))
+            .expect(1)
+            .mount(&server)
+            .await;
+        server
+    }
+
+    #[cfg(feature = "im")]
+    fn token_response(token: &str) -> ResponseTemplate {
+        ResponseTemplate::new(200).set_body_json(json!({
+            "code": 0,
+            "msg": "ok",
+            "tenant_access_token": token,
+            "expire": 7200
+        }))
+    }
+
+    #[cfg(feature = "im")]
+    fn invalid_token_response() -> ResponseTemplate {
+        ResponseTemplate::new(400).set_body_json(json!({
+            "code": 99991663,
+            "msg": "Invalid access token for authorization. Please make a request with token attached."
+        }))
+    }
+
+    #[cfg(feature = "im")]
+    #[tokio::test]
+    async fn test_replay_after_invalid_token() {
+        let server = token_server().await;
+        Mock::given(method("GET"))
+            .and(path("/open-apis/im/v1/chats"))
+            .and(header("Authorization", "Bearer t-revoked"))
+            .respond_with(invalid_token_response())
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/open-apis/im/v1/chats"))
+            .and(header("Authorization", "Bearer t-fresh"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "code": 0,
+                "msg": "success",
+                "data": {"items": [{"chat_id": "oc_1", "name": "test"}], "has_more": false}
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client =
+            LarkClient::new(LarkConfig::new("id", "secret").with_base_url(server.uri())).unwrap();
+        let chats = client.chat().get_chat_group_list().await.unwrap();
+        assert_eq!(chats.len(), 1);
+        assert_eq!(client.token_manager().get_token().await.unwrap(), "t-fresh");
+    }
+
+    #[cfg(feature = "im")]
+    #[tokio::test]
+    async fn test_replay_only_once() {
+        let server = token_server().await;
+        Mock::given(method("GET"))
+            .and(path("/open-apis/im/v1/chats"))
+            .respond_with(invalid_token_response())
+            .expect(2)
+            .mount(&server)
+            .await;
+
+        let client =
+            LarkClient::new(LarkConfig::new("id", "secret").with_base_url(server.uri())).unwrap();
+        let err = client.chat().get_chat_group_list().await.unwrap_err();
+        assert_eq!(err.code(), Some(99991663));
+        assert_eq!(err.kind(), crate::ErrorKind::Auth);
+    }
+
+    #[cfg(feature = "im")]
+    #[tokio::test]
+    #[tracing_test::traced_test]
+    async fn test_log_id_surfaced() {
+        let server = larkrs_mock::MockLarkServer::start().await;
+        server.state().add_chat("oc_1", "test");
+        let client =
+            LarkClient::new(LarkConfig::new("id", "secret").with_base_url(server.uri())).unwrap();
+
+        let response: LarkApiResponse<Value> = client
+            .call(Method::GET, "/open-apis/im/v1/chats", None)
+            .await
+            .unwrap();
+        assert_eq!(response.data["items"][0]["chat_id"], "oc_1");
+        assert_eq!(response.meta.status, 200);
+        assert!(response.meta.log_id.unwrap().starts_with("mock"));
+
+        let err = client
+            .chat()
+            .send_text_message("oc_unknown", "hello")
+            .await
+            .unwrap_err();
+        let log_id = err.log_id().unwrap().to_string();
+        assert!(logs_contain("lark.request"));
+        assert!(logs_contain(&format!("log_id={}", log_id)));
+        assert!(logs_contain("code=230002"));
+    }
+}
@@ -0,0 +1,368 @@
+use reqwest::{Certificate, Client, Proxy};
+use serde::Deserialize;
+use std::env;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::auth::store::{FileTokenStore, MemoryTokenStore, TokenStore};
+use crate::cassette::Cassette;
+use crate::error::{LarkError, Result};
+use crate::middleware::Middleware;
+use crate::ratelimit::RateLimiter;
+use crate::retry::RetryPolicy;
+
+/// Base URL of the Feishu (China) open platform.
+pub const FEISHU_BASE_URL: &str = "https://open.feishu.cn";
+
+/// Base URL of the Lark (international) open platform.
+pub const LARK_BASE_URL: &str = "https://open.larksuite.com";
+
+/// Default `User-Agent` of outbound requests
+pub const DEFAULT_USER_AGENT: &str = concat!("larkrs-client/", env!("CARGO_PKG_VERSION"));
+
+/// Kind of Lark app, which decides how tenant access tokens are obtained
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AppType {
+    /// Self-built ("internal") app, installed in a single tenant
+    #[default]
+    SelfBuilt,
+    /// Marketplace (ISV) app, tokens are obtained per tenant from an `app_ticket`
+    Marketplace,
+}
+
+/// Configuration shared by every client derived from a [`crate::client::LarkClient`].
+///
+/// Holds the app credentials, the open platform domain and HTTP settings.
+#[derive(Debug, Clone)]
+pub struct LarkConfig {
+    /// App ID
+    pub app_id: String,
+    /// App secret
+    pub app_secret: String,
+    /// Self-built or marketplace app (default: self-built)
+    pub app_type: AppType,
+    /// Open platform domain, e.g. [`FEISHU_BASE_URL`], [`LARK_BASE_URL`] or a local mock server
+    pub base_url: String,
+    /// Total timeout applied to every request (default: 30 seconds)
+    pub timeout: Duration,
+    /// Timeout of the connect phase, TLS handshake included (default: none besides `timeout`)
+    pub connect_timeout: Option<Duration>,
+    /// Timeout of each read of the response, reset after every chunk (default: none)
+    pub read_timeout: Option<Duration>,
+    /// Proxy for every request, e.g. `http://proxy.corp:8080` (default: the `HTTP_PROXY`,
+    /// `HTTPS_PROXY` and `NO_PROXY` environment variables)
+    pub proxy: Option<String>,
+    /// PEM bundle of extra root certificates, e.g. a corporate CA (default: system roots)
+    pub ca_bundle: Option<PathBuf>,
+    /// `User-Agent` header (default: [`DEFAULT_USER_AGENT`])
+    pub user_agent: String,
+    /// Default retry policy of clients built from this configuration
+    pub retry: RetryPolicy,
+    /// Where tenant access tokens are persisted (default: in memory only)
+    pub token_store: Arc<dyn TokenStore>,
+    /// Recorded interactions to record to or replay from, instead of plain HTTP calls
+    pub cassette: Option<Arc<Cassette>>,
+    /// Client-side limiter delaying requests to stay within Lark quotas (default: none)
+    pub rate_limiter: Option<Arc<RateLimiter>>,
+    /// Interceptors every HTTP attempt passes through, outermost first
+    pub middleware: Vec<Arc<dyn Middleware>>,
+}
+
+impl LarkConfig {
+    pub fn new(app_id: impl Into<String>, app_secret: impl Into<String>) -> Self {
+        Self {
+            app_id: app_id.into(),
+            app_secret: app_secret.into(),
+            app_type: AppType::default(),
+            base_url: FEISHU_BASE_URL.to_string(),
+            timeout: Duration::from_secs(30),
+            connect_timeout: None,
+            read_timeout: None,
+            proxy: None,
+            ca_bundle: None,
+            user_agent: DEFAULT_USER_AGENT.to_string(),
+            retry: RetryPolicy::default(),
+            token_store: Arc::new(MemoryTokenStore::new()),
+            cassette: None,
+            rate_limiter: None,
+            middleware: Vec::new(),
+        }
+    }
+
+    /// Build a configuration from `FEISHU_APP_ID`, `FEISHU_APP_SECRET` and the optional
+    /// `FEISHU_BASE_URL`, `FEISHU_TIMEOUT`, `FEISHU_CONNECT_TIMEOUT`, `FEISHU_READ_TIMEOUT`,
+    /// `FEISHU_USER_AGENT`, `FEISHU_PROXY`, `FEISHU_CA_BUNDLE` and `FEISHU_TOKEN_CACHE`
+    /// environment variables.
+    ///
+    /// Timeouts are in seconds, e.g. `120` or `2.5`. `FEISHU_TOKEN_CACHE` is the path of a
+    /// [`FileTokenStore`]; `default` selects [`FileTokenStore::default_path`].
+    pub fn from_env() -> Self {
+        let mut config = Self::new(
+            env::var("FEISHU_APP_ID").unwrap_or_default(),
+            env::var("FEISHU_APP_SECRET").unwrap_or_default(),
+        );
+
+        if let Some(base_url) = env_var("FEISHU_BASE_URL") {
+            config = config.with_base_url(base_url);
+        }
+        config.with_env_settings()
+    }
+
+    /// Apply the app-independent `FEISHU_TIMEOUT`, `FEISHU_CONNECT_TIMEOUT`,
+    /// `FEISHU_READ_TIMEOUT`, `FEISHU_USER_AGENT`, `FEISHU_PROXY`, `FEISHU_CA_BUNDLE` and
+    /// `FEISHU_TOKEN_CACHE` environment variables.
+    ///
+    /// Invalid timeouts are ignored with a warning.
+    pub(crate) fn with_env_settings(mut self) -> Self {
+        if let Some(timeout) = env_secs("FEISHU_TIMEOUT") {
+            self = self.with_timeout(timeout);
+        }
+        if let Some(timeout) = env_secs("FEISHU_CONNECT_TIMEOUT") {
+            self = self.with_connect_timeout(timeout);
+        }
+        if let Some(timeout) = env_secs("FEISHU_READ_TIMEOUT") {
+            self = self.with_read_timeout(timeout);
+        }
+        if let Some(user_agent) = env_var("FEISHU_USER_AGENT") {
+            self = self.with_user_agent(user_agent);
+        }
+        if let Some(proxy) = env_var("FEISHU_PROXY") {
+            self = self.with_proxy(proxy);
+        }
+        if let Some(ca_bundle) = env_var("FEISHU_CA_BUNDLE") {
+            self = self.with_ca_bundle(ca_bundle);
+        }
+
+        let cache_path = match env_var("FEISHU_TOKEN_CACHE").as_deref() {
+            Some("default") => FileTokenStore::default_path(),
+            Some(path) => Some(path.into()),
+            None => None,
+        };
+        match cache_path {
+            Some(path) => self.with_token_store(Arc::new(FileTokenStore::new(path))),
+            None => self,
+        }
+    }
+
+    pub fn with_app_type(mut self, app_type: AppType) -> Self {
+        self.app_type = app_type;
+        self
+    }
+
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into().trim_end_matches('/').to_string();
+        self
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    pub fn with_connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = Some(timeout);
+        self
+    }
+
+    pub fn with_read_timeout(mut self, timeout: Duration) -> Self {
+        self.read_timeout = Some(timeout);
+        self
+    }
+
+    pub fn with_proxy(mut self, proxy: impl Into<String>) -> Self {
+        self.proxy = Some(proxy.into());
+        self
+    }
+
+    pub fn with_ca_bundle(mut self, path: impl Into<PathBuf>) -> Self {
+        self.ca_bundle = Some(path.into());
+        self
+    }
+
+    pub fn with_user_agent(mut self, user_agent: impl Into<String>) -> Self {
+        self.user_agent = user_agent.into();
+        self
+    }
+
+    pub fn with_retry_policy(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Persist tenant access tokens in `store`, e.g. a [`FileTokenStore`] shared by several
+    /// processes running the same app
+    pub fn with_token_store(mut self, store: Arc<dyn TokenStore>) -> Self {
+        self.token_store = store;
+        self
+    }
+
+    /// Record every request to, or replay every request from, `cassette`
+    pub fn with_cassette(mut self, cassette: Arc<Cassette>) -> Self {
+        self.cassette = Some(cassette);
+        self
+    }
+
+    /// Delay requests according to `limiter`, e.g. [`RateLimiter::lark_defaults`]
+    pub fn with_rate_limiter(mut self, limiter: Arc<RateLimiter>) -> Self {
+        self.rate_limiter = Some(limiter);
+        self
+    }
+
+    /// Append `middleware` to the stack, see [`crate::middleware`]
+    pub fn with_middleware(mut self, middleware: Arc<dyn Middleware>) -> Self {
+        self.middleware.push(middleware);
+        self
+    }
+
+    /// Build the HTTP client applying the timeouts, proxy, CA bundle and user agent of this
+    /// configuration
+    pub fn http_client(&self) -> Result<Client> {
+        let mut builder = Client::builder()
+            .timeout(self.timeout)
+            .user_agent(&self.user_agent);
+
+        if let Some(timeout) = self.connect_timeout {
+            builder = builder.connect_timeout(timeout);
+        }
+        if let Some(timeout) = self.read_timeout {
+            builder = builder.read_timeout(timeout);
+        }
+        if let Some(proxy) = &self.proxy {
+            let proxy = Proxy::all(proxy)
+                .map_err(|e| LarkError::Config(format!("invalid proxy {}: {}", proxy, e)))?;
+            builder = builder.proxy(proxy);
+        }
+        if let Some(path) = &self.ca_bundle {
+            let invalid = |e: &dyn std::fmt::Display| {
+                LarkError::Config(format!("invalid CA bundle {}: {}", path.display(), e))
+            };
+            let pem = std::fs::read(path).map_err(|e| invalid(&e))?;
+            let certs = Certificate::from_pem_bundle(&pem).map_err(|e| invalid(&e))?;
+            if certs.is_empty() {
+                return Err(invalid(&"no certificate found"));
+            }
+            for cert in certs {
+                builder = builder.add_root_certificate(cert);
+            }
+        }
+
+        Ok(builder.build()?)
+    }
+
+    /// Key under which the tenant access token of this app is stored
+    pub(crate) fn token_store_key(&self) -> String {
+        format!("{}|{}", self.base_url, self.app_id)
+    }
+
+    /// Join an API path such as `/open-apis/im/v1/chats` onto the configured domain.
+    pub fn url(&self, path: &str) -> String {
+        format!(
+            "{}/{}",
+            self.base_url.trim_end_matches('/'),
+            path.trim_start_matches('/')
+        )
+    }
+}
+
+fn env_var(name: &str) -> Option<String> {
+    env::var(name).ok().filter(|value| !value.is_empty())
+}
+
+/// Duration of the environment variable `name`, given in seconds
+fn env_secs(name: &str) -> Option<Duration> {
+    let value = env_var(name)?;
+    let secs = parse_secs(&value);
+    if secs.is_none() {
+        tracing::warn!("ignoring {}={}, expected a number of seconds", name, value);
+    }
+    secs
+}
+
+fn parse_secs(value: &str) -> Option<Duration> {
+    let secs = value.trim().parse::<f64>().ok()?;
+    Duration::try_from_secs_f64(secs)
+        .ok()
+        .filter(|secs| !secs.is_zero())
+}
+
+impl Default for LarkConfig {
+    fn default() -> Self {
+        Self::from_env()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_url_join() {
+        let config = LarkConfig::new("id", "secret").with_base_url("http://127.0.0.1:8080/");
+        assert_eq!(
+            config.url("/open-apis/im/v1/chats"),
+            "http://127.0.0.1:8080/open-apis/im/v1/chats"
+        );
+
+        let config = LarkConfig::new("id", "secret").with_base_url(LARK_BASE_URL);
+        assert_eq!(
+            config.url("open-apis/wiki/v2/spaces"),
+            "https://open.larksuite.com/open-apis/wiki/v2/spaces"
+        );
+    }
+
+    #[test]
+    fn test_parse_secs() {
+        assert_eq!(parse_secs("120"), Some(Duration::from_secs(120)));
+        assert_eq!(parse_secs(" 2.5 "), Some(Duration::from_millis(2500)));
+        assert_eq!(parse_secs("0"), None);
+        assert_eq!(parse_secs("-1"), None);
+        assert_eq!(parse_secs("30s"), None);
+    }
+
+    #[tokio::test]
+    async fn test_proxy_and_user_agent() {
+        use wiremock::matchers::{header, method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        // Requests to an unresolvable domain only succeed through the proxy
+        let proxy = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/open-apis/auth/v3/tenant_access_token/internal"))
+            .and(header("user-agent", "larkrs-test"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "code": 0, "msg": "ok", "tenant_access_token": "t-proxied", "expire": 7200
+            })))
+            .expect(1)
+            .mount(&proxy)
+            .await;
+
+        let config = LarkConfig::new("id", "secret")
+            .with_base_url("http://open.feishu.invalid")
+            .with_proxy(proxy.uri())
+            .with_user_agent("larkrs-test");
+        let client = crate::LarkClient::new(config).unwrap();
+        assert_eq!(
+            client.token_manager().get_token().await.unwrap(),
+            "t-proxied"
+        );
+    }
+
+    #[test]
+    fn test_invalid_http_settings() {
+        let dir = tempfile::tempdir().unwrap();
+        let bundle = dir.path().join("ca.pem");
+        std::fs::write(&bundle, "not a certificate").unwrap();
+
+        let config = LarkConfig::new("id", "secret").with_ca_bundle(&bundle);
+        let err = config.http_client().err().unwrap();
+        assert!(matches!(err, LarkError::Config(msg) if msg.contains("invalid CA bundle")));
+
+        let config = LarkConfig::new("id", "secret").with_proxy("::not a url::");
+        assert!(matches!(
+            config.http_client().err().unwrap(),
+            LarkError::Config(_)
+        ));
+    }
+}
@@ -0,0 +1,181 @@
+use thiserror::Error;
+
+/// Result type returned by every client method
+pub type Result<T, E = LarkError> = std::result::Result<T, E>;
+
+/// Error returned by the Lark (Feishu) clients
+#[derive(Error, Debug)]
+pub enum LarkError {
+    #[error("Network error: {0}")]
+    Network(#[from] reqwest::Error),
+
+    #[error("JSON serialization error: {0}")]
+    Serde(#[from] serde_json::Error),
+
+    #[error(
+        "API error: {msg} (code: {code}, status: {status}, log_id: {})",
+        log_id.as_deref().unwrap_or("-")
+    )]
+    Api {
+        /// Lark business code, see https://open.feishu.cn/document/server-docs/api-call-guide/generic-error-code
+        code: i32,
+        /// Error message returned by Lark
+        msg: String,
+        /// HTTP status code of the response
+        status: u16,
+        /// Value of the `x-tt-logid` response header, asked for by Lark support
+        log_id: Option<String>,
+        /// Classification derived from `code` and `status`
+        kind: ErrorKind,
+    },
+
+    #[error("Configuration error: {0}")]
+    Config(String),
+
+    #[error("Invalid argument: {0}")]
+    InvalidArgument(String),
+
+    #[error("Unauthorized: {0}")]
+    Unauthorized(String),
+}
+
+/// Broad classification of a [`LarkError`] so callers can react without knowing every code
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorKind {
+    /// Access token missing, invalid or expired, or wrong app credentials
+    Auth,
+    /// The app or user lacks the scope or resource permission
+    Permission,
+    /// Frequency limit reached
+    RateLimit,
+    /// Request parameters rejected by Lark or by the client
+    Validation,
+    /// The app, table, record, chat or document does not exist
+    NotFound,
+    /// Lark internal error
+    Server,
+    /// Transport or decoding failure before a Lark response was obtained
+    Network,
+    /// Anything not covered above
+    Other,
+}
+
+impl ErrorKind {
+    /// Classify a Lark business code, falling back on the HTTP status
+    pub fn classify(code: i32, status: u16) -> Self {
+        match code {
+            // Generic access token and credential errors
+            99991661 | 99991663 | 99991664 | 99991665 | 99991668 | 99991671 | 99991677 | 10003
+            | 10014 => ErrorKind::Auth,
+            99991672 | 99991679 | 99991401 => ErrorKind::Permission,
+            99991400 => ErrorKind::RateLimit,
+            // Bitable
+            1254302 | 1254301 | 1254304 => ErrorKind::Permission,
+            1254040..=1254045 => ErrorKind::NotFound,
+            1254290 | 1254291 => ErrorKind::RateLimit,
+            1254000..=1254099 => ErrorKind::Validation,
+            1255000..=1255999 => ErrorKind::Server,
+            // IM
+            230002 | 230006 | 230027 => ErrorKind::Permission,
+            230020 => ErrorKind::RateLimit,
+            230001 => ErrorKind::Validation,
+            // Wiki and docx
+            131005 | 1770002 => ErrorKind::NotFound,
+            131006 | 1770032 => ErrorKind::Permission,
+            _ => match status {
+                401 => ErrorKind::Auth,
+                403 => ErrorKind::Permission,
+                404 => ErrorKind::NotFound,
+                429 => ErrorKind::RateLimit,
+                400 | 422 => ErrorKind::Validation,
+                500..=599 => ErrorKind::Server,
+                _ => ErrorKind::Other,
+            },
+        }
+    }
+}
+
+impl LarkError {
+    pub(crate) fn api(
+        code: i32,
+        msg: impl Into<String>,
+        status: u16,
+        log_id: Option<String>,
+    ) -> Self {
+        LarkError::Api {
+            code,
+            msg: msg.into(),
+            status,
+            log_id,
+            kind: ErrorKind::classify(code, status),
+        }
+    }
+
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            LarkError::Network(_) => ErrorKind::Network,
+            LarkError::Serde(_) => ErrorKind::Network,
+            LarkError::Api { kind, .. } => *kind,
+            LarkError::Config(_) => ErrorKind::Other,
+            LarkError::InvalidArgument(_) => ErrorKind::Validation,
+            LarkError::Unauthorized(_) => ErrorKind::Auth,
+        }
+    }
+
+    /// Lark business code, if the error came from a Lark response
+    pub fn code(&self) -> Option<i32> {
+        match self {
+            LarkError::Api { code, .. } => Some(*code),
+            _ => None,
+        }
+    }
+
+    /// HTTP status, if a response was received
+    pub fn status(&self) -> Option<u16> {
+        match self {
+            LarkError::Api { status, .. } => Some(*status),
+            LarkError::Network(e) => e.status().map(|s| s.as_u16()),
+            _ => None,
+        }
+    }
+
+    /// `x-tt-logid` of the failed request, if any
+    pub fn log_id(&self) -> Option<&str> {
+        match self {
+            LarkError::Api { log_id, .. } => log_id.as_deref(),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify() {
+        assert_eq!(ErrorKind::classify(99991663, 400), ErrorKind::Auth);
+        assert_eq!(ErrorKind::classify(99991400, 400), ErrorKind::RateLimit);
+        assert_eq!(ErrorKind::classify(1254043, 200), ErrorKind::NotFound);
+        assert_eq!(ErrorKind::classify(1254302, 403), ErrorKind::Permission);
+        assert_eq!(ErrorKind::classify(1254001, 400), ErrorKind::Validation);
+        assert_eq!(ErrorKind::classify(123, 429), ErrorKind::RateLimit);
+        assert_eq!(ErrorKind::classify(123, 503), ErrorKind::Server);
+        assert_eq!(ErrorKind::classify(123, 200), ErrorKind::Other);
+    }
+
+    #[test]
+    fn test_api_error_accessors() {
+        let err = LarkError::api(
+            230002,
+            "Bot is not in the chat",
+            400,
+            Some("2025abc".into()),
+        );
+        assert_eq!(err.kind(), ErrorKind::Permission);
+        assert_eq!(err.code(), Some(230002));
+        assert_eq!(err.status(), Some(400));
+        assert_eq!(err.log_id(), Some("2025abc"));
+        assert!(err.to_string().contains("log_id: 2025abc"));
+    }
+}
@@ -0,0 +1,267 @@
+use reqwest::{Client, Method};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tracing::{Instrument, debug_span, field};
+
+use crate::cassette::CassetteMode;
+use crate::config::LarkConfig;
+use crate::error::{LarkError, Result};
+use crate::middleware::Next;
+use crate::{LarkApiResponse, ResponseMeta};
+
+/// One Lark API call, kept as plain data so the pipeline can rebuild and replay it.
+#[derive(Debug, Clone)]
+pub struct ApiRequest {
+    pub method: Method,
+    /// Path below the domain, e.g. `/open-apis/im/v1/chats`
+    pub path: String,
+    pub query: Vec<(String, String)>,
+    /// Extra HTTP headers, e.g. added by a [`crate::middleware::Middleware`]
+    pub headers: Vec<(String, String)>,
+    pub body: Option<Value>,
+    /// Whether the call can be repeated safely after a network or server error
+    pub idempotent: bool,
+}
+
+impl ApiRequest {
+    pub fn new(method: Method, path: impl Into<String>) -> Self {
+        let idempotent = matches!(method, Method::GET | Method::PUT | Method::DELETE);
+        Self {
+            method,
+            path: path.into(),
+            query: Vec::new(),
+            headers: Vec::new(),
+            body: None,
+            idempotent,
+        }
+    }
+
+    // Only used by the API modules, which are all optional
+    #[cfg_attr(
+        not(any(feature = "bitable", feature = "im", feature = "wiki")),
+        allow(dead_code)
+    )]
+    pub fn get(path: impl Into<String>) -> Self {
+        Self::new(Method::GET, path)
+    }
+
+    pub fn post(path: impl Into<String>) -> Self {
+        Self::new(Method::POST, path)
+    }
+
+    pub fn put(path: impl Into<String>) -> Self {
+        Self::new(Method::PUT, path)
+    }
+
+    pub fn delete(path: impl Into<String>) -> Self {
+        Self::new(Method::DELETE, path)
+    }
+
+    pub fn patch(path: impl Into<String>) -> Self {
+        Self::new(Method::PATCH, path)
+    }
+
+    pub fn query(mut self, key: &str, value: impl ToString) -> Self {
+        self.query.push((key.to_string(), value.to_string()));
+        self
+    }
+
+    pub fn header(mut self, name: &str, value: impl ToString) -> Self {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    pub fn json<B: Serialize + ?Sized>(mut self, body: &B) -> Result<Self> {
+        self.body = Some(serde_json::to_value(body)?);
+        Ok(self)
+    }
+
+    /// Mark a read-only `POST` (e.g. a search) as safe to retry
+    pub fn idempotent(mut self) -> Self {
+        self.idempotent = true;
+        self
+    }
+}
+
+/// Raw outcome of an [`ApiRequest`], before the Lark envelope is checked.
+#[derive(Debug, Clone)]
+pub struct RawResponse {
+    pub status: u16,
+    /// `x-tt-logid` response header
+    pub log_id: Option<String>,
+    /// Wait hinted by `x-ogw-ratelimit-reset` or `Retry-After`
+    pub retry_after: Option<Duration>,
+    pub body: String,
+}
+
+impl RawResponse {
+    /// Deserialize the body as-is. Non-JSON bodies of failed requests are reported as
+    /// [`LarkError::Api`] with code `-1` so that the HTTP status and log id are kept.
+    pub fn json<T: DeserializeOwned>(&self) -> Result<T> {
+        serde_json::from_str(&self.body).map_err(|e| {
+            if (200..300).contains(&self.status) {
+                LarkError::Serde(e)
+            } else {
+                LarkError::api(-1, self.body.clone(), self.status, self.log_id.clone())
+            }
+        })
+    }
+
+    /// Lark business code of the body, if it is a Lark envelope
+    pub fn code(&self) -> Option<i32> {
+        serde_json::from_str::<Value>(&self.body)
+            .ok()?
+            .get("code")?
+            .as_i64()
+            .map(|code| code as i32)
+    }
+
+    /// Deserialize the common `{code, msg, data}` envelope, failing on a non-zero code
+    pub fn into_response<T: DeserializeOwned + Default>(self) -> Result<LarkApiResponse<T>> {
+        let mut response = self.json::<LarkApiResponse<T>>()?;
+        if !response.is_success() {
+            return Err(LarkError::api(
+                response.code,
+                response.msg,
+                self.status,
+                self.log_id,
+            ));
+        }
+
+        response.meta = ResponseMeta {
+            status: self.status,
+            log_id: self.log_id,
+        };
+        Ok(response)
+    }
+
+    /// Deserialize the common `{code, msg, data}` envelope and return `data` on success
+    pub fn into_data<T: DeserializeOwned + Default>(self) -> Result<T> {
+        self.into_response().map(|response| response.data)
+    }
+}
+
+/// Shared HTTP layer: joins paths onto the configured domain and executes requests
+/// on the pooled client.
+pub(crate) struct Transport {
+    config: Arc<LarkConfig>,
+    client: Client,
+}
+
+impl Transport {
+    pub fn new(config: Arc<LarkConfig>, client: Client) -> Self {
+        Self { config, client }
+    }
+
+    pub fn config(&self) -> &LarkConfig {
+        &self.config
+    }
+
+    pub fn client(&self) -> &Client {
+        &self.client
+    }
+
+    /// Execute `request` through the middleware stack of the configuration
+    pub async fn execute(&self, request: &ApiRequest, token: Option<&str>) -> Result<RawResponse> {
+        Next::new(self, token, &self.config.middleware)
+            .run(request.clone())
+            .await
+    }
+
+    /// Send `request`, or answer it from the configured cassette
+    pub(crate) async fn dispatch(
+        &self,
+        request: &ApiRequest,
+        token: Option<&str>,
+    ) -> Result<RawResponse> {
+        let Some(cassette) = &self.config.cassette else {
+            return self.send(request, token).await;
+        };
+
+        match cassette.mode() {
+            CassetteMode::Replay => cassette.replay_request(request),
+            CassetteMode::Record => {
+                let response = self.send(request, token).await?;
+                cassette.record_response(request, &response);
+                Ok(response)
+            }
+        }
+    }
+
+    async fn send(&self, request: &ApiRequest, token: Option<&str>) -> Result<RawResponse> {
+        let span = debug_span!(
+            "lark.http",
+            method = %request.method,
+            endpoint = %request.path,
+            status = field::Empty,
+            log_id = field::Empty,
+            latency_ms = field::Empty,
+        );
+        if let Some(limiter) = &self.config.rate_limiter {
+            limiter.acquire(request).instrument(span.clone()).await;
+        }
+        let started = Instant::now();
+        let outcome = self
+            .send_once(request, token)
+            .instrument(span.clone())
+            .await;
+
+        let elapsed = started.elapsed();
+        span.record("latency_ms", elapsed.as_millis() as u64);
+        #[cfg(feature = "metrics")]
+        crate::metrics::record_attempt(request, &outcome, elapsed);
+        if let Ok(response) = &outcome {
+            span.record("status", response.status);
+            if let Some(log_id) = &response.log_id {
+                span.record("log_id", field::display(log_id));
+            }
+        }
+        outcome
+    }
+
+    async fn send_once(&self, request: &ApiRequest, token: Option<&str>) -> Result<RawResponse> {
+        let mut builder = self
+            .client
+            .request(request.method.clone(), self.config.url(&request.path))
+            .header("Content-Type", "application/json; charset=utf-8");
+
+        if !request.query.is_empty() {
+            builder = builder.query(&request.query);
+        }
+        for (name, value) in &request.headers {
+            builder = builder.header(name, value);
+        }
+        if let Some(token) = token {
+            builder = builder.bearer_auth(token);
+        }
+        if let Some(body) = &request.body {
+            builder = builder.json(body);
+        }
+
+        let response = builder.send().await?;
+        let status = response.status().as_u16();
+        let header = |name: &str| {
+            response
+                .headers()
+                .get(name)
+                .and_then(|v| v.to_str().ok())
+                .map(str::to_string)
+        };
+        let log_id = header("x-tt-logid");
+        let retry_after = header("x-ogw-ratelimit-reset")
+            .or_else(|| header("retry-after"))
+            .and_then(|v| v.trim().parse::<u64>().ok())
+            .map(Duration::from_secs);
+        let body = response.text().await?;
+
+        Ok(RawResponse {
+            status,
+            log_id,
+            retry_after,
+            body,
+        })
+    }
+}
@@ -7,37 +7,82 @@
 //!
 //! ## Features
 //!
-//! - Authentication: Tenant access token management with automatic refresh
+//! - Errors: Every client method returns a typed [`LarkError`] carrying the Lark code and log id
+//! - Configuration: One [`LarkClient`] shares credentials, domain and HTTP pool with all API clients
+//! - Observability: `tracing` spans per call with the Lark code and `x-tt-logid`, and
+//!   Prometheus metrics of calls, retries and token refreshes with the `metrics` feature
+//! - Resilience: Jittered exponential [`RetryPolicy`] aware of Lark frequency limits, and a
+//!   client-side [`ratelimit::RateLimiter`] keeping within per-endpoint quotas
+//! - Authentication: Tenant access token management with automatic refresh, and user
+//!   access tokens through the OAuth authorization-code flow, and per-tenant tokens of
+//!   marketplace apps; tenant tokens can be shared between processes through a file store
+//! - Profiles: [`registry::LarkClientRegistry`] hands out one client per named app or tenant
+//! - Pagination: Every list endpoint has `*_all` and `*_stream` variants built on [`Paginator`]
+//! - Middleware: Pluggable [`middleware::Middleware`] stack every HTTP attempt passes through
+//! - Testing: Record-and-replay [`cassette::Cassette`]s of scrubbed request/response pairs
 //! - Bitable: Read and write operations for Feishu Bitable
 //! - Bot: Send messages and interact with chats
+//! - Cargo features: `bitable`, `im` and `wiki` (default) gate the API modules, and
+//!   `blocking` adds synchronous clients in [`blocking`] for code without an async runtime,
+//!   and `derive` maps structs to Bitable records with `#[derive(BitableRecord)]`
 //!
 //! ## Example
 //!
 //! ```rust,no_run
-//! use larkrs_client::auth::FeishuTokenManager;
-//! use larkrs_client::bot::chat::ChatClient;
+//! use larkrs_client::{LarkClient, LarkConfig, LARK_BASE_URL};
 //!
+//! # #[cfg(feature = "im")]
 //! #[tokio::main]
-//! async fn main() -> anyhow::Result<()> {
+//! async fn main() -> larkrs_client::Result<()> {
+//!     // Configure the app once, here for the Lark international domain
+//!     let config = LarkConfig::new("app_id", "app_secret").with_base_url(LARK_BASE_URL);
+//!     let client = LarkClient::new(config)?;
+//!
 //!     // Get a token
-//!     let token_manager = FeishuTokenManager::new();
-//!     let token = token_manager.get_token().await?;
+//!     let token = client.token_manager().get_token().await?;
 //!
-//!     // Send a message
-//!     let client = ChatClient::new();
-//!     client.send_text_message("chat_id", "Hello from Rust!").await?;
+//!     // Send a message, reusing the same HTTP pool and token cache
+//!     client.chat().send_text_message("chat_id", "Hello from Rust!").await?;
 //!
 //!     Ok(())
 //! }
+//! # #[cfg(not(feature = "im"))]
+//! # fn main() {}
 //! ```
 
+// Lets the paths generated by `larkrs-derive` resolve within this crate
+extern crate self as larkrs_client;
+
 use serde::{Deserialize, Serialize};
 
 pub mod auth;
+#[cfg(feature = "bitable")]
 pub mod bitable;
+#[cfg(feature = "blocking")]
+pub mod blocking;
+#[cfg(feature = "im")]
 pub mod bot;
+pub mod cassette;
+pub mod client;
+pub mod config;
+pub mod error;
+mod http;
+#[cfg(feature = "metrics")]
+pub mod metrics;
+pub mod middleware;
+pub mod page;
+pub mod ratelimit;
+pub mod registry;
+pub mod retry;
+#[cfg(feature = "wiki")]
 pub mod wiki;
 
+pub use client::{AuthMode, LarkClient};
+pub use config::{AppType, DEFAULT_USER_AGENT, FEISHU_BASE_URL, LARK_BASE_URL, LarkConfig};
+pub use error::{ErrorKind, LarkError, Result};
+pub use page::{Page, Paginator};
+pub use retry::RetryPolicy;
+
 /// Response structure for Lark API calls.
 ///
 /// All Lark API responses follow this common structure with a code, message, and data payload.
@@ -50,6 +95,18 @@ pub struct LarkApiResponse<T> {
     /// The actual response data. This is generic and depends on the specific API call.
     #[serde(default)]
     pub data: T,
+    /// HTTP metadata of the response, not part of the body
+    #[serde(skip)]
+    pub meta: ResponseMeta,
+}
+
+/// HTTP metadata of a Lark response
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ResponseMeta {
+    /// HTTP status code
+    pub status: u16,
+    /// Value of the `x-tt-logid` header, to quote when contacting Lark support
+    pub log_id: Option<String>,
 }
 
 impl<T> LarkApiResponse<T> {
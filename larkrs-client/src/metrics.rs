@@ -0,0 +1,259 @@
+//! Prometheus-style metrics of API calls, enabled by the `metrics` feature.
+//!
+//! Every client records into one process-wide registry, rendered in the Prometheus text
+//! exposition format by [`render`]:
+//!
+//! - `lark_requests_total{method, endpoint, code}`: HTTP attempts, by Lark code (or
+//!   `http_<status>` for non-Lark bodies, `network` for transport failures)
+//! - `lark_request_duration_seconds{method, endpoint}`: latency histogram of HTTP attempts
+//! - `lark_retries_total{method, endpoint}`: attempts repeated by the [`crate::RetryPolicy`]
+//! - `lark_token_refreshes_total{token, result}`: access token fetches
+//!
+//! Endpoints are paths with their ids replaced by `:id`, e.g.
+//! `/open-apis/bitable/v1/apps/:id/tables/:id/records/search`.
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::{LazyLock, Mutex};
+use std::time::Duration;
+
+use crate::error::Result;
+use crate::http::{ApiRequest, RawResponse};
+
+/// Upper bounds of the latency histogram buckets, in seconds
+const BUCKETS: [f64; 11] = [
+    0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+
+static REGISTRY: LazyLock<Mutex<Registry>> = LazyLock::new(Mutex::default);
+
+#[derive(Debug, Default)]
+struct Registry {
+    requests: BTreeMap<(String, String, String), u64>,
+    durations: BTreeMap<(String, String), Histogram>,
+    retries: BTreeMap<(String, String), u64>,
+    token_refreshes: BTreeMap<(&'static str, &'static str), u64>,
+}
+
+#[derive(Debug, Default)]
+struct Histogram {
+    /// Observations per bucket of [`BUCKETS`], not cumulative
+    counts: [u64; BUCKETS.len()],
+    count: u64,
+    sum: f64,
+}
+
+impl Histogram {
+    fn observe(&mut self, value: f64) {
+        if let Some(bucket) = BUCKETS.iter().position(|bound| value <= *bound) {
+            self.counts[bucket] += 1;
+        }
+        self.count += 1;
+        self.sum += value;
+    }
+}
+
+/// Replace the id segments of `path` with `:id` to keep the label cardinality bounded
+pub fn endpoint_label(path: &str) -> String {
+    let path = path.split('?').next().unwrap_or_default();
+    path.split('/')
+        .map(|segment| {
+            let is_word = segment
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c == '_' || c == '-');
+            let is_version = segment
+                .strip_prefix('v')
+                .is_some_and(|n| !n.is_empty() && n.chars().all(|c| c.is_ascii_digit()));
+            match is_word || is_version {
+                true => segment,
+                false => ":id",
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+fn labels(request: &ApiRequest) -> (String, String) {
+    (request.method.to_string(), endpoint_label(&request.path))
+}
+
+/// Record one HTTP attempt
+pub(crate) fn record_attempt(
+    request: &ApiRequest,
+    outcome: &Result<RawResponse>,
+    elapsed: Duration,
+) {
+    let (method, endpoint) = labels(request);
+    let code = match outcome {
+        Ok(response) => match response.code() {
+            Some(code) => code.to_string(),
+            None => format!("http_{}", response.status),
+        },
+        Err(_) => "network".to_string(),
+    };
+
+    let mut registry = REGISTRY.lock().unwrap();
+    *registry
+        .requests
+        .entry((method.clone(), endpoint.clone(), code))
+        .or_default() += 1;
+    registry
+        .durations
+        .entry((method, endpoint))
+        .or_default()
+        .observe(elapsed.as_secs_f64());
+}
+
+/// Record a retry of `request`
+pub(crate) fn record_retry(request: &ApiRequest) {
+    *REGISTRY
+        .lock()
+        .unwrap()
+        .retries
+        .entry(labels(request))
+        .or_default() += 1;
+}
+
+/// Record an access token fetch of the given kind (`tenant`, `user` or `marketplace_tenant`)
+pub(crate) fn record_token_refresh<T>(token: &'static str, outcome: &Result<T>) {
+    let result = match outcome {
+        Ok(_) => "ok",
+        Err(_) => "error",
+    };
+    *REGISTRY
+        .lock()
+        .unwrap()
+        .token_refreshes
+        .entry((token, result))
+        .or_default() += 1;
+}
+
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// Render every metric in the Prometheus text exposition format, e.g. for a `/metrics`
+/// endpoint
+pub fn render() -> String {
+    let registry = REGISTRY.lock().unwrap();
+    let mut out = String::new();
+
+    out.push_str("# HELP lark_requests_total Lark API HTTP attempts by response code\n");
+    out.push_str("# TYPE lark_requests_total counter\n");
+    for ((method, endpoint, code), count) in &registry.requests {
+        let _ = writeln!(
+            out,
+            "lark_requests_total{{method=\"{}\",endpoint=\"{}\",code=\"{}\"}} {}",
+            method,
+            escape(endpoint),
+            code,
+            count
+        );
+    }
+
+    out.push_str("# HELP lark_request_duration_seconds Latency of Lark API HTTP attempts\n");
+    out.push_str("# TYPE lark_request_duration_seconds histogram\n");
+    for ((method, endpoint), histogram) in &registry.durations {
+        let labels = format!("method=\"{}\",endpoint=\"{}\"", method, escape(endpoint));
+        let mut cumulative = 0;
+        for (bound, count) in BUCKETS.iter().zip(histogram.counts) {
+            cumulative += count;
+            let _ = writeln!(
+                out,
+                "lark_request_duration_seconds_bucket{{{},le=\"{}\"}} {}",
+                labels, bound, cumulative
+            );
+        }
+        let _ = writeln!(
+            out,
+            "lark_request_duration_seconds_bucket{{{},le=\"+Inf\"}} {}",
+            labels, histogram.count
+        );
+        let _ = writeln!(
+            out,
+            "lark_request_duration_seconds_sum{{{}}} {}",
+            labels, histogram.sum
+        );
+        let _ = writeln!(
+            out,
+            "lark_request_duration_seconds_count{{{}}} {}",
+            labels, histogram.count
+        );
+    }
+
+    out.push_str("# HELP lark_retries_total Lark API attempts repeated by the retry policy\n");
+    out.push_str("# TYPE lark_retries_total counter\n");
+    for ((method, endpoint), count) in &registry.retries {
+        let _ = writeln!(
+            out,
+            "lark_retries_total{{method=\"{}\",endpoint=\"{}\"}} {}",
+            method,
+            escape(endpoint),
+            count
+        );
+    }
+
+    out.push_str("# HELP lark_token_refreshes_total Access token fetches\n");
+    out.push_str("# TYPE lark_token_refreshes_total counter\n");
+    for ((token, result), count) in &registry.token_refreshes {
+        let _ = writeln!(
+            out,
+            "lark_token_refreshes_total{{token=\"{}\",result=\"{}\"}} {}",
+            token, result, count
+        );
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "im")]
+    use crate::{LarkClient, LarkConfig};
+
+    #[test]
+    fn test_endpoint_label() {
+        assert_eq!(
+            endpoint_label(
+                "/open-apis/bitable/v1/apps/bascnCMII2ORej2/tables/tblsRc9GRR/records/search"
+            ),
+            "/open-apis/bitable/v1/apps/:id/tables/:id/records/search"
+        );
+        assert_eq!(
+            endpoint_label("/open-apis/im/v1/messages?receive_id_type=chat_id"),
+            "/open-apis/im/v1/messages"
+        );
+    }
+
+    #[cfg(feature = "im")]
+    #[tokio::test]
+    async fn test_render_after_calls() {
+        let server = larkrs_mock::MockLarkServer::start().await;
+        server.state().add_chat("oc_metrics", "test");
+        let client =
+            LarkClient::new(LarkConfig::new("id", "secret").with_base_url(server.uri())).unwrap();
+
+        client.chat().get_chat_group_list().await.unwrap();
+        client
+            .chat()
+            .send_text_message("oc_unknown", "hello")
+            .await
+            .unwrap_err();
+
+        let text = render();
+        assert!(text.contains(
+            "lark_requests_total{method=\"GET\",endpoint=\"/open-apis/im/v1/chats\",code=\"0\"}"
+        ));
+        assert!(text.contains(
+            "lark_requests_total{method=\"POST\",endpoint=\"/open-apis/im/v1/messages\",code=\"230002\"}"
+        ));
+        assert!(text.contains(
+            "lark_request_duration_seconds_count{method=\"GET\",endpoint=\"/open-apis/im/v1/chats\"}"
+        ));
+        assert!(text.contains("lark_token_refreshes_total{token=\"tenant\",result=\"ok\"}"));
+    }
+}
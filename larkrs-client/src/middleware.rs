@@ -0,0 +1,226 @@
+//! Hooks on the request pipeline.
+//!
+//! Every HTTP attempt of every client, token requests and retries included, passes
+//! through the [`Middleware`] stack of its [`crate::LarkConfig`] in the order the
+//! middleware were added. A middleware may change the request (e.g. add headers), inspect
+//! or change the response, or answer without calling the rest of the stack (e.g. a
+//! circuit breaker or a mocked call). The bearer token is added after the stack.
+//!
+//! ```rust
+//! use futures_util::future::BoxFuture;
+//! use larkrs_client::middleware::{ApiRequest, Middleware, Next, RawResponse};
+//! use larkrs_client::{LarkConfig, Result};
+//! use std::sync::Arc;
+//!
+//! #[derive(Debug)]
+//! struct RequestSource;
+//!
+//! impl Middleware for RequestSource {
+//!     fn handle<'a>(
+//!         &'a self,
+//!         request: ApiRequest,
+//!         next: Next<'a>,
+//!     ) -> BoxFuture<'a, Result<RawResponse>> {
+//!         next.run(request.header("X-Request-Source", "larkrs"))
+//!     }
+//! }
+//!
+//! let config = LarkConfig::from_env().with_middleware(Arc::new(RequestSource));
+//! ```
+
+use futures_util::future::BoxFuture;
+use std::fmt::Debug;
+use std::sync::Arc;
+
+use crate::error::Result;
+use crate::http::Transport;
+
+pub use crate::http::{ApiRequest, RawResponse};
+
+/// Interceptor of [`ApiRequest`]s, see the [module documentation](self)
+pub trait Middleware: Send + Sync + Debug {
+    /// Handle `request`, usually by passing it on with [`Next::run`]
+    fn handle<'a>(
+        &'a self,
+        request: ApiRequest,
+        next: Next<'a>,
+    ) -> BoxFuture<'a, Result<RawResponse>>;
+}
+
+/// The rest of the middleware stack, ending with the HTTP call
+pub struct Next<'a> {
+    transport: &'a Transport,
+    token: Option<&'a str>,
+    middleware: &'a [Arc<dyn Middleware>],
+}
+
+impl<'a> Next<'a> {
+    pub(crate) fn new(
+        transport: &'a Transport,
+        token: Option<&'a str>,
+        middleware: &'a [Arc<dyn Middleware>],
+    ) -> Self {
+        Self {
+            transport,
+            token,
+            middleware,
+        }
+    }
+
+    /// Pass `request` to the next middleware, or send it
+    pub fn run(self, request: ApiRequest) -> BoxFuture<'a, Result<RawResponse>> {
+        match self.middleware.split_first() {
+            Some((first, rest)) => first.handle(
+                request,
+                Next {
+                    middleware: rest,
+                    ..self
+                },
+            ),
+            None => Box::pin(async move { self.transport.dispatch(&request, self.token).await }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{LarkClient, LarkConfig};
+    use larkrs_mock::MockLarkServer;
+    use std::sync::Mutex;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[derive(Debug)]
+    struct Header(&'static str);
+
+    impl Middleware for Header {
+        fn handle<'a>(
+            &'a self,
+            request: ApiRequest,
+            next: Next<'a>,
+        ) -> BoxFuture<'a, Result<RawResponse>> {
+            next.run(request.header("X-Trace", self.0))
+        }
+    }
+
+    /// Records the paths and `X-Trace` headers it sees
+    #[derive(Debug, Default)]
+    struct Recorder(Mutex<Vec<String>>);
+
+    impl Middleware for Recorder {
+        fn handle<'a>(
+            &'a self,
+            request: ApiRequest,
+            next: Next<'a>,
+        ) -> BoxFuture<'a, Result<RawResponse>> {
+            let trace = request
+                .headers
+                .iter()
+                .find(|(name, _)| name == "X-Trace")
+                .map(|(_, value)| value.clone())
+                .unwrap_or_default();
+            self.0
+                .lock()
+                .unwrap()
+                .push(format!("{} {}", request.path, trace));
+            next.run(request)
+        }
+    }
+
+    /// Answers message sends itself
+    #[derive(Debug, Default)]
+    struct FakeSend(AtomicUsize);
+
+    impl Middleware for FakeSend {
+        fn handle<'a>(
+            &'a self,
+            request: ApiRequest,
+            next: Next<'a>,
+        ) -> BoxFuture<'a, Result<RawResponse>> {
+            if request.path != "/open-apis/im/v1/messages" {
+                return next.run(request);
+            }
+            self.0.fetch_add(1, Ordering::SeqCst);
+            Box::pin(async {
+                Ok(RawResponse {
+                    status: 200,
+                    log_id: None,
+                    retry_after: None,
+                    body: r#"{"code":0,"msg":"ok","data":{"message_id":"om_fake"}}"#.to_string(),
+                })
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_stack_order_and_short_circuit() {
+        let server = MockLarkServer::start().await;
+        let recorder = Arc::new(Recorder::default());
+        let fake = Arc::new(FakeSend::default());
+        let config = LarkConfig::new("id", "secret")
+            .with_base_url(server.uri())
+            .with_middleware(Arc::new(Header("abc")))
+            .with_middleware(recorder.clone())
+            .with_middleware(fake.clone());
+        let client = LarkClient::new(config).unwrap();
+
+        let body = serde_json::json!({"receive_id": "oc_1", "msg_type": "text", "content": "{}"});
+        let sent = client
+            .call::<serde_json::Value>(
+                reqwest::Method::POST,
+                "/open-apis/im/v1/messages",
+                Some(&body),
+            )
+            .await
+            .unwrap();
+        assert_eq!(sent.data["message_id"], "om_fake");
+        assert_eq!(fake.0.load(Ordering::SeqCst), 1);
+        assert!(server.state().messages().is_empty());
+
+        // The token request goes through the stack too
+        assert_eq!(
+            *recorder.0.lock().unwrap(),
+            [
+                "/open-apis/auth/v3/tenant_access_token/internal abc",
+                "/open-apis/im/v1/messages abc",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_headers_reach_the_server() {
+        use wiremock::matchers::{header, method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/open-apis/auth/v3/tenant_access_token/internal"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "code": 0, "msg": "ok", "tenant_access_token": "t-test", "expire": 7200
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/open-apis/contact/v3/users/ou_1"))
+            .and(header("X-Trace", "abc"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "code": 0, "msg": "ok", "data": {}
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let config = LarkConfig::new("id", "secret")
+            .with_base_url(server.uri())
+            .with_middleware(Arc::new(Header("abc")));
+        let client = LarkClient::new(config).unwrap();
+        client
+            .call::<serde_json::Value>(
+                reqwest::Method::GET,
+                "/open-apis/contact/v3/users/ou_1",
+                None,
+            )
+            .await
+            .unwrap();
+    }
+}
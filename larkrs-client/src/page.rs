@@ -0,0 +1,221 @@
+use futures_util::stream::{self, BoxStream, Stream, StreamExt, TryStreamExt};
+use serde::de::DeserializeOwned;
+use std::marker::PhantomData;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use crate::client::LarkClient;
+use crate::error::Result;
+use crate::http::ApiRequest;
+
+/// One page of a list or search endpoint
+pub trait Page: DeserializeOwned + Default + Send + 'static {
+    type Item: Send + 'static;
+
+    /// Token of the next page, `None` on the last page
+    fn next_page_token(&self) -> Option<&str>;
+
+    fn into_items(self) -> Vec<Self::Item>;
+}
+
+/// Follows the `page_token`/`has_more` cursor of a list endpoint.
+///
+/// A `Paginator` is a [`Stream`] of the items of every page. Pages are fetched lazily,
+/// one request at a time, so a consumer that stops early does not fetch the rest.
+///
+/// ```rust,no_run
+/// # #[cfg(feature = "im")]
+/// # async fn example(client: larkrs_client::LarkClient) -> larkrs_client::Result<()> {
+/// use futures_util::TryStreamExt;
+///
+/// let mut chats = client.chat().get_chat_group_list_stream().with_page_size(100);
+/// while let Some(chat) = chats.try_next().await? {
+///     println!("{}", chat.name);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub struct Paginator<P: Page> {
+    client: LarkClient,
+    request: ApiRequest,
+    page_size: Option<u32>,
+    items: Option<BoxStream<'static, Result<P::Item>>>,
+    _page: PhantomData<fn() -> P>,
+}
+
+impl<P: Page> Paginator<P> {
+    // Only used by the API modules, which are all optional
+    #[cfg_attr(
+        not(any(feature = "bitable", feature = "im", feature = "wiki")),
+        allow(dead_code)
+    )]
+    pub(crate) fn new(client: LarkClient, request: ApiRequest) -> Self {
+        Self {
+            client,
+            request,
+            page_size: None,
+            items: None,
+            _page: PhantomData,
+        }
+    }
+
+    /// Number of items requested per page, within the limits of the endpoint (default:
+    /// the endpoint default)
+    pub fn with_page_size(mut self, page_size: u32) -> Self {
+        self.page_size = Some(page_size);
+        self
+    }
+
+    /// Stream of whole pages instead of items
+    pub fn pages(self) -> BoxStream<'static, Result<P>> {
+        fetch_pages(self.client, self.request, self.page_size)
+    }
+
+    /// Fetch every page and collect the items
+    pub async fn all(self) -> Result<Vec<P::Item>> {
+        self.try_collect().await
+    }
+}
+
+impl<P: Page> Stream for Paginator<P> {
+    type Item = Result<P::Item>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+        let items = this.items.get_or_insert_with(|| {
+            fetch_pages::<P>(this.client.clone(), this.request.clone(), this.page_size)
+                .map_ok(|page| stream::iter(page.into_items().into_iter().map(Ok)))
+                .try_flatten()
+                .boxed()
+        });
+        items.poll_next_unpin(cx)
+    }
+}
+
+fn fetch_pages<P: Page>(
+    client: LarkClient,
+    request: ApiRequest,
+    page_size: Option<u32>,
+) -> BoxStream<'static, Result<P>> {
+    // `None` once the last page has been fetched
+    let first: Option<Option<String>> = Some(None);
+    stream::try_unfold(first, move |cursor| {
+        let client = client.clone();
+        let mut request = request.clone();
+        async move {
+            let Some(page_token) = cursor else {
+                return Ok(None);
+            };
+
+            if let Some(page_size) = page_size {
+                request = request.query("page_size", page_size);
+            }
+            if let Some(page_token) = &page_token {
+                request = request.query("page_token", page_token);
+            }
+
+            let page: P = client.send(request).await?;
+            // Guard against a server repeating the same cursor forever
+            let next = page
+                .next_page_token()
+                .filter(|next| !next.is_empty() && Some(*next) != page_token.as_deref())
+                .map(|next| Some(next.to_string()));
+            Ok(Some((page, next)))
+        }
+    })
+    .boxed()
+}
+
+#[cfg(all(test, feature = "im"))]
+mod tests {
+    use super::*;
+    use crate::LarkConfig;
+    use crate::bot::ChatListResponse;
+    use serde_json::json;
+    use wiremock::matchers::{method, path, query_param};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    async fn mock_server() -> MockServer {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/open-apis/auth/v3/tenant_access_token/internal"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "code": 0,
+                "msg": "ok",
+                "tenant_access_token": "t-test",
+                "expire": 7200
+            })))
+            .mount(&server)
+            .await;
+        server
+    }
+
+    fn chats_page(ids: &[&str], page_token: Option<&str>) -> ResponseTemplate {
+        let items: Vec<_> = ids
+            .iter()
+            .map(|id| json!({"chat_id": id, "name": id}))
+            .collect();
+        ResponseTemplate::new(200).set_body_json(json!({
+            "code": 0,
+            "msg": "success",
+            "data": {"items": items, "page_token": page_token, "has_more": page_token.is_some()}
+        }))
+    }
+
+    fn paginator(server: &MockServer) -> Paginator<ChatListResponse> {
+        let client =
+            LarkClient::new(LarkConfig::new("id", "secret").with_base_url(server.uri())).unwrap();
+        Paginator::new(client, ApiRequest::get("/open-apis/im/v1/chats"))
+    }
+
+    #[tokio::test]
+    async fn test_follows_page_tokens() {
+        let server = mock_server().await;
+        Mock::given(method("GET"))
+            .and(path("/open-apis/im/v1/chats"))
+            .and(query_param("page_token", "p2"))
+            .respond_with(chats_page(&["oc_3"], None))
+            .with_priority(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/open-apis/im/v1/chats"))
+            .and(query_param("page_size", "2"))
+            .respond_with(chats_page(&["oc_1", "oc_2"], Some("p2")))
+            .mount(&server)
+            .await;
+
+        let chats = paginator(&server).with_page_size(2).all().await.unwrap();
+        let ids: Vec<_> = chats.iter().map(|chat| chat.chat_id.as_str()).collect();
+        assert_eq!(ids, ["oc_1", "oc_2", "oc_3"]);
+    }
+
+    #[tokio::test]
+    async fn test_stops_on_repeated_page_token() {
+        let server = mock_server().await;
+        Mock::given(method("GET"))
+            .and(path("/open-apis/im/v1/chats"))
+            .respond_with(chats_page(&["oc_1"], Some("same")))
+            .expect(2)
+            .mount(&server)
+            .await;
+
+        let pages: Vec<_> = paginator(&server).pages().try_collect().await.unwrap();
+        assert_eq!(pages.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_lazy_stream() {
+        let server = mock_server().await;
+        Mock::given(method("GET"))
+            .and(path("/open-apis/im/v1/chats"))
+            .respond_with(chats_page(&["oc_1", "oc_2"], Some("p2")))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let mut chats = paginator(&server);
+        assert_eq!(chats.try_next().await.unwrap().unwrap().chat_id, "oc_1");
+        assert_eq!(chats.try_next().await.unwrap().unwrap().chat_id, "oc_2");
+    }
+}
@@ -0,0 +1,316 @@
+use reqwest::Method;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::http::ApiRequest;
+
+/// Group of endpoints sharing a Lark quota
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EndpointFamily {
+    /// `POST /open-apis/im/v1/messages`, limited per receiving chat or user
+    MessageSend,
+    /// Other IM endpoints (chat list, message reply, ...)
+    Im,
+    /// Bitable reads, record search and batch get included
+    BitableRead,
+    /// Bitable creates, updates and deletes
+    BitableWrite,
+    /// Wiki endpoints
+    Wiki,
+    /// Docx endpoints
+    Docx,
+    /// Access token endpoints
+    Auth,
+    /// Anything else
+    Other,
+}
+
+impl EndpointFamily {
+    /// Family of a request, from its method and path
+    pub fn classify(method: &Method, path: &str) -> Self {
+        let path = path.split('?').next().unwrap_or_default();
+        let Some(rest) = path.strip_prefix("/open-apis/") else {
+            return EndpointFamily::Other;
+        };
+
+        match rest.split('/').next().unwrap_or_default() {
+            "auth" | "authen" => EndpointFamily::Auth,
+            "im" if *method == Method::POST && rest == "im/v1/messages" => {
+                EndpointFamily::MessageSend
+            }
+            "im" => EndpointFamily::Im,
+            "bitable"
+                if *method == Method::GET
+                    || rest.ends_with("/records/search")
+                    || rest.ends_with("/records/batch_get") =>
+            {
+                EndpointFamily::BitableRead
+            }
+            "bitable" => EndpointFamily::BitableWrite,
+            "wiki" => EndpointFamily::Wiki,
+            "docx" => EndpointFamily::Docx,
+            _ => EndpointFamily::Other,
+        }
+    }
+}
+
+/// Token bucket quota of an [`EndpointFamily`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RateLimit {
+    /// Sustained requests per second
+    pub per_second: f64,
+    /// Requests allowed at once after an idle period
+    pub burst: u32,
+    /// Give every receiving chat or user its own bucket (only meaningful for
+    /// [`EndpointFamily::MessageSend`])
+    pub per_receiver: bool,
+}
+
+impl RateLimit {
+    pub fn per_second(per_second: f64) -> Self {
+        Self {
+            per_second,
+            burst: per_second.ceil().max(1.0) as u32,
+            per_receiver: false,
+        }
+    }
+
+    pub fn with_burst(mut self, burst: u32) -> Self {
+        self.burst = burst.max(1);
+        self
+    }
+
+    pub fn per_receiver(mut self) -> Self {
+        self.per_receiver = true;
+        self
+    }
+}
+
+/// Client-side token bucket limiter that delays requests to stay within Lark quotas.
+///
+/// Requests over quota wait for their turn instead of failing, so batch jobs do not trip
+/// the server-side frequency limits. One limiter is shared by every client built from the
+/// same [`crate::LarkConfig`]; families without a configured limit are not delayed.
+///
+/// ```rust
+/// use larkrs_client::ratelimit::{EndpointFamily, RateLimit, RateLimiter};
+///
+/// let limiter = RateLimiter::lark_defaults()
+///     .with_limit(EndpointFamily::BitableWrite, RateLimit::per_second(5.0));
+/// ```
+#[derive(Debug, Default)]
+pub struct RateLimiter {
+    limits: HashMap<EndpointFamily, RateLimit>,
+    buckets: Mutex<HashMap<BucketKey, Bucket>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct BucketKey {
+    family: EndpointFamily,
+    receiver: Option<String>,
+}
+
+#[derive(Debug)]
+struct Bucket {
+    /// Available tokens, negative when callers are queued
+    tokens: f64,
+    updated: Instant,
+}
+
+/// Buckets are pruned once there are more than this many, e.g. one per chat
+const MAX_BUCKETS: usize = 1024;
+
+impl RateLimiter {
+    /// A limiter without any limit
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Quotas documented by Lark: 5 QPS per receiving chat or user for message sends,
+    /// 10 QPS for Bitable writes and 20 QPS for Bitable reads
+    pub fn lark_defaults() -> Self {
+        Self::new()
+            .with_limit(
+                EndpointFamily::MessageSend,
+                RateLimit::per_second(5.0).per_receiver(),
+            )
+            .with_limit(EndpointFamily::BitableWrite, RateLimit::per_second(10.0))
+            .with_limit(EndpointFamily::BitableRead, RateLimit::per_second(20.0))
+    }
+
+    pub fn with_limit(mut self, family: EndpointFamily, limit: RateLimit) -> Self {
+        self.limits.insert(family, limit);
+        self
+    }
+
+    pub fn limit(&self, family: EndpointFamily) -> Option<&RateLimit> {
+        self.limits.get(&family)
+    }
+
+    /// Wait until `request` may be sent
+    pub(crate) async fn acquire(&self, request: &ApiRequest) {
+        let family = EndpointFamily::classify(&request.method, &request.path);
+        let receiver = request
+            .body
+            .as_ref()
+            .and_then(|body| body.get("receive_id"))
+            .and_then(|id| id.as_str());
+        self.acquire_for(family, receiver).await;
+    }
+
+    /// Wait for a slot of `family`, for the given receiving chat or user
+    pub async fn acquire_for(&self, family: EndpointFamily, receiver: Option<&str>) {
+        let wait = self.reserve(family, receiver);
+        if !wait.is_zero() {
+            tracing::debug!(?family, ?wait, "Waiting for local rate limit");
+            tokio::time::sleep(wait).await;
+        }
+    }
+
+    /// Take a token and return how long to wait before using it
+    fn reserve(&self, family: EndpointFamily, receiver: Option<&str>) -> Duration {
+        let Some(limit) = self.limits.get(&family) else {
+            return Duration::ZERO;
+        };
+        if limit.per_second <= 0.0 {
+            return Duration::ZERO;
+        }
+
+        let key = BucketKey {
+            family,
+            receiver: receiver.filter(|_| limit.per_receiver).map(str::to_string),
+        };
+        let now = Instant::now();
+        let burst = f64::from(limit.burst);
+
+        let mut buckets = self.buckets.lock().unwrap();
+        if buckets.len() >= MAX_BUCKETS {
+            // Idle buckets are full again and equivalent to fresh ones
+            buckets.retain(|key, bucket| {
+                self.limits.get(&key.family).is_some_and(|limit| {
+                    let burst = f64::from(limit.burst);
+                    bucket.refilled(now, limit.per_second, burst) < burst
+                })
+            });
+        }
+        let bucket = buckets.entry(key).or_insert(Bucket {
+            tokens: burst,
+            updated: now,
+        });
+
+        bucket.tokens = bucket.refilled(now, limit.per_second, burst) - 1.0;
+        bucket.updated = now;
+        match bucket.tokens < 0.0 {
+            true => Duration::from_secs_f64(-bucket.tokens / limit.per_second),
+            false => Duration::ZERO,
+        }
+    }
+}
+
+impl Bucket {
+    fn refilled(&self, now: Instant, per_second: f64, burst: f64) -> f64 {
+        let elapsed = now.duration_since(self.updated).as_secs_f64();
+        (self.tokens + elapsed * per_second).min(burst)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify() {
+        let classify = EndpointFamily::classify;
+        assert_eq!(
+            classify(&Method::POST, "/open-apis/im/v1/messages"),
+            EndpointFamily::MessageSend
+        );
+        assert_eq!(
+            classify(&Method::GET, "/open-apis/im/v1/chats"),
+            EndpointFamily::Im
+        );
+        assert_eq!(
+            classify(
+                &Method::POST,
+                "/open-apis/bitable/v1/apps/a/tables/t/records/search"
+            ),
+            EndpointFamily::BitableRead
+        );
+        assert_eq!(
+            classify(
+                &Method::POST,
+                "/open-apis/bitable/v1/apps/a/tables/t/records/batch_get"
+            ),
+            EndpointFamily::BitableRead
+        );
+        assert_eq!(
+            classify(
+                &Method::POST,
+                "/open-apis/bitable/v1/apps/a/tables/t/records/batch_create"
+            ),
+            EndpointFamily::BitableWrite
+        );
+        assert_eq!(
+            classify(
+                &Method::POST,
+                "/open-apis/auth/v3/tenant_access_token/internal"
+            ),
+            EndpointFamily::Auth
+        );
+    }
+
+    #[test]
+    fn test_bucket_queues_over_burst() {
+        let limiter = RateLimiter::new().with_limit(
+            EndpointFamily::BitableWrite,
+            RateLimit::per_second(10.0).with_burst(2),
+        );
+
+        let reserve = || limiter.reserve(EndpointFamily::BitableWrite, None);
+        assert_eq!(reserve(), Duration::ZERO);
+        assert_eq!(reserve(), Duration::ZERO);
+        // Queued behind each other, 100 ms apart
+        let third = reserve();
+        let fourth = reserve();
+        assert!(third > Duration::from_millis(90) && third <= Duration::from_millis(100));
+        assert!(fourth > Duration::from_millis(190) && fourth <= Duration::from_millis(200));
+
+        assert_eq!(limiter.reserve(EndpointFamily::Im, None), Duration::ZERO);
+    }
+
+    #[test]
+    fn test_per_receiver_buckets() {
+        let limiter = RateLimiter::new().with_limit(
+            EndpointFamily::MessageSend,
+            RateLimit::per_second(1.0).per_receiver(),
+        );
+
+        let reserve = |chat| limiter.reserve(EndpointFamily::MessageSend, Some(chat));
+        assert_eq!(reserve("oc_1"), Duration::ZERO);
+        assert!(reserve("oc_1") > Duration::ZERO);
+        assert_eq!(reserve("oc_2"), Duration::ZERO);
+    }
+
+    #[cfg(feature = "im")]
+    #[tokio::test]
+    async fn test_requests_wait_instead_of_failing() {
+        let server = larkrs_mock::MockLarkServer::start().await;
+        server.state().add_chat("oc_1", "test");
+        let limiter = RateLimiter::new().with_limit(
+            EndpointFamily::MessageSend,
+            RateLimit::per_second(20.0).with_burst(1).per_receiver(),
+        );
+        let config = crate::LarkConfig::new("id", "secret")
+            .with_base_url(server.uri())
+            .with_rate_limiter(std::sync::Arc::new(limiter));
+        let chat = crate::LarkClient::new(config).unwrap().chat();
+
+        let started = Instant::now();
+        for _ in 0..3 {
+            chat.send_text_message("oc_1", "hello").await.unwrap();
+        }
+        assert!(started.elapsed() >= Duration::from_millis(90));
+        assert_eq!(server.state().messages().len(), 3);
+    }
+}
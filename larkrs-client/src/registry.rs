@@ -0,0 +1,324 @@
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use crate::client::LarkClient;
+use crate::config::{AppType, FEISHU_BASE_URL, LarkConfig};
+use crate::error::{LarkError, Result};
+
+/// Identity the clients of a [`Profile`] use by default
+#[derive(Debug, Clone, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProfileAuth {
+    /// Tenant access token of the app, i.e. act as the bot
+    #[default]
+    Tenant,
+    /// User access token; the user must complete the authorization flow on
+    /// [`LarkClient::user_token_manager`] of the profile's client first
+    User,
+    /// Tenant access token of the tenant with this `tenant_key` (marketplace apps)
+    TenantKey(String),
+}
+
+/// Credentials and domain of one app, as stored in a profiles file
+#[derive(Debug, Clone, Deserialize)]
+pub struct Profile {
+    pub app_id: String,
+    pub app_secret: String,
+    #[serde(default)]
+    pub app_type: AppType,
+    /// Open platform domain (default: [`FEISHU_BASE_URL`])
+    #[serde(default)]
+    pub base_url: Option<String>,
+    #[serde(default)]
+    pub auth: ProfileAuth,
+}
+
+impl Profile {
+    pub fn new(app_id: impl Into<String>, app_secret: impl Into<String>) -> Self {
+        Self {
+            app_id: app_id.into(),
+            app_secret: app_secret.into(),
+            app_type: AppType::default(),
+            base_url: None,
+            auth: ProfileAuth::default(),
+        }
+    }
+
+    pub fn with_app_type(mut self, app_type: AppType) -> Self {
+        self.app_type = app_type;
+        self
+    }
+
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = Some(base_url.into());
+        self
+    }
+
+    pub fn with_auth(mut self, auth: ProfileAuth) -> Self {
+        self.auth = auth;
+        self
+    }
+
+    /// Configuration of this profile, with default HTTP settings; [`LarkClientRegistry::from_env`]
+    /// applies the `FEISHU_*` HTTP settings on top
+    pub fn config(&self) -> LarkConfig {
+        LarkConfig::new(&self.app_id, &self.app_secret)
+            .with_app_type(self.app_type)
+            .with_base_url(self.base_url.as_deref().unwrap_or(FEISHU_BASE_URL))
+    }
+}
+
+/// Named profiles, e.g. loaded from a JSON file:
+///
+/// ```json
+/// {
+///   "default": "ops",
+///   "profiles": {
+///     "ops": {"app_id": "cli_a", "app_secret": "..."},
+///     "intl": {"app_id": "cli_b", "app_secret": "...", "base_url": "https://open.larksuite.com"},
+///     "isv": {"app_id": "cli_c", "app_secret": "...", "app_type": "marketplace",
+///             "auth": {"tenant_key": "736588c9260f175d"}}
+///   }
+/// }
+/// ```
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct Profiles {
+    /// Profile used when none is named; may be omitted when there is a single profile
+    #[serde(default)]
+    pub default: Option<String>,
+    pub profiles: BTreeMap<String, Profile>,
+}
+
+impl Profiles {
+    pub fn from_json(json: &str) -> Result<Self> {
+        Ok(serde_json::from_str(json)?)
+    }
+
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path).map_err(|e| {
+            LarkError::Config(format!("cannot read profiles {}: {}", path.display(), e))
+        })?;
+        Self::from_json(&content)
+    }
+}
+
+/// Clients of several apps or tenants, looked up by profile name.
+///
+/// Every profile gets its own [`LarkClient`], so tokens, rate limits and user
+/// authorizations are kept per app.
+///
+/// ```rust,no_run
+/// # fn example() -> larkrs_client::Result<()> {
+/// use larkrs_client::registry::{LarkClientRegistry, Profiles};
+///
+/// let profiles = Profiles::from_file("profiles.json")?;
+/// let registry = LarkClientRegistry::from_profiles(&profiles, |config| config)?;
+///
+/// let ops = registry.get(Some("ops"))?;
+/// let default = registry.get(None)?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Default)]
+pub struct LarkClientRegistry {
+    clients: BTreeMap<String, LarkClient>,
+    default: Option<String>,
+}
+
+impl LarkClientRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Build one client per profile; `configure` can add settings shared by every profile,
+    /// such as a rate limiter or middleware
+    pub fn from_profiles(
+        profiles: &Profiles,
+        configure: impl Fn(LarkConfig) -> LarkConfig,
+    ) -> Result<Self> {
+        let mut registry = Self::new();
+        for (name, profile) in &profiles.profiles {
+            let client = LarkClient::new(configure(profile.config()))?;
+            let client = match &profile.auth {
+                ProfileAuth::Tenant => client,
+                ProfileAuth::User => {
+                    let user = std::sync::Arc::new(client.user_token_manager());
+                    client.as_user(user)
+                }
+                ProfileAuth::TenantKey(tenant_key) => client.for_tenant(tenant_key),
+            };
+            registry.insert(name, client);
+        }
+
+        match &profiles.default {
+            Some(name) if !registry.clients.contains_key(name) => Err(LarkError::Config(format!(
+                "default profile {} is not defined",
+                name
+            ))),
+            Some(name) => Ok(registry.with_default(name)),
+            None => Ok(registry),
+        }
+    }
+
+    /// Load the profiles file named by `FEISHU_PROFILES`, or fall back to a single
+    /// `default` profile built from [`LarkConfig::from_env`]. The timeout, user agent,
+    /// proxy, CA bundle and token cache variables apply to every profile.
+    pub fn from_env(configure: impl Fn(LarkConfig) -> LarkConfig) -> Result<Self> {
+        match std::env::var("FEISHU_PROFILES") {
+            Ok(path) if !path.is_empty() => {
+                let profiles = Profiles::from_file(path)?;
+                Self::from_profiles(&profiles, |config| configure(config.with_env_settings()))
+            }
+            _ => {
+                let client = LarkClient::new(configure(LarkConfig::from_env()))?;
+                Ok(Self::new().with_client("default", client))
+            }
+        }
+    }
+
+    /// Add or replace the client of profile `name`
+    pub fn insert(&mut self, name: impl Into<String>, client: LarkClient) {
+        self.clients.insert(name.into(), client);
+    }
+
+    pub fn with_client(mut self, name: impl Into<String>, client: LarkClient) -> Self {
+        self.insert(name, client);
+        self
+    }
+
+    /// Profile used by [`Self::get`] when no name is given
+    pub fn with_default(mut self, name: impl Into<String>) -> Self {
+        self.default = Some(name.into());
+        self
+    }
+
+    /// Client of the named profile, or of the default profile. Without a configured
+    /// default, a registry holding a single profile uses it.
+    pub fn get(&self, profile: Option<&str>) -> Result<LarkClient> {
+        let name = match (profile, &self.default) {
+            (Some(name), _) => name,
+            (None, Some(default)) => default.as_str(),
+            (None, None) if self.clients.len() == 1 => {
+                return Ok(self.clients.values().next().cloned().unwrap());
+            }
+            (None, None) => {
+                return Err(LarkError::InvalidArgument(format!(
+                    "no default profile, name one of: {}",
+                    self.names().collect::<Vec<_>>().join(", ")
+                )));
+            }
+        };
+
+        self.clients.get(name).cloned().ok_or_else(|| {
+            LarkError::InvalidArgument(format!(
+                "unknown profile {}, expected one of: {}",
+                name,
+                self.names().collect::<Vec<_>>().join(", ")
+            ))
+        })
+    }
+
+    /// Names of the registered profiles, in alphabetical order
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.clients.keys().map(String::as_str)
+    }
+
+    /// Name of the profile used when none is given, if any
+    pub fn default_profile(&self) -> Option<&str> {
+        match &self.default {
+            Some(name) => Some(name),
+            None if self.clients.len() == 1 => self.names().next(),
+            None => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::AuthMode;
+
+    const PROFILES: &str = r#"{
+        "default": "ops",
+        "profiles": {
+            "ops": {"app_id": "cli_a", "app_secret": "a"},
+            "intl": {"app_id": "cli_b", "app_secret": "b", "base_url": "https://open.larksuite.com"},
+            "isv": {"app_id": "cli_c", "app_secret": "c", "app_type": "marketplace",
+                    "auth": {"tenant_key": "t1"}}
+        }
+    }"#;
+
+    #[test]
+    fn test_profiles_from_json() {
+        let profiles = Profiles::from_json(PROFILES).unwrap();
+        let registry = LarkClientRegistry::from_profiles(&profiles, |config| config).unwrap();
+
+        assert_eq!(registry.names().collect::<Vec<_>>(), ["intl", "isv", "ops"]);
+        assert_eq!(registry.get(None).unwrap().config().app_id, "cli_a");
+        assert_eq!(
+            registry.get(Some("intl")).unwrap().config().base_url,
+            crate::LARK_BASE_URL
+        );
+
+        let isv = registry.get(Some("isv")).unwrap();
+        assert_eq!(isv.config().app_type, AppType::Marketplace);
+        assert!(matches!(isv.auth_mode(), AuthMode::TenantKey(key) if key == "t1"));
+    }
+
+    #[test]
+    fn test_unknown_and_missing_default() {
+        let profiles = Profiles::from_json(PROFILES).unwrap();
+        let registry = LarkClientRegistry::from_profiles(&profiles, |config| config).unwrap();
+        let err = registry.get(Some("nope")).err().unwrap();
+        assert!(matches!(err, LarkError::InvalidArgument(msg) if msg.contains("intl, isv, ops")));
+
+        let registry = LarkClientRegistry::new()
+            .with_client("a", LarkClient::new(LarkConfig::new("cli_a", "a")).unwrap())
+            .with_client("b", LarkClient::new(LarkConfig::new("cli_b", "b")).unwrap());
+        assert!(registry.get(None).is_err());
+        assert_eq!(registry.default_profile(), None);
+
+        let mut profiles = Profiles::from_json(PROFILES).unwrap();
+        profiles.default = Some("missing".to_string());
+        assert!(LarkClientRegistry::from_profiles(&profiles, |config| config).is_err());
+    }
+
+    #[cfg(feature = "im")]
+    #[tokio::test]
+    async fn test_profiles_use_their_own_app() {
+        let first = larkrs_mock::MockLarkServer::start().await;
+        first.state().add_chat("oc_first", "first");
+        let second = larkrs_mock::MockLarkServer::start().await;
+        second.state().add_chat("oc_second", "second");
+
+        let registry = LarkClientRegistry::new()
+            .with_client(
+                "first",
+                LarkClient::new(LarkConfig::new("a", "a").with_base_url(first.uri())).unwrap(),
+            )
+            .with_client(
+                "second",
+                LarkClient::new(LarkConfig::new("b", "b").with_base_url(second.uri())).unwrap(),
+            )
+            .with_default("first");
+
+        let chats = registry
+            .get(None)
+            .unwrap()
+            .chat()
+            .get_chat_group_list()
+            .await
+            .unwrap();
+        assert_eq!(chats[0].chat_id, "oc_first");
+        let chats = registry
+            .get(Some("second"))
+            .unwrap()
+            .chat()
+            .get_chat_group_list()
+            .await
+            .unwrap();
+        assert_eq!(chats[0].chat_id, "oc_second");
+    }
+}
@@ -0,0 +1,271 @@
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::time::Duration;
+use tracing::warn;
+
+use crate::error::{ErrorKind, LarkError, Result};
+use crate::http::{ApiRequest, RawResponse, Transport};
+
+/// Retry policy applied to every request of a [`crate::LarkClient`].
+///
+/// Throttled requests (HTTP 429 or a Lark frequency-limit code such as `99991400`) are
+/// always retried, since Lark rejected them before doing any work. Network errors and
+/// server errors are only retried for idempotent calls.
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    /// Maximum number of retries after the first attempt (default: 3)
+    pub max_retries: u32,
+    /// Delay before the first retry, doubled on every attempt (default: 200 ms)
+    pub base_delay: Duration,
+    /// Upper bound of a single wait, including server-provided reset hints (default: 10 s)
+    pub max_delay: Duration,
+}
+
+impl RetryPolicy {
+    /// A policy that never retries
+    pub fn none() -> Self {
+        Self {
+            max_retries: 0,
+            ..Self::default()
+        }
+    }
+
+    pub fn with_max_retries(mut self, max_retries: u32) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    pub fn with_base_delay(mut self, delay: Duration) -> Self {
+        self.base_delay = delay;
+        self
+    }
+
+    pub fn with_max_delay(mut self, delay: Duration) -> Self {
+        self.max_delay = delay;
+        self
+    }
+
+    /// Jittered exponential backoff for the given retry attempt (starting at 0).
+    ///
+    /// Returns a random delay between half and the full exponential value.
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let exp = self
+            .base_delay
+            .saturating_mul(2u32.saturating_pow(attempt))
+            .min(self.max_delay);
+        let half = exp / 2;
+        let jitter_ms = match half.as_millis() as u64 {
+            0 => 0,
+            range => random_u64() % (range + 1),
+        };
+        half + Duration::from_millis(jitter_ms)
+    }
+
+    fn should_retry(&self, request: &ApiRequest, outcome: &Result<RawResponse>) -> bool {
+        match outcome {
+            Ok(response) => {
+                let code = response.code().unwrap_or_default();
+                if code == 0 && (200..300).contains(&response.status) {
+                    return false;
+                }
+                match ErrorKind::classify(code, response.status) {
+                    ErrorKind::RateLimit => true,
+                    ErrorKind::Server => request.idempotent,
+                    _ => false,
+                }
+            }
+            Err(LarkError::Network(e)) => request.idempotent || e.is_connect(),
+            Err(_) => false,
+        }
+    }
+
+    /// Execute `request` on `transport`, retrying according to this policy
+    pub(crate) async fn execute(
+        &self,
+        transport: &Transport,
+        request: &ApiRequest,
+        token: Option<&str>,
+    ) -> Result<RawResponse> {
+        let mut attempt = 0;
+        loop {
+            let outcome = transport.execute(request, token).await;
+            if attempt >= self.max_retries || !self.should_retry(request, &outcome) {
+                return outcome;
+            }
+
+            let delay = match &outcome {
+                Ok(RawResponse {
+                    retry_after: Some(reset),
+                    ..
+                }) => (*reset).min(self.max_delay),
+                _ => self.backoff(attempt),
+            };
+            warn!(
+                method = %request.method,
+                path = %request.path,
+                ?delay,
+                attempt = attempt + 1,
+                max_retries = self.max_retries,
+                "Retrying Lark request"
+            );
+            #[cfg(feature = "metrics")]
+            crate::metrics::record_retry(request);
+            tokio::time::sleep(delay).await;
+            attempt += 1;
+        }
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            base_delay: Duration::from_millis(200),
+            max_delay: Duration::from_secs(10),
+        }
+    }
+}
+
+fn random_u64() -> u64 {
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_u64(
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_nanos() as u64)
+            .unwrap_or_default(),
+    );
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "im")]
+    use crate::{LarkClient, LarkConfig};
+    #[cfg(feature = "im")]
+    use serde_json::json;
+    #[cfg(feature = "im")]
+    use wiremock::matchers::{method, path};
+    #[cfg(feature = "im")]
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[cfg(feature = "im")]
+    async fn mock_server() -> MockServer {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/open-apis/auth/v3/tenant_access_token/internal"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "code": 0,
+                "msg": "ok",
+                "tenant_access_token": "t-test",
+                "expire": 7200
+            })))
+            .mount(&server)
+            .await;
+        server
+    }
+
+    #[cfg(feature = "im")]
+    fn client(server: &MockServer, policy: RetryPolicy) -> LarkClient {
+        let config = LarkConfig::new("id", "secret")
+            .with_base_url(server.uri())
+            .with_retry_policy(policy);
+        LarkClient::new(config).unwrap()
+    }
+
+    #[test]
+    fn test_backoff_bounds() {
+        let policy = RetryPolicy::default()
+            .with_base_delay(Duration::from_millis(100))
+            .with_max_delay(Duration::from_millis(300));
+
+        for _ in 0..20 {
+            let first = policy.backoff(0);
+            assert!(first >= Duration::from_millis(50) && first <= Duration::from_millis(100));
+            let capped = policy.backoff(10);
+            assert!(capped >= Duration::from_millis(150) && capped <= Duration::from_millis(300));
+        }
+    }
+
+    #[cfg(feature = "im")]
+    #[tokio::test]
+    async fn test_retry_on_frequency_limit() {
+        let server = mock_server().await;
+        Mock::given(method("GET"))
+            .and(path("/open-apis/im/v1/chats"))
+            .respond_with(
+                ResponseTemplate::new(400)
+                    .insert_header("x-ogw-ratelimit-reset", "0")
+                    .set_body_json(
+                        json!({"code": 99991400, "msg": "request trigger frequency limit"}),
+                    ),
+            )
+            .up_to_n_times(2)
+            .with_priority(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/open-apis/im/v1/chats"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "code": 0,
+                "msg": "success",
+                "data": {"items": [{"chat_id": "oc_1", "name": "test"}], "has_more": false}
+            })))
+            .mount(&server)
+            .await;
+
+        let chats = client(&server, RetryPolicy::default())
+            .chat()
+            .get_chat_group_list()
+            .await
+            .unwrap();
+        assert_eq!(chats.len(), 1);
+    }
+
+    #[cfg(feature = "im")]
+    #[tokio::test]
+    async fn test_no_retry_for_non_idempotent_server_error() {
+        let server = mock_server().await;
+        Mock::given(method("POST"))
+            .and(path("/open-apis/im/v1/messages"))
+            .respond_with(
+                ResponseTemplate::new(500)
+                    .set_body_json(json!({"code": 1, "msg": "internal error"})),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let err = client(
+            &server,
+            RetryPolicy::default().with_base_delay(Duration::from_millis(1)),
+        )
+        .chat()
+        .send_text_message("oc_1", "hello")
+        .await
+        .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Server);
+    }
+
+    #[cfg(feature = "im")]
+    #[tokio::test]
+    async fn test_gives_up_after_max_retries() {
+        let server = mock_server().await;
+        Mock::given(method("GET"))
+            .and(path("/open-apis/im/v1/chats"))
+            .respond_with(ResponseTemplate::new(503).set_body_string("unavailable"))
+            .expect(3)
+            .mount(&server)
+            .await;
+
+        let policy = RetryPolicy::default()
+            .with_max_retries(2)
+            .with_base_delay(Duration::from_millis(1));
+        let err = client(&server, policy)
+            .chat()
+            .get_chat_group_list()
+            .await
+            .unwrap_err();
+        assert_eq!(err.status(), Some(503));
+    }
+}
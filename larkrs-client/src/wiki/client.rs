@@ -1,140 +1,100 @@
-use crate::auth::FeishuTokenManager;
-use crate::LarkApiResponse;
-use super::{WikiContentResponse, WikiNodeResponse, WikiListResponse};
-use anyhow::Result;
-use reqwest::Client;
-use log::{info, warn, error, debug};
+use super::{WikiContentResponse, WikiListResponse, WikiNodeResponse};
+use crate::client::LarkClient;
+use crate::error::{LarkError, Result};
+use crate::http::ApiRequest;
+use crate::page::Paginator;
+use tracing::debug;
 
 pub struct WikiClient {
-    token_manager: FeishuTokenManager,
-    client: Client,
+    client: LarkClient,
 }
 
 impl WikiClient {
+    /// Create a client configured from the `FEISHU_*` environment variables
+    ///
+    /// # Panics
+    ///
+    /// Panics if the HTTP client cannot be initialized, see [`LarkClient::from_env`].
     pub fn new() -> Self {
-        Self {
-            token_manager: FeishuTokenManager::new(),
-            client: Client::new(),
-        }
+        Self::with_client(LarkClient::from_env().expect("Failed to create Lark client"))
+    }
+
+    pub fn with_client(client: LarkClient) -> Self {
+        Self { client }
     }
 
     /// Get wiki node information by node token
-    pub async fn get_wiki_node(&self, space_id: &str, node_token: &str) -> Result<WikiNodeResponse> {
-        let token = self.token_manager.get_token().await?;
-        
-        let url = format!(
-            "https://open.feishu.cn/open-apis/wiki/v2/spaces/{}/nodes/{}",
+    pub async fn get_wiki_node(
+        &self,
+        space_id: &str,
+        node_token: &str,
+    ) -> Result<WikiNodeResponse> {
+        let request = ApiRequest::get(format!(
+            "/open-apis/wiki/v2/spaces/{}/nodes/{}",
             space_id, node_token
-        );
+        ));
 
-        let response = self
-            .client
-            .get(&url)
-            .header("Authorization", format!("Bearer {}", token))
-            .send()
-            .await?;
-
-        let api_response: LarkApiResponse<WikiNodeResponse> = response.json().await?;
-        
-        if api_response.is_success() {
-            Ok(api_response.data)
-        } else {
-            Ok(WikiNodeResponse::default())
-        }
+        self.client.send(request).await
     }
 
     /// Get wiki content by node token using raw_content API
     pub async fn get_wiki_content(&self, node_token: &str) -> Result<WikiContentResponse> {
-        debug!("Getting wiki content for node_token: {}", node_token);
-        
-        let token = self.token_manager.get_token().await?;
-        
-        let url = format!(
-            "https://open.feishu.cn/open-apis/docx/v1/documents/{}/raw_content",
+        debug!(node_token, "Getting wiki content");
+
+        let request = ApiRequest::get(format!(
+            "/open-apis/docx/v1/documents/{}/raw_content",
             node_token
-        );
+        ));
 
-        debug!("Requesting URL: {}", url);
-
-        let response = self
-            .client
-            .get(&url)
-            .header("Authorization", format!("Bearer {}", token))
-            .send()
-            .await?;
-
-        let status = response.status();
-        debug!("HTTP Status: {}", status);
-
-        let response_text = response.text().await?;
-        debug!("Response body length: {} chars", response_text.len());
-
-        // Try to parse as JSON
-        match serde_json::from_str::<LarkApiResponse<WikiContentResponse>>(&response_text) {
-            Ok(api_response) => {
-                debug!("API Response code: {}, msg: {}", api_response.code, api_response.msg);
-                if api_response.is_success() {
-                    info!("Successfully retrieved wiki content");
-                    Ok(api_response.data)
-                } else {
-                    warn!("API call failed with code: {}, message: {}", api_response.code, api_response.msg);
-                    Ok(WikiContentResponse::default())
-                }
-            }
-            Err(e) => {
-                error!("Failed to parse JSON response: {}. Response text: {}", e, response_text);
-                Ok(WikiContentResponse::default())
-            }
-        }
+        let content: WikiContentResponse = self.client.send(request).await?;
+        debug!(chars = content.content.len(), "Got wiki content");
+
+        Ok(content)
     }
 
-    /// List wiki nodes in a space
+    /// List wiki nodes in a space, first page only
     pub async fn list_wiki_nodes(&self, space_id: &str) -> Result<WikiListResponse> {
-        let token = self.token_manager.get_token().await?;
-        
-        let url = format!(
-            "https://open.feishu.cn/open-apis/wiki/v2/spaces/{}/nodes",
-            space_id
-        );
+        let request = ApiRequest::get(format!("/open-apis/wiki/v2/spaces/{}/nodes", space_id));
 
-        let response = self
-            .client
-            .get(&url)
-            .header("Authorization", format!("Bearer {}", token))
-            .send()
-            .await?;
-
-        let api_response: LarkApiResponse<WikiListResponse> = response.json().await?;
-        
-        if api_response.is_success() {
-            Ok(api_response.data)
-        } else {
-            Ok(WikiListResponse::default())
-        }
+        self.client.send(request).await
+    }
+
+    /// List every wiki node in a space
+    pub async fn list_wiki_nodes_all(&self, space_id: &str) -> Result<Vec<WikiNodeResponse>> {
+        self.list_wiki_nodes_stream(space_id)
+            .with_page_size(50)
+            .all()
+            .await
+    }
+
+    /// List wiki nodes in a space, as a stream fetching pages on demand (page size: up to 50)
+    pub fn list_wiki_nodes_stream(&self, space_id: &str) -> Paginator<WikiListResponse> {
+        let request = ApiRequest::get(format!("/open-apis/wiki/v2/spaces/{}/nodes", space_id));
+        Paginator::new(self.client.clone(), request)
     }
 
     /// Extract wiki information from a Feishu wiki URL
     /// URL format: https://xxx.feishu.cn/wiki/{node_token}?fromScene=spaceOverview
     pub fn parse_wiki_url(&self, url: &str) -> Result<(String, String)> {
-        debug!("Parsing wiki URL: {}", url);
-        
+        debug!(url, "Parsing wiki URL");
+
         // Extract node_token from URL
         let url_parts: Vec<&str> = url.split('/').collect();
-        debug!("URL parts: {:?}", url_parts);
-        
-        if let Some(wiki_part) = url_parts.iter().find(|&&part| part.contains("wiki")) {
-            if let Some(index) = url_parts.iter().position(|&x| x == *wiki_part) {
-                if index + 1 < url_parts.len() {
-                    let node_token = url_parts[index + 1].split('?').next().unwrap_or("");
-                    debug!("Extracted node_token: {}", node_token);
-                    // For now, we'll use a default space_id or extract from URL if available
-                    let space_id = "default"; // This would need to be extracted or provided
-                    return Ok((space_id.to_string(), node_token.to_string()));
-                }
-            }
+        debug!(?url_parts, "Split wiki URL");
+
+        if let Some(index) = url_parts.iter().position(|part| part.contains("wiki"))
+            && index + 1 < url_parts.len()
+        {
+            let node_token = url_parts[index + 1].split('?').next().unwrap_or("");
+            debug!(node_token, "Extracted node_token");
+            // For now, we'll use a default space_id or extract from URL if available
+            let space_id = "default"; // This would need to be extracted or provided
+            return Ok((space_id.to_string(), node_token.to_string()));
         }
-        error!("Failed to parse wiki URL: {}", url);
-        Err(anyhow::anyhow!("Invalid wiki URL format"))
+        Err(LarkError::InvalidArgument(format!(
+            "Invalid wiki URL format: {}",
+            url
+        )))
     }
 }
 
@@ -142,4 +102,34 @@ impl Default for WikiClient {
     fn default() -> Self {
         Self::new()
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::LarkConfig;
+    use larkrs_mock::MockLarkServer;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_read_wiki_content() {
+        let server = MockLarkServer::start().await;
+        for i in 0..60 {
+            let node = json!({"node_token": format!("wik{}", i), "title": format!("page {}", i)});
+            server.state().add_wiki_node("space", node);
+        }
+        server.state().add_document("wik0", "hello wiki");
+        let client = WikiClient::with_client(
+            LarkClient::new(LarkConfig::new("id", "secret").with_base_url(server.uri())).unwrap(),
+        );
+
+        let nodes = client.list_wiki_nodes_all("space").await.unwrap();
+        assert_eq!(nodes.len(), 60);
+
+        let (_, node_token) = client
+            .parse_wiki_url("https://xxx.feishu.cn/wiki/wik0?fromScene=spaceOverview")
+            .unwrap();
+        let content = client.get_wiki_content(&node_token).await.unwrap();
+        assert_eq!(content.content, "hello wiki");
+    }
+}
@@ -1,5 +1,7 @@
 use serde::{Deserialize, Serialize};
 
+use crate::page::Page;
+
 pub mod client;
 
 #[derive(Debug, Serialize, Deserialize, Default)]
@@ -32,4 +34,16 @@ pub struct WikiListResponse {
     pub items: Vec<WikiNodeResponse>,
     pub page_token: Option<String>,
     pub has_more: bool,
-}
\ No newline at end of file
+}
+
+impl Page for WikiListResponse {
+    type Item = WikiNodeResponse;
+
+    fn next_page_token(&self) -> Option<&str> {
+        self.page_token.as_deref().filter(|_| self.has_more)
+    }
+
+    fn into_items(self) -> Vec<WikiNodeResponse> {
+        self.items
+    }
+}
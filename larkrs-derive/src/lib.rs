@@ -0,0 +1,190 @@
+//! Derive macros of `larkrs-client`, see `larkrs_client::bitable::record`.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{Attribute, Data, DeriveInput, Fields, LitStr, parse_macro_input};
+
+/// Implement `BitableRecord` for a struct with named fields.
+///
+/// Field attributes:
+/// - `#[bitable(field = "股票名称")]`: name of the Bitable field (default: the Rust name)
+/// - `#[bitable(record_id)]`: `String` receiving the record id, never written
+/// - `#[bitable(skip)]`: neither read nor written, set to `Default::default()` when read
+#[proc_macro_derive(BitableRecord, attributes(bitable))]
+pub fn derive_bitable_record(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_record(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Implement `BitableValue` for an enum of unit variants, mapped to select options.
+///
+/// Variant attribute `#[bitable(option = "四板")]` names the option (default: the variant
+/// name).
+#[proc_macro_derive(BitableSelect, attributes(bitable))]
+pub fn derive_bitable_select(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_select(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Settings of one `#[bitable(...)]` attribute list
+#[derive(Default)]
+struct Attrs {
+    name: Option<LitStr>,
+    record_id: bool,
+    skip: bool,
+}
+
+/// Parse the `bitable` attributes, accepting `key` (`field` or `option`) for the name
+fn parse_attrs(attrs: &[Attribute], key: &str) -> syn::Result<Attrs> {
+    let mut parsed = Attrs::default();
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("bitable")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident(key) {
+                parsed.name = Some(meta.value()?.parse()?);
+            } else if key == "field" && meta.path.is_ident("record_id") {
+                parsed.record_id = true;
+            } else if key == "field" && meta.path.is_ident("skip") {
+                parsed.skip = true;
+            } else {
+                return Err(meta.error("unsupported bitable attribute"));
+            }
+            Ok(())
+        })?;
+    }
+    Ok(parsed)
+}
+
+fn expand_record(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let Data::Struct(data) = &input.data else {
+        return Err(syn::Error::new_spanned(
+            input,
+            "BitableRecord can only be derived for structs",
+        ));
+    };
+    let Fields::Named(fields) = &data.fields else {
+        return Err(syn::Error::new_spanned(
+            input,
+            "BitableRecord needs a struct with named fields",
+        ));
+    };
+
+    let record = quote!(::larkrs_client::bitable::record);
+    let mut names = Vec::new();
+    let mut writes = Vec::new();
+    let mut reads = Vec::new();
+    for field in &fields.named {
+        let ident = field.ident.as_ref().expect("named field");
+        let attrs = parse_attrs(&field.attrs, "field")?;
+
+        if attrs.skip {
+            reads.push(quote!(#ident: ::core::default::Default::default()));
+            continue;
+        }
+        if attrs.record_id {
+            reads.push(quote!(#ident: ::core::clone::Clone::clone(&record.record_id)));
+            continue;
+        }
+
+        let name = attrs
+            .name
+            .unwrap_or_else(|| LitStr::new(&ident.to_string(), ident.span()));
+        names.push(name.clone());
+        writes.push(quote! {
+            let value = #record::BitableValue::to_value(&self.#ident);
+            if !value.is_null() {
+                fields.insert(::std::string::String::from(#name), value);
+            }
+        });
+        reads.push(quote!(#ident: #record::read_field(record, #name)?));
+    }
+
+    let ty = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics #record::BitableRecord for #ty #ty_generics #where_clause {
+            fn field_names() -> ::std::vec::Vec<&'static str> {
+                ::std::vec![#(#names),*]
+            }
+
+            fn to_fields(
+                &self,
+            ) -> ::std::collections::HashMap<::std::string::String, #record::Value> {
+                let mut fields = ::std::collections::HashMap::new();
+                #(#writes)*
+                fields
+            }
+
+            fn from_record(
+                record: &::larkrs_client::bitable::Record,
+            ) -> ::larkrs_client::Result<Self> {
+                ::core::result::Result::Ok(Self {
+                    #(#reads,)*
+                })
+            }
+        }
+    })
+}
+
+fn expand_select(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let Data::Enum(data) = &input.data else {
+        return Err(syn::Error::new_spanned(
+            input,
+            "BitableSelect can only be derived for enums",
+        ));
+    };
+
+    let mut variants = Vec::new();
+    let mut options = Vec::new();
+    for variant in &data.variants {
+        if !matches!(variant.fields, Fields::Unit) {
+            return Err(syn::Error::new_spanned(
+                variant,
+                "BitableSelect variants cannot hold data",
+            ));
+        }
+        let attrs = parse_attrs(&variant.attrs, "option")?;
+        let ident = &variant.ident;
+        variants.push(ident);
+        options.push(
+            attrs
+                .name
+                .unwrap_or_else(|| LitStr::new(&ident.to_string(), ident.span())),
+        );
+    }
+
+    let record = quote!(::larkrs_client::bitable::record);
+    let ty = &input.ident;
+    let expected = options
+        .iter()
+        .map(LitStr::value)
+        .collect::<Vec<_>>()
+        .join(", ");
+    Ok(quote! {
+        impl #record::BitableValue for #ty {
+            fn to_value(&self) -> #record::Value {
+                let option = match self {
+                    #(Self::#variants => #options,)*
+                };
+                #record::Value::String(::std::string::String::from(option))
+            }
+
+            fn from_value(
+                value: &#record::Value,
+            ) -> ::core::result::Result<Self, ::std::string::String> {
+                match value.as_str() {
+                    #(::core::option::Option::Some(#options) => ::core::result::Result::Ok(Self::#variants),)*
+                    _ => ::core::result::Result::Err(::std::format!(
+                        "expected one of {}, got {}",
+                        #expected,
+                        value
+                    )),
+                }
+            }
+        }
+    })
+}
@@ -0,0 +1,192 @@
+//! # larkrs-mock
+//!
+//! An in-memory mock of the Lark (Feishu) open platform, for testing `larkrs-client` and
+//! the MCP server without network access or a real app.
+//!
+//! The server emulates the endpoints used by the client:
+//!
+//! - Auth: `tenant_access_token/internal`, with issued tokens checked on every other call
+//! - Bitable: record search (with filters and field projection), batch create and field list
+//! - Bitable management: tables, fields and views, created, renamed and deleted in memory
+//! - IM: chat list and message creation
+//! - Wiki: node list and lookup, and docx raw content
+//!
+//! List endpoints honour `page_size`/`page_token`, and every response carries an
+//! `x-tt-logid` header.
+//!
+//! ## Example
+//!
+//! ```rust,no_run
+//! use larkrs_mock::MockLarkServer;
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let server = MockLarkServer::start().await;
+//!     server.state().add_chat("oc_1", "test chat");
+//!
+//!     // Point the client at the mock, e.g. with FEISHU_BASE_URL
+//!     println!("listening on {}", server.uri());
+//! }
+//! ```
+
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tokio::net::TcpListener;
+use tokio::task::JoinHandle;
+
+mod routes;
+pub mod state;
+
+pub use state::{MockState, Seed, SeedTable, field};
+
+/// A mock Lark server running in the background on a local port.
+///
+/// The server stops when this handle is dropped.
+pub struct MockLarkServer {
+    addr: SocketAddr,
+    state: Arc<MockState>,
+    task: JoinHandle<()>,
+}
+
+impl MockLarkServer {
+    /// Start a server on a random local port
+    pub async fn start() -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("Failed to bind mock server");
+        Self::serve(listener)
+    }
+
+    /// Serve on an already bound listener
+    pub fn serve(listener: TcpListener) -> Self {
+        let addr = listener
+            .local_addr()
+            .expect("Failed to read mock server address");
+        let state = Arc::new(MockState::default());
+        let app = routes::router(state.clone());
+        let task = tokio::spawn(async move {
+            if let Err(e) = axum::serve(listener, app).await {
+                log::error!("Mock server stopped: {}", e);
+            }
+        });
+
+        Self { addr, state, task }
+    }
+
+    /// Base URL to configure the client with, e.g. `http://127.0.0.1:40123`
+    pub fn uri(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// Data served by this server, to seed and inspect
+    pub fn state(&self) -> &MockState {
+        &self.state
+    }
+
+    /// Wait until the server stops
+    pub async fn wait(mut self) {
+        let _ = (&mut self.task).await;
+    }
+}
+
+impl Drop for MockLarkServer {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{Value, json};
+
+    async fn token(server: &MockLarkServer) -> String {
+        let response: Value = reqwest::Client::new()
+            .post(format!(
+                "{}/open-apis/auth/v3/tenant_access_token/internal",
+                server.uri()
+            ))
+            .json(&json!({"app_id": "id", "app_secret": "secret"}))
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        response["tenant_access_token"]
+            .as_str()
+            .unwrap()
+            .to_string()
+    }
+
+    async fn call(request: reqwest::RequestBuilder, token: &str) -> Value {
+        let response = request.bearer_auth(token).send().await.unwrap();
+        assert!(response.headers().contains_key("x-tt-logid"));
+        response.json().await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_rejects_unknown_token() {
+        let server = MockLarkServer::start().await;
+        server.state().add_chat("oc_1", "test");
+        let client = reqwest::Client::new();
+        let url = format!("{}/open-apis/im/v1/chats", server.uri());
+
+        let response = call(client.get(&url), "t-forged").await;
+        assert_eq!(response["code"], 99991663);
+
+        let response = call(client.get(&url), &token(&server).await).await;
+        assert_eq!(response["code"], 0);
+        assert_eq!(response["data"]["items"][0]["chat_id"], "oc_1");
+    }
+
+    #[tokio::test]
+    async fn test_search_filters_and_paginates() {
+        let server = MockLarkServer::start().await;
+        server
+            .state()
+            .add_table("app", "tbl", vec![field("name", 1, "Text")]);
+        server.state().add_records(
+            "app",
+            "tbl",
+            vec![
+                json!({"name": "a"}),
+                json!({"name": "b"}),
+                json!({"name": "a"}),
+            ],
+        );
+        let token = token(&server).await;
+        let client = reqwest::Client::new();
+        let url = format!(
+            "{}/open-apis/bitable/v1/apps/app/tables/tbl/records/search",
+            server.uri()
+        );
+        let filter = json!({"filter": {"conjunction": "and", "conditions": [
+            {"field_name": "name", "operator": "is", "value": ["a"]}
+        ]}});
+
+        let first = call(
+            client.post(&url).query(&[("page_size", "1")]).json(&filter),
+            &token,
+        )
+        .await;
+        assert_eq!(first["data"]["total"], 2);
+        assert_eq!(first["data"]["has_more"], true);
+
+        let page_token = first["data"]["page_token"].as_str().unwrap();
+        let second = call(
+            client
+                .post(&url)
+                .query(&[("page_size", "1"), ("page_token", page_token)])
+                .json(&filter),
+            &token,
+        )
+        .await;
+        assert_eq!(second["data"]["items"][0]["fields"]["name"], "a");
+        assert_eq!(second["data"]["has_more"], false);
+    }
+}
@@ -0,0 +1,26 @@
+use larkrs_mock::{MockLarkServer, Seed};
+use log::info;
+use std::env;
+use tokio::net::TcpListener;
+
+/// Run a mock Lark server
+///
+/// Usage: `larkrs-mock [seed.json]`, listening on `LARKRS_MOCK_ADDR` (default
+/// `127.0.0.1:8787`).
+#[tokio::main]
+async fn main() -> std::io::Result<()> {
+    let addr = env::var("LARKRS_MOCK_ADDR").unwrap_or_else(|_| "127.0.0.1:8787".to_string());
+    let server = MockLarkServer::serve(TcpListener::bind(&addr).await?);
+
+    if let Some(path) = env::args().nth(1) {
+        let seed: Seed = serde_json::from_str(&std::fs::read_to_string(&path)?)
+            .map_err(std::io::Error::other)?;
+        server.state().seed(seed);
+        info!("Loaded seed data from {}", path);
+    }
+
+    eprintln!("Mock Lark server listening on {}", server.uri());
+    eprintln!("Set FEISHU_BASE_URL={} to use it", server.uri());
+    server.wait().await;
+    Ok(())
+}
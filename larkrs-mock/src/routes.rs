@@ -0,0 +1,1113 @@
+use axum::Router;
+use axum::extract::{Path, Query, Request, State};
+use axum::http::{HeaderValue, StatusCode, header};
+use axum::middleware::{self, Next};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, patch, post};
+use log::debug;
+use serde::Deserialize;
+use serde_json::{Value, json};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::state::{MockState, TOKEN_EXPIRE_SECS, Table};
+
+/// Codes returned by the mock, named as in the Lark documentation
+const MISSING_ACCESS_TOKEN: i32 = 99991661;
+const INVALID_ACCESS_TOKEN: i32 = 99991663;
+const INVALID_APP_SECRET: i32 = 10014;
+const INVALID_PARAM: i32 = 99992402;
+const WRONG_TABLE_ID: i32 = 1254004;
+const RECORD_NOT_FOUND: i32 = 1254043;
+const FIELD_NAME_NOT_FOUND: i32 = 1254045;
+const TABLE_NAME_DUPLICATED: i32 = 1254013;
+const FIELD_NAME_DUPLICATED: i32 = 1254014;
+const VIEW_NOT_FOUND: i32 = 1254042;
+const FIELD_ID_NOT_FOUND: i32 = 1254044;
+const RECORD_ADD_ONCE_EXCEED_LIMIT: i32 = 1254104;
+const USER_FIELD_CONV_FAIL: i32 = 1254066;
+const BOT_NOT_IN_CHAT: i32 = 230002;
+const WIKI_NOT_FOUND: i32 = 131005;
+const DOCUMENT_NOT_FOUND: i32 = 1770002;
+
+pub(crate) fn router(state: Arc<MockState>) -> Router {
+    Router::new()
+        .route(
+            "/open-apis/bitable/v1/apps/{app_token}/tables/{table_id}/records/search",
+            post(search_records),
+        )
+        .route(
+            "/open-apis/bitable/v1/apps/{app_token}/tables/{table_id}/records/batch_create",
+            post(batch_create_records),
+        )
+        .route(
+            "/open-apis/bitable/v1/apps/{app_token}/tables/{table_id}/records/batch_get",
+            post(batch_get_records),
+        )
+        .route(
+            "/open-apis/bitable/v1/apps/{app_token}/tables/{table_id}/records/batch_update",
+            post(batch_update_records),
+        )
+        .route(
+            "/open-apis/bitable/v1/apps/{app_token}/tables/{table_id}/records/batch_delete",
+            post(batch_delete_records),
+        )
+        .route(
+            "/open-apis/bitable/v1/apps/{app_token}/tables/{table_id}/records/{record_id}",
+            get(get_record).put(update_record).delete(delete_record),
+        )
+        .route(
+            "/open-apis/bitable/v1/apps/{app_token}/tables/{table_id}/fields",
+            get(list_fields).post(create_field),
+        )
+        .route(
+            "/open-apis/bitable/v1/apps/{app_token}/tables/{table_id}/fields/{field_id}",
+            axum::routing::put(update_field).delete(delete_field),
+        )
+        .route(
+            "/open-apis/bitable/v1/apps/{app_token}/tables",
+            get(list_tables).post(create_table),
+        )
+        .route(
+            "/open-apis/bitable/v1/apps/{app_token}/tables/batch_create",
+            post(batch_create_tables),
+        )
+        .route(
+            "/open-apis/bitable/v1/apps/{app_token}/tables/batch_delete",
+            post(batch_delete_tables),
+        )
+        .route(
+            "/open-apis/bitable/v1/apps/{app_token}/tables/{table_id}",
+            patch(rename_table).delete(delete_table),
+        )
+        .route(
+            "/open-apis/bitable/v1/apps/{app_token}/tables/{table_id}/views",
+            get(list_views).post(create_view),
+        )
+        .route(
+            "/open-apis/bitable/v1/apps/{app_token}/tables/{table_id}/views/{view_id}",
+            get(get_view).patch(rename_view).delete(delete_view),
+        )
+        .route("/open-apis/im/v1/chats", get(list_chats))
+        .route("/open-apis/im/v1/messages", post(create_message))
+        .route(
+            "/open-apis/wiki/v2/spaces/{space_id}/nodes",
+            get(list_wiki_nodes),
+        )
+        .route(
+            "/open-apis/wiki/v2/spaces/{space_id}/nodes/{node_token}",
+            get(get_wiki_node),
+        )
+        .route(
+            "/open-apis/docx/v1/documents/{document_id}/raw_content",
+            get(raw_content),
+        )
+        .route_layer(middleware::from_fn_with_state(state.clone(), require_token))
+        .route(
+            "/open-apis/auth/v3/tenant_access_token/internal",
+            post(tenant_access_token),
+        )
+        .fallback(|| async { (StatusCode::NOT_FOUND, "404 page not found") })
+        .layer(middleware::from_fn(log_id))
+        .with_state(state)
+}
+
+fn success(data: Value) -> Response {
+    axum::Json(json!({"code": 0, "msg": "success", "data": data})).into_response()
+}
+
+fn failure(status: StatusCode, code: i32, msg: &str) -> Response {
+    (status, axum::Json(json!({"code": code, "msg": msg}))).into_response()
+}
+
+/// Stamp every response with an `x-tt-logid` header, as the Lark gateway does
+async fn log_id(request: Request, next: Next) -> Response {
+    static NEXT_LOG_ID: AtomicU64 = AtomicU64::new(1);
+
+    debug!("{} {}", request.method(), request.uri());
+    let mut response = next.run(request).await;
+    let log_id = format!("mock{:016x}", NEXT_LOG_ID.fetch_add(1, Ordering::Relaxed));
+    if let Ok(value) = HeaderValue::from_str(&log_id) {
+        response.headers_mut().insert("x-tt-logid", value);
+    }
+    response
+}
+
+async fn require_token(
+    State(state): State<Arc<MockState>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let token = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+
+    match token {
+        None => failure(
+            StatusCode::BAD_REQUEST,
+            MISSING_ACCESS_TOKEN,
+            "Missing access token for authorization",
+        ),
+        Some(token) if !state.lock().tokens.contains(token) => failure(
+            StatusCode::BAD_REQUEST,
+            INVALID_ACCESS_TOKEN,
+            "Invalid access token for authorization",
+        ),
+        Some(_) => next.run(request).await,
+    }
+}
+
+#[derive(Deserialize)]
+struct TokenRequest {
+    #[serde(default)]
+    app_id: String,
+    #[serde(default)]
+    app_secret: String,
+}
+
+async fn tenant_access_token(
+    State(state): State<Arc<MockState>>,
+    axum::Json(request): axum::Json<TokenRequest>,
+) -> Response {
+    let mut inner = state.lock();
+    inner.token_requests += 1;
+
+    let accepted = match inner.apps.get(&request.app_id) {
+        Some(secret) => *secret == request.app_secret,
+        None => inner.apps.is_empty() && !request.app_id.is_empty(),
+    };
+    if !accepted {
+        return axum::Json(json!({"code": INVALID_APP_SECRET, "msg": "app secret invalid"}))
+            .into_response();
+    }
+
+    let token = inner.issue_token();
+    axum::Json(json!({
+        "code": 0,
+        "msg": "ok",
+        "tenant_access_token": token,
+        "expire": TOKEN_EXPIRE_SECS
+    }))
+    .into_response()
+}
+
+#[derive(Deserialize)]
+struct PageQuery {
+    page_size: Option<usize>,
+    page_token: Option<String>,
+}
+
+/// Respond with the page of `items` selected by the `page_size`/`page_token` query, with
+/// the page token being the offset of the next item
+fn paginate(
+    items: Vec<Value>,
+    query: &PageQuery,
+    default_size: usize,
+    max_size: usize,
+) -> Response {
+    let page_size = query.page_size.unwrap_or(default_size);
+    if page_size == 0 || page_size > max_size {
+        return failure(
+            StatusCode::BAD_REQUEST,
+            INVALID_PARAM,
+            "page_size is out of range",
+        );
+    }
+    let offset = match query.page_token.as_deref() {
+        None | Some("") => 0,
+        Some(token) => match token.parse::<usize>() {
+            Ok(offset) => offset,
+            Err(_) => {
+                return failure(
+                    StatusCode::BAD_REQUEST,
+                    INVALID_PARAM,
+                    "page_token is invalid",
+                );
+            }
+        },
+    };
+
+    let total = items.len();
+    let end = (offset + page_size).min(total);
+    let has_more = end < total;
+    let page: Vec<Value> = items.into_iter().skip(offset).take(page_size).collect();
+
+    let mut data = json!({"items": page, "has_more": has_more, "total": total});
+    // Lark leaves the token out on the last page
+    if has_more {
+        data["page_token"] = json!(end.to_string());
+    }
+    success(data)
+}
+
+fn table_not_found() -> Response {
+    failure(StatusCode::BAD_REQUEST, WRONG_TABLE_ID, "WrongTableId")
+}
+
+fn record_not_found() -> Response {
+    failure(
+        StatusCode::BAD_REQUEST,
+        RECORD_NOT_FOUND,
+        "RecordIdNotFound",
+    )
+}
+
+fn field_not_found() -> Response {
+    failure(
+        StatusCode::BAD_REQUEST,
+        FIELD_NAME_NOT_FOUND,
+        "FieldNameNotFound",
+    )
+}
+
+/// Whether one of `names` is not a field of `table`. Tables created without fields accept
+/// any field name.
+fn has_unknown_field<'a>(table: &Table, mut names: impl Iterator<Item = &'a String>) -> bool {
+    !table.fields.is_empty()
+        && names.any(|name| {
+            !table
+                .fields
+                .iter()
+                .any(|field| field["field_name"] == name.as_str())
+        })
+}
+
+/// Whether a person cell of `fields` holds an id other than an open id, which Lark cannot
+/// convert with the default `user_id_type`
+fn has_unknown_user(table: &Table, fields: &serde_json::Map<String, Value>) -> bool {
+    fields.iter().any(|(name, value)| {
+        let is_person = table
+            .fields
+            .iter()
+            .any(|field| field["field_name"] == name.as_str() && field["type"] == 11);
+        is_person
+            && value.as_array().is_some_and(|persons| {
+                persons.iter().any(|person| {
+                    !person["id"]
+                        .as_str()
+                        .is_some_and(|id| id.starts_with("ou_"))
+                })
+            })
+    })
+}
+
+fn user_conversion_failed() -> Response {
+    failure(
+        StatusCode::BAD_REQUEST,
+        USER_FIELD_CONV_FAIL,
+        "UserFieldConvFail",
+    )
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct SearchRequest {
+    field_names: Option<Vec<String>>,
+    filter: Option<SearchFilter>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct SearchFilter {
+    conjunction: String,
+    conditions: Vec<SearchCondition>,
+}
+
+#[derive(Deserialize)]
+struct SearchCondition {
+    field_name: String,
+    operator: String,
+    #[serde(default)]
+    value: Vec<String>,
+}
+
+/// Text of a cell, for filtering; rich text segments are joined as Lark shows them
+fn cell_text(value: Option<&Value>) -> String {
+    match value {
+        None | Some(Value::Null) => String::new(),
+        Some(Value::String(text)) => text.clone(),
+        Some(Value::Array(items)) if items.iter().all(|item| item.get("text").is_some()) => items
+            .iter()
+            .map(|item| cell_text(item.get("text")))
+            .collect(),
+        Some(Value::Array(items)) => items
+            .iter()
+            .map(|item| cell_text(Some(item)))
+            .collect::<Vec<_>>()
+            .join(","),
+        Some(other) => other.to_string(),
+    }
+}
+
+impl SearchCondition {
+    fn matches(&self, fields: &Value) -> bool {
+        let text = cell_text(fields.get(&self.field_name));
+        let expected = self.value.first().map(String::as_str).unwrap_or_default();
+        let compare = || match (text.parse::<f64>(), expected.parse::<f64>()) {
+            (Ok(actual), Ok(expected)) => actual.partial_cmp(&expected),
+            _ => None,
+        };
+
+        match self.operator.as_str() {
+            "is" => text == expected,
+            "isNot" => text != expected,
+            "contains" => text.contains(expected),
+            "doesNotContain" => !text.contains(expected),
+            "isEmpty" => text.is_empty(),
+            "isNotEmpty" => !text.is_empty(),
+            "isGreater" => compare().is_some_and(|ord| ord.is_gt()),
+            "isGreaterEqual" => compare().is_some_and(|ord| ord.is_ge()),
+            "isLess" => compare().is_some_and(|ord| ord.is_lt()),
+            "isLessEqual" => compare().is_some_and(|ord| ord.is_le()),
+            _ => true,
+        }
+    }
+}
+
+async fn search_records(
+    State(state): State<Arc<MockState>>,
+    Path((app_token, table_id)): Path<(String, String)>,
+    Query(query): Query<PageQuery>,
+    axum::Json(request): axum::Json<SearchRequest>,
+) -> Response {
+    let records = {
+        let inner = state.lock();
+        let Some(table) = inner.tables.get(&(app_token, table_id)) else {
+            return table_not_found();
+        };
+        table.records.clone()
+    };
+
+    let records = records
+        .into_iter()
+        .filter(|record| {
+            let Some(filter) = &request.filter else {
+                return true;
+            };
+            let mut results = filter
+                .conditions
+                .iter()
+                .map(|condition| condition.matches(&record["fields"]));
+            match filter.conjunction.as_str() {
+                "or" => results.any(|matched| matched),
+                _ => results.all(|matched| matched),
+            }
+        })
+        .map(|mut record| {
+            if let Some(names) = &request.field_names
+                && let Some(fields) = record["fields"].as_object_mut()
+            {
+                fields.retain(|name, _| names.contains(name));
+            }
+            record
+        })
+        .collect();
+
+    paginate(records, &query, 20, 500)
+}
+
+#[derive(Deserialize)]
+struct BatchCreateRequest {
+    records: Vec<RecordFields>,
+}
+
+#[derive(Deserialize)]
+struct RecordFields {
+    fields: serde_json::Map<String, Value>,
+}
+
+async fn batch_create_records(
+    State(state): State<Arc<MockState>>,
+    Path((app_token, table_id)): Path<(String, String)>,
+    axum::Json(request): axum::Json<BatchCreateRequest>,
+) -> Response {
+    let mut inner = state.lock();
+    let Some(table) = inner.tables.get(&(app_token.clone(), table_id.clone())) else {
+        return table_not_found();
+    };
+    if request.records.len() > 500 {
+        return failure(
+            StatusCode::BAD_REQUEST,
+            RECORD_ADD_ONCE_EXCEED_LIMIT,
+            "RecordAddOnceExceedLimit",
+        );
+    }
+    let names = request
+        .records
+        .iter()
+        .flat_map(|record| record.fields.keys());
+    if has_unknown_field(table, names) {
+        return field_not_found();
+    }
+    if request
+        .records
+        .iter()
+        .any(|record| has_unknown_user(table, &record.fields))
+    {
+        return user_conversion_failed();
+    }
+
+    let records: Vec<Value> = request
+        .records
+        .into_iter()
+        .map(|record| inner.insert_record(&app_token, &table_id, Value::Object(record.fields)))
+        .collect();
+    success(json!({ "records": records }))
+}
+
+async fn get_record(
+    State(state): State<Arc<MockState>>,
+    Path((app_token, table_id, record_id)): Path<(String, String, String)>,
+) -> Response {
+    let inner = state.lock();
+    let Some(table) = inner.tables.get(&(app_token, table_id)) else {
+        return table_not_found();
+    };
+    match table.record(&record_id) {
+        Some(record) => success(json!({ "record": record })),
+        None => record_not_found(),
+    }
+}
+
+/// Merge `fields` into the record, as Lark only changes the fields it is given
+fn apply_update(
+    table: &mut Table,
+    record_id: &str,
+    fields: serde_json::Map<String, Value>,
+) -> Option<Value> {
+    let record = table
+        .records
+        .iter_mut()
+        .find(|record| record["record_id"] == record_id)?;
+    let current = record["fields"].as_object_mut()?;
+    current.extend(fields);
+    Some(record.clone())
+}
+
+async fn update_record(
+    State(state): State<Arc<MockState>>,
+    Path((app_token, table_id, record_id)): Path<(String, String, String)>,
+    axum::Json(request): axum::Json<RecordFields>,
+) -> Response {
+    let mut inner = state.lock();
+    let Some(table) = inner.tables.get_mut(&(app_token, table_id)) else {
+        return table_not_found();
+    };
+    if has_unknown_field(table, request.fields.keys()) {
+        return field_not_found();
+    }
+    if has_unknown_user(table, &request.fields) {
+        return user_conversion_failed();
+    }
+    match apply_update(table, &record_id, request.fields) {
+        Some(record) => success(json!({ "record": record })),
+        None => record_not_found(),
+    }
+}
+
+async fn delete_record(
+    State(state): State<Arc<MockState>>,
+    Path((app_token, table_id, record_id)): Path<(String, String, String)>,
+) -> Response {
+    let mut inner = state.lock();
+    let Some(table) = inner.tables.get_mut(&(app_token, table_id)) else {
+        return table_not_found();
+    };
+    if table.record(&record_id).is_none() {
+        return record_not_found();
+    }
+    table
+        .records
+        .retain(|record| record["record_id"] != record_id);
+    success(json!({ "deleted": true, "record_id": record_id }))
+}
+
+#[derive(Deserialize)]
+struct BatchGetRequest {
+    record_ids: Vec<String>,
+}
+
+async fn batch_get_records(
+    State(state): State<Arc<MockState>>,
+    Path((app_token, table_id)): Path<(String, String)>,
+    axum::Json(request): axum::Json<BatchGetRequest>,
+) -> Response {
+    let inner = state.lock();
+    let Some(table) = inner.tables.get(&(app_token, table_id)) else {
+        return table_not_found();
+    };
+
+    let (found, absent): (Vec<_>, Vec<_>) = request
+        .record_ids
+        .into_iter()
+        .partition(|id| table.record(id).is_some());
+    let records: Vec<Value> = found
+        .iter()
+        .filter_map(|id| table.record(id).cloned())
+        .collect();
+    success(json!({
+        "records": records,
+        "absent_record_ids": absent,
+        "forbidden_record_ids": [],
+    }))
+}
+
+#[derive(Deserialize)]
+struct BatchUpdateRequest {
+    records: Vec<RecordUpdate>,
+}
+
+#[derive(Deserialize)]
+struct RecordUpdate {
+    record_id: String,
+    fields: serde_json::Map<String, Value>,
+}
+
+async fn batch_update_records(
+    State(state): State<Arc<MockState>>,
+    Path((app_token, table_id)): Path<(String, String)>,
+    axum::Json(request): axum::Json<BatchUpdateRequest>,
+) -> Response {
+    let mut inner = state.lock();
+    let Some(table) = inner.tables.get_mut(&(app_token, table_id)) else {
+        return table_not_found();
+    };
+    let names = request
+        .records
+        .iter()
+        .flat_map(|record| record.fields.keys());
+    if has_unknown_field(table, names) {
+        return field_not_found();
+    }
+    if request
+        .records
+        .iter()
+        .any(|record| has_unknown_user(table, &record.fields))
+    {
+        return user_conversion_failed();
+    }
+    // Lark validates the whole batch before changing anything
+    if let Some(missing) = request
+        .records
+        .iter()
+        .find(|record| table.record(&record.record_id).is_none())
+    {
+        debug!("batch_update: unknown record {}", missing.record_id);
+        return record_not_found();
+    }
+
+    let records: Vec<Value> = request
+        .records
+        .into_iter()
+        .filter_map(|record| apply_update(table, &record.record_id, record.fields))
+        .collect();
+    success(json!({ "records": records }))
+}
+
+#[derive(Deserialize)]
+struct BatchDeleteRequest {
+    records: Vec<String>,
+}
+
+async fn batch_delete_records(
+    State(state): State<Arc<MockState>>,
+    Path((app_token, table_id)): Path<(String, String)>,
+    axum::Json(request): axum::Json<BatchDeleteRequest>,
+) -> Response {
+    let mut inner = state.lock();
+    let Some(table) = inner.tables.get_mut(&(app_token, table_id)) else {
+        return table_not_found();
+    };
+    if request.records.iter().any(|id| table.record(id).is_none()) {
+        return record_not_found();
+    }
+
+    table.records.retain(|record| {
+        !request
+            .records
+            .iter()
+            .any(|id| record["record_id"] == id.as_str())
+    });
+    let records: Vec<Value> = request
+        .records
+        .iter()
+        .map(|id| json!({ "deleted": true, "record_id": id }))
+        .collect();
+    success(json!({ "records": records }))
+}
+
+async fn list_fields(
+    State(state): State<Arc<MockState>>,
+    Path((app_token, table_id)): Path<(String, String)>,
+    Query(query): Query<PageQuery>,
+) -> Response {
+    let fields = match state.lock().tables.get(&(app_token, table_id)) {
+        Some(table) => table.fields.clone(),
+        None => return table_not_found(),
+    };
+
+    paginate(fields, &query, 20, 100)
+}
+
+fn field_id_not_found() -> Response {
+    failure(
+        StatusCode::BAD_REQUEST,
+        FIELD_ID_NOT_FOUND,
+        "FieldIdNotFound",
+    )
+}
+
+fn view_not_found() -> Response {
+    failure(StatusCode::BAD_REQUEST, VIEW_NOT_FOUND, "ViewIdNotFound")
+}
+
+/// Whether another field than `field_id` of `table` is named like `field`
+fn has_duplicate_field(table: &Table, field: &Value, field_id: Option<&str>) -> bool {
+    table.fields.iter().any(|other| {
+        other["field_name"] == field["field_name"]
+            && field_id.is_none_or(|id| other["field_id"] != id)
+    })
+}
+
+async fn create_field(
+    State(state): State<Arc<MockState>>,
+    Path((app_token, table_id)): Path<(String, String)>,
+    axum::Json(field): axum::Json<Value>,
+) -> Response {
+    let mut inner = state.lock();
+    let key = (app_token, table_id);
+    match inner.tables.get(&key) {
+        None => return table_not_found(),
+        Some(table) if has_duplicate_field(table, &field, None) => {
+            return failure(
+                StatusCode::BAD_REQUEST,
+                FIELD_NAME_DUPLICATED,
+                "FieldNameDuplicated",
+            );
+        }
+        Some(_) => {}
+    }
+
+    let field = inner.new_field(field);
+    if let Some(table) = inner.tables.get_mut(&key) {
+        table.fields.push(field.clone());
+    }
+    success(json!({ "field": field }))
+}
+
+async fn update_field(
+    State(state): State<Arc<MockState>>,
+    Path((app_token, table_id, field_id)): Path<(String, String, String)>,
+    axum::Json(mut field): axum::Json<Value>,
+) -> Response {
+    let mut inner = state.lock();
+    let Some(table) = inner.tables.get_mut(&(app_token, table_id)) else {
+        return table_not_found();
+    };
+    if has_duplicate_field(table, &field, Some(&field_id)) {
+        return failure(
+            StatusCode::BAD_REQUEST,
+            FIELD_NAME_DUPLICATED,
+            "FieldNameDuplicated",
+        );
+    }
+    let Some(current) = table
+        .fields
+        .iter_mut()
+        .find(|current| current["field_id"] == field_id.as_str())
+    else {
+        return field_id_not_found();
+    };
+
+    let old_name = current["field_name"].clone();
+    field["field_id"] = json!(field_id);
+    field["property"] = field.get("property").cloned().unwrap_or(Value::Null);
+    *current = field.clone();
+    // Records keep their values under the new name
+    if let (Some(old), Some(new)) = (old_name.as_str(), field["field_name"].as_str()) {
+        for record in &mut table.records {
+            if let Some(fields) = record["fields"].as_object_mut()
+                && let Some(value) = fields.remove(old)
+            {
+                fields.insert(new.to_string(), value);
+            }
+        }
+    }
+    success(json!({ "field": field }))
+}
+
+async fn delete_field(
+    State(state): State<Arc<MockState>>,
+    Path((app_token, table_id, field_id)): Path<(String, String, String)>,
+) -> Response {
+    let mut inner = state.lock();
+    let Some(table) = inner.tables.get_mut(&(app_token, table_id)) else {
+        return table_not_found();
+    };
+    let Some(index) = table
+        .fields
+        .iter()
+        .position(|field| field["field_id"] == field_id.as_str())
+    else {
+        return field_id_not_found();
+    };
+
+    let field = table.fields.remove(index);
+    if let Some(name) = field["field_name"].as_str() {
+        for record in &mut table.records {
+            if let Some(fields) = record["fields"].as_object_mut() {
+                fields.remove(name);
+            }
+        }
+    }
+    success(json!({ "deleted": true, "field_id": field_id }))
+}
+
+async fn list_tables(
+    State(state): State<Arc<MockState>>,
+    Path(app_token): Path<String>,
+    Query(query): Query<PageQuery>,
+) -> Response {
+    let tables = state
+        .tables(&app_token)
+        .into_iter()
+        .map(|(table_id, name)| json!({"table_id": table_id, "name": name, "revision": 1}))
+        .collect();
+
+    paginate(tables, &query, 20, 100)
+}
+
+fn table_name_duplicated() -> Response {
+    failure(
+        StatusCode::BAD_REQUEST,
+        TABLE_NAME_DUPLICATED,
+        "TableNameDuplicated",
+    )
+}
+
+/// Whether a table of `app_token` is already named `name`
+fn has_table_named(state: &MockState, app_token: &str, name: &str) -> bool {
+    state
+        .tables(app_token)
+        .iter()
+        .any(|(_, existing)| existing == name)
+}
+
+#[derive(Deserialize)]
+struct CreateTableRequest {
+    table: NewTable,
+}
+
+#[derive(Deserialize)]
+struct NewTable {
+    name: String,
+    default_view_name: Option<String>,
+    #[serde(default)]
+    fields: Vec<Value>,
+}
+
+/// Add an empty table named `name` with one grid view, returning its id
+fn insert_table(state: &MockState, app_token: &str, name: &str) -> String {
+    let mut inner = state.lock();
+    let table_id = format!("tblmock{}", inner.next_id());
+    inner.table_mut(app_token, &table_id).name = name.to_string();
+    table_id
+}
+
+async fn create_table(
+    State(state): State<Arc<MockState>>,
+    Path(app_token): Path<String>,
+    axum::Json(request): axum::Json<CreateTableRequest>,
+) -> Response {
+    let table = request.table;
+    if has_table_named(&state, &app_token, &table.name) {
+        return table_name_duplicated();
+    }
+
+    let table_id = insert_table(&state, &app_token, &table.name);
+    let mut inner = state.lock();
+    let fields: Vec<Value> = table
+        .fields
+        .into_iter()
+        .map(|field| inner.new_field(field))
+        .collect();
+    let field_ids: Vec<Value> = fields
+        .iter()
+        .map(|field| field["field_id"].clone())
+        .collect();
+    let created = inner.table_mut(&app_token, &table_id);
+    created.fields = fields;
+    if let Some(name) = table.default_view_name {
+        created.views[0]["view_name"] = json!(name);
+    }
+    let view_id = created.views[0]["view_id"].clone();
+    success(json!({"table_id": table_id, "default_view_id": view_id, "field_id_list": field_ids}))
+}
+
+#[derive(Deserialize)]
+struct BatchCreateTablesRequest {
+    tables: Vec<TableName>,
+}
+
+#[derive(Deserialize)]
+struct TableName {
+    name: String,
+}
+
+async fn batch_create_tables(
+    State(state): State<Arc<MockState>>,
+    Path(app_token): Path<String>,
+    axum::Json(request): axum::Json<BatchCreateTablesRequest>,
+) -> Response {
+    if request
+        .tables
+        .iter()
+        .any(|table| has_table_named(&state, &app_token, &table.name))
+    {
+        return table_name_duplicated();
+    }
+
+    let table_ids: Vec<String> = request
+        .tables
+        .iter()
+        .map(|table| insert_table(&state, &app_token, &table.name))
+        .collect();
+    success(json!({ "table_ids": table_ids }))
+}
+
+async fn rename_table(
+    State(state): State<Arc<MockState>>,
+    Path((app_token, table_id)): Path<(String, String)>,
+    axum::Json(request): axum::Json<TableName>,
+) -> Response {
+    if has_table_named(&state, &app_token, &request.name) {
+        return table_name_duplicated();
+    }
+    let mut inner = state.lock();
+    let Some(table) = inner.tables.get_mut(&(app_token, table_id)) else {
+        return table_not_found();
+    };
+    table.name = request.name;
+    success(json!({ "name": table.name }))
+}
+
+async fn delete_table(
+    State(state): State<Arc<MockState>>,
+    Path((app_token, table_id)): Path<(String, String)>,
+) -> Response {
+    match state.lock().tables.remove(&(app_token, table_id)) {
+        Some(_) => success(json!({})),
+        None => table_not_found(),
+    }
+}
+
+#[derive(Deserialize)]
+struct BatchDeleteTablesRequest {
+    table_ids: Vec<String>,
+}
+
+async fn batch_delete_tables(
+    State(state): State<Arc<MockState>>,
+    Path(app_token): Path<String>,
+    axum::Json(request): axum::Json<BatchDeleteTablesRequest>,
+) -> Response {
+    let mut inner = state.lock();
+    let keys: Vec<(String, String)> = request
+        .table_ids
+        .into_iter()
+        .map(|table_id| (app_token.clone(), table_id))
+        .collect();
+    if keys.iter().any(|key| !inner.tables.contains_key(key)) {
+        return table_not_found();
+    }
+    for key in &keys {
+        inner.tables.remove(key);
+    }
+    success(json!({}))
+}
+
+async fn list_views(
+    State(state): State<Arc<MockState>>,
+    Path((app_token, table_id)): Path<(String, String)>,
+    Query(query): Query<PageQuery>,
+) -> Response {
+    let views = match state.lock().tables.get(&(app_token, table_id)) {
+        Some(table) => table.views.clone(),
+        None => return table_not_found(),
+    };
+
+    paginate(views, &query, 20, 100)
+}
+
+async fn create_view(
+    State(state): State<Arc<MockState>>,
+    Path((app_token, table_id)): Path<(String, String)>,
+    axum::Json(view): axum::Json<Value>,
+) -> Response {
+    let mut inner = state.lock();
+    let key = (app_token, table_id);
+    if !inner.tables.contains_key(&key) {
+        return table_not_found();
+    }
+    let view = inner.new_view(view);
+    if let Some(table) = inner.tables.get_mut(&key) {
+        table.views.push(view.clone());
+    }
+    success(json!({ "view": view }))
+}
+
+async fn get_view(
+    State(state): State<Arc<MockState>>,
+    Path((app_token, table_id, view_id)): Path<(String, String, String)>,
+) -> Response {
+    let inner = state.lock();
+    let Some(table) = inner.tables.get(&(app_token, table_id)) else {
+        return table_not_found();
+    };
+    match table
+        .views
+        .iter()
+        .find(|view| view["view_id"] == view_id.as_str())
+    {
+        Some(view) => success(json!({ "view": view })),
+        None => view_not_found(),
+    }
+}
+
+async fn rename_view(
+    State(state): State<Arc<MockState>>,
+    Path((app_token, table_id, view_id)): Path<(String, String, String)>,
+    axum::Json(request): axum::Json<serde_json::Map<String, Value>>,
+) -> Response {
+    let mut inner = state.lock();
+    let Some(table) = inner.tables.get_mut(&(app_token, table_id)) else {
+        return table_not_found();
+    };
+    let Some(view) = table
+        .views
+        .iter_mut()
+        .find(|view| view["view_id"] == view_id.as_str())
+    else {
+        return view_not_found();
+    };
+    if let Some(view) = view.as_object_mut() {
+        view.extend(request);
+    }
+    success(json!({ "view": view }))
+}
+
+async fn delete_view(
+    State(state): State<Arc<MockState>>,
+    Path((app_token, table_id, view_id)): Path<(String, String, String)>,
+) -> Response {
+    let mut inner = state.lock();
+    let Some(table) = inner.tables.get_mut(&(app_token, table_id)) else {
+        return table_not_found();
+    };
+    if !table
+        .views
+        .iter()
+        .any(|view| view["view_id"] == view_id.as_str())
+    {
+        return view_not_found();
+    }
+    table
+        .views
+        .retain(|view| view["view_id"] != view_id.as_str());
+    success(json!({}))
+}
+
+async fn list_chats(
+    State(state): State<Arc<MockState>>,
+    Query(query): Query<PageQuery>,
+) -> Response {
+    let chats = state.lock().chats.clone();
+    paginate(chats, &query, 20, 100)
+}
+
+#[derive(Deserialize)]
+struct MessageQuery {
+    receive_id_type: String,
+}
+
+#[derive(Deserialize)]
+struct CreateMessageRequest {
+    receive_id: String,
+    msg_type: String,
+    content: String,
+}
+
+async fn create_message(
+    State(state): State<Arc<MockState>>,
+    Query(query): Query<MessageQuery>,
+    axum::Json(request): axum::Json<CreateMessageRequest>,
+) -> Response {
+    let mut inner = state.lock();
+    if query.receive_id_type == "chat_id"
+        && !inner
+            .chats
+            .iter()
+            .any(|chat| chat["chat_id"] == request.receive_id.as_str())
+    {
+        return failure(
+            StatusCode::BAD_REQUEST,
+            BOT_NOT_IN_CHAT,
+            "Bot/User can NOT be out of the chat.",
+        );
+    }
+
+    let message = json!({
+        "message_id": format!("om_mock{}", inner.next_id()),
+        "chat_id": request.receive_id,
+        "msg_type": request.msg_type,
+        "body": {"content": request.content},
+    });
+    inner.messages.push(message.clone());
+    success(message)
+}
+
+async fn list_wiki_nodes(
+    State(state): State<Arc<MockState>>,
+    Path(space_id): Path<String>,
+    Query(query): Query<PageQuery>,
+) -> Response {
+    let nodes = state
+        .lock()
+        .wiki_nodes
+        .get(&space_id)
+        .cloned()
+        .unwrap_or_default();
+    paginate(nodes, &query, 20, 50)
+}
+
+async fn get_wiki_node(
+    State(state): State<Arc<MockState>>,
+    Path((space_id, node_token)): Path<(String, String)>,
+) -> Response {
+    let inner = state.lock();
+    let node = inner.wiki_nodes.get(&space_id).and_then(|nodes| {
+        nodes
+            .iter()
+            .find(|node| node["node_token"] == node_token.as_str())
+    });
+    match node {
+        Some(node) => success(node.clone()),
+        None => failure(StatusCode::NOT_FOUND, WIKI_NOT_FOUND, "not found"),
+    }
+}
+
+async fn raw_content(
+    State(state): State<Arc<MockState>>,
+    Path(document_id): Path<String>,
+) -> Response {
+    match state.lock().documents.get(&document_id) {
+        Some(content) => success(json!({ "content": content })),
+        None => failure(
+            StatusCode::NOT_FOUND,
+            DOCUMENT_NOT_FOUND,
+            "document not found",
+        ),
+    }
+}
@@ -0,0 +1,273 @@
+use serde::Deserialize;
+use serde_json::{Value, json};
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+
+/// Lifetime announced for issued tenant access tokens
+pub(crate) const TOKEN_EXPIRE_SECS: i64 = 7200;
+
+/// In-memory data served by a [`crate::MockLarkServer`]
+#[derive(Debug, Default)]
+pub struct MockState {
+    inner: Mutex<Inner>,
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct Inner {
+    /// Accepted credentials, app_id to app_secret. Any credentials are accepted while empty.
+    pub apps: HashMap<String, String>,
+    pub tokens: HashSet<String>,
+    pub token_requests: usize,
+    pub tables: HashMap<(String, String), Table>,
+    pub chats: Vec<Value>,
+    pub messages: Vec<Value>,
+    pub wiki_nodes: HashMap<String, Vec<Value>>,
+    pub documents: HashMap<String, String>,
+    pub counter: u64,
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct Table {
+    pub name: String,
+    /// Creation order, tables being listed in that order
+    pub position: u64,
+    pub fields: Vec<Value>,
+    pub records: Vec<Value>,
+    pub views: Vec<Value>,
+}
+
+impl Table {
+    pub fn record(&self, record_id: &str) -> Option<&Value> {
+        self.records
+            .iter()
+            .find(|record| record["record_id"] == record_id)
+    }
+}
+
+impl Inner {
+    /// Next value of a counter shared by every generated id
+    pub fn next_id(&mut self) -> u64 {
+        self.counter += 1;
+        self.counter
+    }
+
+    pub fn issue_token(&mut self) -> String {
+        let token = format!("t-mock-{}", self.next_id());
+        self.tokens.insert(token.clone());
+        token
+    }
+
+    /// The table `table_id`, created with the id as name and one grid view if missing
+    pub fn table_mut(&mut self, app_token: &str, table_id: &str) -> &mut Table {
+        let key = (app_token.to_string(), table_id.to_string());
+        if !self.tables.contains_key(&key) {
+            let position = self.next_id();
+            let view = self.new_view(json!({"view_name": "Grid", "view_type": "grid"}));
+            let table = Table {
+                name: table_id.to_string(),
+                position,
+                views: vec![view],
+                ..Default::default()
+            };
+            self.tables.insert(key.clone(), table);
+        }
+        self.tables.get_mut(&key).unwrap()
+    }
+
+    /// Fill in the `field_id`, `type`, `ui_type` and `property` of a field definition
+    pub fn new_field(&mut self, mut field: Value) -> Value {
+        if let Some(field) = field.as_object_mut() {
+            if !field.contains_key("field_id") {
+                let id = format!("fldmock{}", self.next_id());
+                field.insert("field_id".to_string(), json!(id));
+            }
+            field.entry("type").or_insert(json!(1));
+            field.entry("ui_type").or_insert(json!("Text"));
+            field.entry("property").or_insert(Value::Null);
+        }
+        field
+    }
+
+    /// Fill in the `view_id` and `view_type` of a view definition
+    pub fn new_view(&mut self, mut view: Value) -> Value {
+        let id = format!("vewmock{}", self.next_id());
+        if let Some(view) = view.as_object_mut() {
+            view.insert("view_id".to_string(), json!(id));
+            view.entry("view_type").or_insert(json!("grid"));
+        }
+        view
+    }
+
+    pub fn insert_record(&mut self, app_token: &str, table_id: &str, fields: Value) -> Value {
+        let record = json!({
+            "record_id": format!("recmock{}", self.next_id()),
+            "fields": fields,
+        });
+        self.table_mut(app_token, table_id)
+            .records
+            .push(record.clone());
+        record
+    }
+}
+
+/// Initial data of a mock server, e.g. loaded from a JSON file by the `larkrs-mock` binary
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct Seed {
+    /// Accepted credentials, app_id to app_secret
+    pub apps: HashMap<String, String>,
+    pub tables: Vec<SeedTable>,
+    /// Chats in the Lark shape, at least `chat_id` and `name`
+    pub chats: Vec<Value>,
+    /// Wiki nodes by space id, in the Lark shape
+    pub wiki_nodes: HashMap<String, Vec<Value>>,
+    /// Raw content of docx documents by document token
+    pub documents: HashMap<String, String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct SeedTable {
+    pub app_token: String,
+    pub table_id: String,
+    /// Fields in the Lark shape, at least `field_name`
+    pub fields: Vec<Value>,
+    /// `fields` objects of the initial records
+    pub records: Vec<Value>,
+}
+
+impl MockState {
+    pub(crate) fn lock(&self) -> std::sync::MutexGuard<'_, Inner> {
+        self.inner.lock().unwrap()
+    }
+
+    /// Only accept these credentials from now on
+    pub fn add_app(&self, app_id: &str, app_secret: &str) {
+        self.lock()
+            .apps
+            .insert(app_id.to_string(), app_secret.to_string());
+    }
+
+    /// Create a table with the given fields. Missing `field_id`, `type` and `ui_type` are
+    /// filled with text field defaults.
+    pub fn add_table(&self, app_token: &str, table_id: &str, fields: Vec<Value>) {
+        let mut inner = self.lock();
+        let fields = fields
+            .into_iter()
+            .map(|field| inner.new_field(field))
+            .collect();
+        inner.table_mut(app_token, table_id).fields = fields;
+    }
+
+    /// Insert records into a table and return their ids
+    pub fn add_records(&self, app_token: &str, table_id: &str, records: Vec<Value>) -> Vec<String> {
+        let mut inner = self.lock();
+        records
+            .into_iter()
+            .map(|fields| {
+                let record = inner.insert_record(app_token, table_id, fields);
+                record["record_id"].as_str().unwrap_or_default().to_string()
+            })
+            .collect()
+    }
+
+    /// Records of a table, as `{"record_id", "fields"}` objects
+    pub fn records(&self, app_token: &str, table_id: &str) -> Vec<Value> {
+        self.lock()
+            .tables
+            .get(&(app_token.to_string(), table_id.to_string()))
+            .map(|table| table.records.clone())
+            .unwrap_or_default()
+    }
+
+    /// Tables of an app as `(table_id, name)` pairs, in creation order
+    pub fn tables(&self, app_token: &str) -> Vec<(String, String)> {
+        let inner = self.lock();
+        let mut tables: Vec<_> = inner
+            .tables
+            .iter()
+            .filter(|((app, _), _)| app == app_token)
+            .collect();
+        tables.sort_by_key(|(_, table)| table.position);
+        tables
+            .into_iter()
+            .map(|((_, table_id), table)| (table_id.clone(), table.name.clone()))
+            .collect()
+    }
+
+    /// Add a chat the bot is a member of
+    pub fn add_chat(&self, chat_id: &str, name: &str) {
+        self.lock()
+            .chats
+            .push(json!({"chat_id": chat_id, "name": name, "chat_mode": "group"}));
+    }
+
+    /// Messages sent so far, as returned by the create message endpoint
+    pub fn messages(&self) -> Vec<Value> {
+        self.lock().messages.clone()
+    }
+
+    /// Add a wiki node to a space. `node_token` is required, `space_id` and the other
+    /// fields are filled with defaults.
+    pub fn add_wiki_node(&self, space_id: &str, node: Value) {
+        let mut node = node;
+        if let Some(fields) = node.as_object_mut() {
+            fields.insert("space_id".to_string(), json!(space_id));
+            for key in [
+                "node_type",
+                "parent_node_token",
+                "title",
+                "obj_token",
+                "obj_type",
+            ] {
+                fields.entry(key).or_insert(json!(""));
+            }
+            fields.entry("has_child").or_insert(json!(false));
+        }
+        self.lock()
+            .wiki_nodes
+            .entry(space_id.to_string())
+            .or_default()
+            .push(node);
+    }
+
+    /// Set the raw content of a docx document
+    pub fn add_document(&self, document_id: &str, content: &str) {
+        self.lock()
+            .documents
+            .insert(document_id.to_string(), content.to_string());
+    }
+
+    /// Invalidate every issued token, as if they had expired on the Lark side
+    pub fn revoke_tokens(&self) {
+        self.lock().tokens.clear();
+    }
+
+    /// Number of tenant access token requests received
+    pub fn token_requests(&self) -> usize {
+        self.lock().token_requests
+    }
+
+    /// Load initial data
+    pub fn seed(&self, seed: Seed) {
+        for (app_id, app_secret) in &seed.apps {
+            self.add_app(app_id, app_secret);
+        }
+        for table in seed.tables {
+            self.add_table(&table.app_token, &table.table_id, table.fields);
+            self.add_records(&table.app_token, &table.table_id, table.records);
+        }
+        self.lock().chats.extend(seed.chats);
+        for (space_id, nodes) in seed.wiki_nodes {
+            for node in nodes {
+                self.add_wiki_node(&space_id, node);
+            }
+        }
+        self.lock().documents.extend(seed.documents);
+    }
+}
+
+/// A field definition for [`MockState::add_table`]
+pub fn field(name: &str, field_type: i32, ui_type: &str) -> Value {
+    json!({"field_name": name, "type": field_type, "ui_type": ui_type})
+}
@@ -1,40 +1,94 @@
-use poem_mcpserver::{McpServer, Tools, stdio::stdio, tool::Json};
-use log::{info, warn, error};
+use poem::{Response, Route, Server, handler, listener::TcpListener};
+use poem_mcpserver::{McpServer, Tools, sse::sse_endpoint, stdio::stdio, tool::Json};
+use log::{info, error};
+use futures_util::{StreamExt, TryStreamExt};
+
+use std::sync::Arc;
 
 use larkrs_client::{
-    bitable::{FieldInfo, SearchRecordsCond, SearchRecordsResponse, table::BitableTableClient},
-    bot::{ChatInfoItem, chat::ChatClient},
-    wiki::{WikiContentResponse, client::WikiClient},
+    LarkClient, Result,
+    ratelimit::RateLimiter,
+    registry::LarkClientRegistry,
+    bitable::{
+        BatchDeleteRecordsRequest, BatchGetRecordsRequest, BatchGetRecordsResponse,
+        BatchUpdateRecordsRequest, CreateReport, CreatedTable, DeletedField, DeletedRecord, Field, FieldCreate,
+        FieldInfo, Record, SearchRecordsCond, Table, TableCreate,
+        UpsertReport, View, ViewCreate,
+    },
+    bot::ChatInfoItem,
+    wiki::WikiContentResponse,
 };
 
-struct LarkServer {}
+/// Most records `table_records_list` returns, so a large table does not flood the context
+const MAX_LISTED_RECORDS: usize = 2000;
+
+/// Most chats `chat_group_list` returns
+const MAX_LISTED_CHATS: usize = 500;
+
+#[derive(Clone)]
+struct LarkServer {
+    registry: LarkClientRegistry,
+}
+
+/// Log the error of a failed tool call, which is returned to the model as the tool's error text
+fn reply<T>(tool: &str, result: Result<T>) -> Result<Json<T>> {
+    result.map(Json).inspect_err(|e| {
+        error!("{} failed: {}", tool, e);
+    })
+}
+
+impl LarkServer {
+    /// Client of the named profile, or of the default one
+    fn client(&self, profile: Option<String>) -> Result<LarkClient> {
+        self.registry.get(profile.as_deref()).inspect_err(|e| {
+            error!("{}", e);
+        })
+    }
+}
 
 #[Tools]
 impl LarkServer {
+    /// List the configured Feishu app profiles
+    ///
+    /// Every other tool takes an optional `profile` argument selecting the app (and tenant)
+    /// to act as; without it the default profile is used.
+    ///
+    /// Returns:
+    ///     A JSON array of profile names
+    async fn list_profiles(&self) -> Json<Vec<String>> {
+        Json(self.registry.names().map(str::to_string).collect())
+    }
+
     /// Get records list from a Bitable table
     ///
     /// input feishu url like: https://xxx.feishu.cn/base/{app_token}?table={table_id}&view={view_id}
     /// Args:
     ///     app_token: The app token of the Bitable app
     ///     table_id: The ID of the table
+    ///     profile: Optional app profile, see list_profiles
     ///
     /// Returns:
-    ///     A JSON response containing the list of records
+    ///     A JSON array of the records, following every page up to the first 2000 records
     async fn table_records_list(
         &self,
         app_token: String,
         table_id: String,
-    ) -> Json<SearchRecordsResponse> {
-        Json(
-            BitableTableClient::new()
-                .get_records_list(
+        profile: Option<String>,
+    ) -> Result<Json<Vec<Record>>> {
+        let records = async {
+            self.client(profile)?
+                .bitable()
+                .get_records_list_stream(
                     app_token.as_str(),
                     table_id.as_str(),
                     SearchRecordsCond::default(),
-                )
+                )?
+                .with_page_size(500)
+                .take(MAX_LISTED_RECORDS)
+                .try_collect()
                 .await
-                .unwrap_or_default(),
-        )
+        };
+        reply("table_records_list", records.await)
     }
 
     /// Batch create multiple records in a Bitable table
@@ -44,34 +98,261 @@ impl LarkServer {
     ///     app_token: The app token of the Bitable app
     ///     table_id: The ID of the table
     ///     records_json: A JSON string containing an array of records to create
+    ///     profile: Optional app profile, see list_profiles
     ///
     /// records_json like:
-    /// 如果字段类型是时间类型，需要传入时间戳，单位是毫秒; 日期得是当前上下文中的日期时间戳
+    /// 时间类型字段可传毫秒时间戳，或 "2025-03-26"、"20250326"、"2025-03-26 09:30"（默认 Asia/Shanghai，可在末尾指定时区）
     /// ```
     /// [
     ///     {"股票名称": "太阳电缆", "题材概念": "海洋经济", "日期": 1742956800000, "梯队": ["四板"]},
-    ///     {"股票名称": "太阳电缆", "题材概念": "海洋经济", "日期": 1742956800000, "梯队": ["四板"]}
+    ///     {"股票名称": "太阳电缆", "题材概念": "海洋经济", "日期": "2025-03-26", "梯队": "四板"}
     /// ]
     /// ```
     ///
+    /// Records are checked against the table fields first; nothing is created if one of them
+    /// is invalid. Valid records are created 500 at a time; if a batch fails, the later ones
+    /// are not sent and the earlier batches stay created.
+    ///
     /// Returns:
-    ///     A JSON response containing the result of the batch create operation
+    ///     The records as written, plus the errors (row, field, message) if they were rejected,
+    ///     the record_ids of the created records, and the error if a batch failed: only the
+    ///     records listed in record_ids were created
     async fn create_table_records_json(
         &self,
         app_token: String,
         table_id: String,
         records_json: String,
-    ) -> Json<()> {
-        Json(
-            BitableTableClient::new()
-                .batch_create_records_json(
+        profile: Option<String>,
+    ) -> Result<Json<CreateReport>> {
+        let created = async {
+            self.client(profile)?
+                .bitable()
+                .batch_create_validated_records_json(
                     app_token.as_str(),
                     table_id.as_str(),
                     records_json.as_str(),
                 )
                 .await
-                .unwrap_or_default(),
-        )
+        };
+        let created = created.await.inspect(|report| {
+            if let Some(e) = &report.error {
+                error!("create_table_records_json failed: {}", e);
+            }
+        });
+        reply("create_table_records_json", created)
+    }
+
+    /// Create or update records in a Bitable table, matched on key fields
+    ///
+    /// Rows whose key field values match an existing record update it (when a value differs),
+    /// the other rows are created, so that rerunning an import does not create duplicates.
+    ///
+    /// Args:
+    ///     app_token: The app token of the Bitable app
+    ///     table_id: The ID of the table
+    ///     key_fields: Names of the fields identifying a record, like ["股票名称", "日期"]
+    ///     records_json: A JSON string containing an array of records, as for create_table_records_json
+    ///     profile: Optional app profile, see list_profiles
+    ///
+    /// Returns:
+    ///     One entry per record with its record_id and action (created, updated or unchanged),
+    ///     plus the error if a batch failed: its records and the later ones are "failed",
+    ///     the others were written
+    async fn upsert_table_records_json(
+        &self,
+        app_token: String,
+        table_id: String,
+        key_fields: Vec<String>,
+        records_json: String,
+        profile: Option<String>,
+    ) -> Result<Json<UpsertReport>> {
+        let report = async {
+            let key_fields: Vec<&str> = key_fields.iter().map(String::as_str).collect();
+            self.client(profile)?
+                .bitable()
+                .upsert_records_json(&app_token, &table_id, &key_fields, &records_json)
+                .await
+        };
+        let report = report.await.inspect(|report| {
+            if let Some(e) = &report.error {
+                error!("upsert_table_records_json failed: {}", e);
+            }
+        });
+        reply("upsert_table_records_json", report)
+    }
+
+    /// Get one record of a Bitable table by id
+    ///
+    /// Args:
+    ///     app_token: The app token of the Bitable app
+    ///     table_id: The ID of the table
+    ///     record_id: The ID of the record, like rec...
+    ///     profile: Optional app profile, see list_profiles
+    ///
+    /// Returns:
+    ///     The record with its fields
+    async fn get_table_record(
+        &self,
+        app_token: String,
+        table_id: String,
+        record_id: String,
+        profile: Option<String>,
+    ) -> Result<Json<Record>> {
+        let record = async {
+            self.client(profile)?
+                .bitable()
+                .get_record(&app_token, &table_id, &record_id)
+                .await
+        };
+        reply("get_table_record", record.await)
+    }
+
+    /// Get several records of a Bitable table by id
+    ///
+    /// Args:
+    ///     app_token: The app token of the Bitable app
+    ///     table_id: The ID of the table
+    ///     record_ids: The IDs of the records, at most 100
+    ///     profile: Optional app profile, see list_profiles
+    ///
+    /// Returns:
+    ///     The records found, plus the requested ids that do not exist (absent_record_ids)
+    ///     or cannot be read (forbidden_record_ids)
+    async fn batch_get_table_records(
+        &self,
+        app_token: String,
+        table_id: String,
+        record_ids: Vec<String>,
+        profile: Option<String>,
+    ) -> Result<Json<BatchGetRecordsResponse>> {
+        let records = async {
+            self.client(profile)?
+                .bitable()
+                .batch_get_records(&app_token, &table_id, BatchGetRecordsRequest::new(record_ids))
+                .await
+        };
+        reply("batch_get_table_records", records.await)
+    }
+
+    /// Update some fields of one record in a Bitable table
+    ///
+    /// Args:
+    ///     app_token: The app token of the Bitable app
+    ///     table_id: The ID of the table
+    ///     record_id: The ID of the record
+    ///     fields_json: A JSON object of the fields to change, like {"梯队": ["五板"]};
+    ///         fields left out keep their value
+    ///     profile: Optional app profile, see list_profiles
+    ///
+    /// Returns:
+    ///     The updated record
+    async fn update_table_record(
+        &self,
+        app_token: String,
+        table_id: String,
+        record_id: String,
+        fields_json: String,
+        profile: Option<String>,
+    ) -> Result<Json<Record>> {
+        let record = async {
+            let fields = serde_json::from_str(&fields_json)?;
+            self.client(profile)?
+                .bitable()
+                .update_record(&app_token, &table_id, &record_id, fields)
+                .await
+        };
+        reply("update_table_record", record.await)
+    }
+
+    /// Update several records of a Bitable table at once
+    ///
+    /// Args:
+    ///     app_token: The app token of the Bitable app
+    ///     table_id: The ID of the table
+    ///     records_json: A JSON array of records with their ID and the fields to change
+    ///     profile: Optional app profile, see list_profiles
+    ///
+    /// records_json like:
+    /// ```
+    /// [
+    ///     {"record_id": "recA", "fields": {"梯队": ["五板"]}},
+    ///     {"record_id": "recB", "fields": {"题材概念": "海洋经济"}}
+    /// ]
+    /// ```
+    ///
+    /// Returns:
+    ///     The updated records; nothing is updated if one record is invalid
+    async fn batch_update_table_records(
+        &self,
+        app_token: String,
+        table_id: String,
+        records_json: String,
+        profile: Option<String>,
+    ) -> Result<Json<Vec<Record>>> {
+        let records = async {
+            let records = serde_json::from_str(&records_json)?;
+            self.client(profile)?
+                .bitable()
+                .batch_update_records(&app_token, &table_id, BatchUpdateRecordsRequest { records })
+                .await
+        };
+        reply("batch_update_table_records", records.await)
+    }
+
+    /// Delete one record of a Bitable table
+    ///
+    /// Args:
+    ///     app_token: The app token of the Bitable app
+    ///     table_id: The ID of the table
+    ///     record_id: The ID of the record
+    ///     profile: Optional app profile, see list_profiles
+    ///
+    /// Returns:
+    ///     The ID of the record and whether it was deleted
+    async fn delete_table_record(
+        &self,
+        app_token: String,
+        table_id: String,
+        record_id: String,
+        profile: Option<String>,
+    ) -> Result<Json<DeletedRecord>> {
+        let deleted = async {
+            self.client(profile)?
+                .bitable()
+                .delete_record(&app_token, &table_id, &record_id)
+                .await
+        };
+        reply("delete_table_record", deleted.await)
+    }
+
+    /// Delete several records of a Bitable table at once
+    ///
+    /// Args:
+    ///     app_token: The app token of the Bitable app
+    ///     table_id: The ID of the table
+    ///     record_ids: The IDs of the records to delete
+    ///     profile: Optional app profile, see list_profiles
+    ///
+    /// Returns:
+    ///     The ID of every record and whether it was deleted
+    async fn batch_delete_table_records(
+        &self,
+        app_token: String,
+        table_id: String,
+        record_ids: Vec<String>,
+        profile: Option<String>,
+    ) -> Result<Json<Vec<DeletedRecord>>> {
+        let deleted = async {
+            self.client(profile)?
+                .bitable()
+                .batch_delete_records(
+                    &app_token,
+                    &table_id,
+                    BatchDeleteRecordsRequest { records: record_ids },
+                )
+                .await
+        };
+        reply("batch_delete_table_records", deleted.await)
     }
 
     /// Get simplified fields info from a Bitable table
@@ -80,29 +361,376 @@ impl LarkServer {
     /// Args:
     ///     app_token: The app token of the Bitable app
     ///     table_id: The ID of the table
+    ///     profile: Optional app profile, see list_profiles
     ///
     /// Returns:
-    ///     A JSON array of simplified field information (field_name, description, is_primary, ui_type, write_type)
-    async fn table_fields_info(&self, app_token: String, table_id: String) -> Json<Vec<FieldInfo>> {
-        let fields_response = BitableTableClient::new()
-            .get_fields_list(app_token.as_str(), table_id.as_str())
-            .await
-            .unwrap_or_default();
+    ///     A JSON array of simplified field information (field_id, field_name, description, is_primary, ui_type, write_type, example);
+    ///     write the example's shape to each field, fields whose write_type is null are read-only
+    async fn table_fields_info(
+        &self,
+        app_token: String,
+        table_id: String,
+        profile: Option<String>,
+    ) -> Result<Json<Vec<FieldInfo>>> {
+        let fields = async {
+            self.client(profile)?
+                .bitable()
+                .get_fields_list_all(app_token.as_str(), table_id.as_str())
+                .await
+        };
+        let fields = fields.await.map(|fields| fields.into_iter().map(FieldInfo::from).collect());
+        reply("table_fields_info", fields)
+    }
+
+    /// Add a field to a Bitable table
+    ///
+    /// Args:
+    ///     app_token: The app token of the Bitable app
+    ///     table_id: The ID of the table
+    ///     field_json: A JSON object defining the field, see below
+    ///     profile: Optional app profile, see list_profiles
+    ///
+    /// field_json like:
+    /// type: 1 文本, 2 数字, 3 单选, 4 多选, 5 日期, 7 复选框, 11 人员, 13 电话, 15 超链接, 17 附件,
+    /// 18 单向关联, 21 双向关联, 22 地理位置, 23 群组
+    /// ```
+    /// {"field_name": "梯队", "type": 4, "property": {"options": [{"name": "四板"}, {"name": "五板"}]}}
+    /// ```
+    ///
+    /// Returns:
+    ///     The created field with its field_id
+    async fn create_table_field(
+        &self,
+        app_token: String,
+        table_id: String,
+        field_json: String,
+        profile: Option<String>,
+    ) -> Result<Json<Field>> {
+        let field = async {
+            let field: FieldCreate = serde_json::from_str(&field_json)?;
+            self.client(profile)?
+                .bitable()
+                .create_field(&app_token, &table_id, field)
+                .await
+        };
+        reply("create_table_field", field.await)
+    }
+
+    /// Replace the definition of a field of a Bitable table, e.g. to rename it or change its options
+    ///
+    /// Args:
+    ///     app_token: The app token of the Bitable app
+    ///     table_id: The ID of the table
+    ///     field_id: The ID of the field, see table_fields_info
+    ///     field_json: The full new definition, as for create_table_field; settings left out are reset
+    ///     profile: Optional app profile, see list_profiles
+    ///
+    /// Returns:
+    ///     The updated field
+    async fn update_table_field(
+        &self,
+        app_token: String,
+        table_id: String,
+        field_id: String,
+        field_json: String,
+        profile: Option<String>,
+    ) -> Result<Json<Field>> {
+        let field = async {
+            let field: FieldCreate = serde_json::from_str(&field_json)?;
+            self.client(profile)?
+                .bitable()
+                .update_field(&app_token, &table_id, &field_id, field)
+                .await
+        };
+        reply("update_table_field", field.await)
+    }
+
+    /// Delete a field of a Bitable table with its values
+    ///
+    /// Args:
+    ///     app_token: The app token of the Bitable app
+    ///     table_id: The ID of the table
+    ///     field_id: The ID of the field, see table_fields_info; the primary field cannot be deleted
+    ///     profile: Optional app profile, see list_profiles
+    ///
+    /// Returns:
+    ///     The ID of the field and whether it was deleted
+    async fn delete_table_field(
+        &self,
+        app_token: String,
+        table_id: String,
+        field_id: String,
+        profile: Option<String>,
+    ) -> Result<Json<DeletedField>> {
+        let deleted = async {
+            self.client(profile)?
+                .bitable()
+                .delete_field(&app_token, &table_id, &field_id)
+                .await
+        };
+        reply("delete_table_field", deleted.await)
+    }
+
+    /// List the tables of a Bitable app
+    ///
+    /// input feishu url like: https://xxx.feishu.cn/base/{app_token}
+    /// Args:
+    ///     app_token: The app token of the Bitable app
+    ///     profile: Optional app profile, see list_profiles
+    ///
+    /// Returns:
+    ///     A JSON array of tables with table_id and name
+    async fn list_tables(
+        &self,
+        app_token: String,
+        profile: Option<String>,
+    ) -> Result<Json<Vec<Table>>> {
+        let tables = async {
+            self.client(profile)?
+                .bitable()
+                .get_tables_list_all(&app_token)
+                .await
+        };
+        reply("list_tables", tables.await)
+    }
+
+    /// Create a table in a Bitable app, with its fields
+    ///
+    /// Args:
+    ///     app_token: The app token of the Bitable app
+    ///     name: The name of the table
+    ///     fields_json: Optional JSON array of fields as for create_table_field; the first one
+    ///         becomes the primary field
+    ///     profile: Optional app profile, see list_profiles
+    ///
+    /// Returns:
+    ///     The table_id, the ID of the default view and the IDs of the fields
+    async fn create_table(
+        &self,
+        app_token: String,
+        name: String,
+        fields_json: Option<String>,
+        profile: Option<String>,
+    ) -> Result<Json<CreatedTable>> {
+        let created = async {
+            let mut table = TableCreate::new(name);
+            if let Some(fields_json) = fields_json {
+                table.fields = serde_json::from_str(&fields_json)?;
+            }
+            self.client(profile)?
+                .bitable()
+                .create_table(&app_token, table)
+                .await
+        };
+        reply("create_table", created.await)
+    }
+
+    /// Create several empty tables in a Bitable app
+    ///
+    /// Args:
+    ///     app_token: The app token of the Bitable app
+    ///     names: The names of the tables
+    ///     profile: Optional app profile, see list_profiles
+    ///
+    /// Returns:
+    ///     The IDs of the tables, in the order of names
+    async fn batch_create_tables(
+        &self,
+        app_token: String,
+        names: Vec<String>,
+        profile: Option<String>,
+    ) -> Result<Json<Vec<String>>> {
+        let table_ids = async {
+            let names: Vec<&str> = names.iter().map(String::as_str).collect();
+            self.client(profile)?
+                .bitable()
+                .batch_create_tables(&app_token, &names)
+                .await
+        };
+        reply("batch_create_tables", table_ids.await)
+    }
+
+    /// Rename a table of a Bitable app
+    ///
+    /// Args:
+    ///     app_token: The app token of the Bitable app
+    ///     table_id: The ID of the table
+    ///     name: The new name
+    ///     profile: Optional app profile, see list_profiles
+    ///
+    /// Returns:
+    ///     Whether the table was renamed
+    async fn rename_table(
+        &self,
+        app_token: String,
+        table_id: String,
+        name: String,
+        profile: Option<String>,
+    ) -> Result<Json<bool>> {
+        let renamed = async {
+            self.client(profile)?
+                .bitable()
+                .rename_table(&app_token, &table_id, &name)
+                .await
+        };
+        reply("rename_table", renamed.await.map(|()| true))
+    }
+
+    /// Delete tables of a Bitable app with their records
+    ///
+    /// Args:
+    ///     app_token: The app token of the Bitable app
+    ///     table_ids: The IDs of the tables; the last table of an app cannot be deleted
+    ///     profile: Optional app profile, see list_profiles
+    ///
+    /// Returns:
+    ///     Whether the tables were deleted
+    async fn delete_tables(
+        &self,
+        app_token: String,
+        table_ids: Vec<String>,
+        profile: Option<String>,
+    ) -> Result<Json<bool>> {
+        let deleted = async {
+            let table_ids: Vec<&str> = table_ids.iter().map(String::as_str).collect();
+            self.client(profile)?
+                .bitable()
+                .batch_delete_tables(&app_token, &table_ids)
+                .await
+        };
+        reply("delete_tables", deleted.await.map(|()| true))
+    }
+
+    /// List the views of a Bitable table
+    ///
+    /// Args:
+    ///     app_token: The app token of the Bitable app
+    ///     table_id: The ID of the table
+    ///     profile: Optional app profile, see list_profiles
+    ///
+    /// Returns:
+    ///     A JSON array of views with view_id, view_name and view_type
+    async fn list_table_views(
+        &self,
+        app_token: String,
+        table_id: String,
+        profile: Option<String>,
+    ) -> Result<Json<Vec<View>>> {
+        let views = async {
+            self.client(profile)?
+                .bitable()
+                .get_views_list_all(&app_token, &table_id)
+                .await
+        };
+        reply("list_table_views", views.await)
+    }
+
+    /// Create a view of a Bitable table
+    ///
+    /// Args:
+    ///     app_token: The app token of the Bitable app
+    ///     table_id: The ID of the table
+    ///     view_name: The name of the view
+    ///     view_type: Optional type: grid (default), kanban, gallery, gantt or form
+    ///     profile: Optional app profile, see list_profiles
+    ///
+    /// Returns:
+    ///     The created view with its view_id
+    async fn create_table_view(
+        &self,
+        app_token: String,
+        table_id: String,
+        view_name: String,
+        view_type: Option<String>,
+        profile: Option<String>,
+    ) -> Result<Json<View>> {
+        let view = async {
+            let mut view = ViewCreate::new(view_name);
+            if let Some(view_type) = view_type {
+                view.view_type = Some(view_type.parse()?);
+            }
+            self.client(profile)?
+                .bitable()
+                .create_view(&app_token, &table_id, view)
+                .await
+        };
+        reply("create_table_view", view.await)
+    }
+
+    /// Rename a view of a Bitable table
+    ///
+    /// Args:
+    ///     app_token: The app token of the Bitable app
+    ///     table_id: The ID of the table
+    ///     view_id: The ID of the view
+    ///     view_name: The new name
+    ///     profile: Optional app profile, see list_profiles
+    ///
+    /// Returns:
+    ///     The renamed view
+    async fn rename_table_view(
+        &self,
+        app_token: String,
+        table_id: String,
+        view_id: String,
+        view_name: String,
+        profile: Option<String>,
+    ) -> Result<Json<View>> {
+        let view = async {
+            self.client(profile)?
+                .bitable()
+                .rename_view(&app_token, &table_id, &view_id, &view_name)
+                .await
+        };
+        reply("rename_table_view", view.await)
+    }
 
-        Json(fields_response.into())
+    /// Delete a view of a Bitable table; its records are kept
+    ///
+    /// Args:
+    ///     app_token: The app token of the Bitable app
+    ///     table_id: The ID of the table
+    ///     view_id: The ID of the view; the last view of a table cannot be deleted
+    ///     profile: Optional app profile, see list_profiles
+    ///
+    /// Returns:
+    ///     Whether the view was deleted
+    async fn delete_table_view(
+        &self,
+        app_token: String,
+        table_id: String,
+        view_id: String,
+        profile: Option<String>,
+    ) -> Result<Json<bool>> {
+        let deleted = async {
+            self.client(profile)?
+                .bitable()
+                .delete_view(&app_token, &table_id, &view_id)
+                .await
+        };
+        reply("delete_table_view", deleted.await.map(|()| true))
     }
 
     /// Get a list of chat groups
     ///
+    /// Args:
+    ///     profile: Optional app profile, see list_profiles
+    ///
     /// Returns:
-    ///     A JSON array of chat groups with chat_id and name
-    async fn chat_group_list(&self) -> Json<Vec<ChatInfoItem>> {
-        Json(
-            ChatClient::new()
-                .get_chat_group_list()
+    ///     A JSON array of chat groups with chat_id and name, following every page up to
+    ///     the first 500 chats
+    async fn chat_group_list(&self, profile: Option<String>) -> Result<Json<Vec<ChatInfoItem>>> {
+        let chats = async {
+            self.client(profile)?
+                .chat()
+                .get_chat_group_list_stream()
+                .with_page_size(100)
+                .take(MAX_LISTED_CHATS)
+                .map_ok(ChatInfoItem::from)
+                .try_collect()
                 .await
-                .unwrap_or_default(),
-        )
+        };
+        reply("chat_group_list", chats.await)
     }
 
     /// Send a text message to a chat
@@ -110,15 +738,23 @@ impl LarkServer {
     /// Parameters:
     ///     chat_id - The ID of the chat to send the message to
     ///     text - The text content of the message
+    ///     profile - Optional app profile, see list_profiles
     ///
     /// Returns:
     ///     A JSON response containing the message_id
-    async fn send_text_message(&self, chat_id: String, text: String) -> Json<()> {
-        let _ = ChatClient::new()
-            .send_text_message(&chat_id, &text)
-            .await
-            .unwrap_or_default();
-        Json(())
+    async fn send_text_message(
+        &self,
+        chat_id: String,
+        text: String,
+        profile: Option<String>,
+    ) -> Result<Json<()>> {
+        let sent = async {
+            self.client(profile)?
+                .chat()
+                .send_text_message(&chat_id, &text)
+                .await
+        };
+        reply("send_text_message", sent.await.map(|_| ()))
     }
 
     /// Send a markdown message to a chat
@@ -127,6 +763,7 @@ impl LarkServer {
     ///     chat_id - The ID of the chat to send the message to
     ///     title - The title of the markdown message
     ///     content - The markdown formatted content as a string
+    ///     profile - Optional app profile, see list_profiles
     ///
     /// Returns:
     ///     A JSON response containing the message_id
@@ -135,12 +772,15 @@ impl LarkServer {
         chat_id: String,
         title: String,
         content: String,
-    ) -> Json<()> {
-        let _ = ChatClient::new()
-            .send_markdown_message(&chat_id, &title, &content)
-            .await
-            .unwrap_or_default();
-        Json(())
+        profile: Option<String>,
+    ) -> Result<Json<()>> {
+        let sent = async {
+            self.client(profile)?
+                .chat()
+                .send_markdown_message(&chat_id, &title, &content)
+                .await
+        };
+        reply("send_markdown_message", sent.await.map(|_| ()))
     }
 
     /// Read wiki content from a Feishu wiki URL
@@ -148,34 +788,24 @@ impl LarkServer {
     /// input feishu wiki url like: https://tparts-global.feishu.cn/wiki/TCm0wUnFwilLH8kiQGlc9Suunfd?fromScene=spaceOverview
     /// Args:
     ///     wiki_url: The full Feishu wiki URL
+    ///     profile: Optional app profile, see list_profiles
     ///
     /// Returns:
     ///     A JSON response containing the wiki content
-    async fn read_wiki_content(&self, wiki_url: String) -> Json<WikiContentResponse> {
+    async fn read_wiki_content(
+        &self,
+        wiki_url: String,
+        profile: Option<String>,
+    ) -> Result<Json<WikiContentResponse>> {
         info!("Processing wiki URL: {}", wiki_url);
-        
-        let wiki_client = WikiClient::new();
-        
-        // Extract node_token from the URL
-        match wiki_client.parse_wiki_url(&wiki_url) {
-            Ok((_, node_token)) => {
-                info!("Extracted node_token: {}", node_token);
-                match wiki_client.get_wiki_content(&node_token).await {
-                    Ok(content) => {
-                        info!("Successfully retrieved wiki content");
-                        Json(content)
-                    }
-                    Err(e) => {
-                        error!("Failed to get wiki content: {}", e);
-                        Json(WikiContentResponse::default())
-                    }
-                }
-            }
-            Err(e) => {
-                error!("Failed to parse wiki URL: {}", e);
-                Json(WikiContentResponse::default())
-            }
-        }
+
+        let content = async {
+            let wiki_client = self.client(profile)?.wiki();
+            let (_, node_token) = wiki_client.parse_wiki_url(&wiki_url)?;
+            info!("Extracted node_token: {}", node_token);
+            wiki_client.get_wiki_content(&node_token).await
+        };
+        reply("read_wiki_content", content.await)
     }
 }
 
@@ -187,5 +817,347 @@ async fn main() -> std::io::Result<()> {
         .init();
     
     info!("Starting Lark MCP Server");
-    stdio(McpServer::new().tools(LarkServer {})).await
+    // One client per profile of FEISHU_PROFILES, or the FEISHU_APP_ID app. Tool calls are
+    // queued locally rather than tripping Lark frequency limits, which are per app.
+    let registry = LarkClientRegistry::from_env(|config| {
+        config.with_rate_limiter(Arc::new(RateLimiter::lark_defaults()))
+    })
+    .map_err(std::io::Error::other)?;
+    info!("Profiles: {}", registry.names().collect::<Vec<_>>().join(", "));
+    let server = LarkServer { registry };
+
+    // HTTP mode serves MCP over SSE at /sse and Prometheus metrics at /metrics
+    match std::env::var("MCP_HTTP_ADDR") {
+        Ok(addr) => {
+            info!("Listening on http://{}", addr);
+            let app = Route::new()
+                .at("/sse", sse_endpoint(move |_| McpServer::new().tools(server.clone())))
+                .at("/metrics", metrics);
+            Server::new(TcpListener::bind(addr)).run(app).await
+        }
+        Err(_) => stdio(McpServer::new().tools(server)).await,
+    }
+}
+
+#[handler]
+fn metrics() -> Response {
+    Response::builder()
+        .content_type("text/plain; version=0.0.4")
+        .body(larkrs_client::metrics::render())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use larkrs_mock::{MockLarkServer, field};
+
+    use larkrs_client::LarkConfig;
+
+    async fn mock_server() -> (MockLarkServer, LarkServer) {
+        let server = MockLarkServer::start().await;
+        let config = LarkConfig::new("id", "secret").with_base_url(server.uri());
+        let client = LarkClient::new(config).unwrap();
+        let registry = LarkClientRegistry::new().with_client("default", client);
+        (server, LarkServer { registry })
+    }
+
+    #[tokio::test]
+    async fn test_table_tools() {
+        let (server, tools) = mock_server().await;
+        server
+            .state()
+            .add_table("app", "tbl", vec![field("股票名称", 1, "Text")]);
+
+        tools
+            .create_table_records_json(
+                "app".to_string(),
+                "tbl".to_string(),
+                r#"[{"股票名称": "太阳电缆"}]"#.to_string(),
+                None,
+            )
+            .await
+            .unwrap();
+
+        let Json(records) = tools
+            .table_records_list("app".to_string(), "tbl".to_string(), None)
+            .await
+            .unwrap();
+        assert_eq!(records.len(), 1);
+
+        let Json(fields) = tools
+            .table_fields_info("app".to_string(), "tbl".to_string(), None)
+            .await
+            .unwrap();
+        assert_eq!(fields[0].field_name, "股票名称");
+    }
+
+    #[tokio::test]
+    async fn test_records_list_follows_pages_up_to_the_cap() {
+        let (server, tools) = mock_server().await;
+        server
+            .state()
+            .add_table("app", "tbl", vec![field("股票名称", 1, "Text")]);
+        let rows = (0..MAX_LISTED_RECORDS + 1).map(|i| serde_json::json!({"股票名称": i.to_string()}));
+        server.state().add_records("app", "tbl", rows.collect());
+
+        let Json(records) = tools
+            .table_records_list("app".to_string(), "tbl".to_string(), None)
+            .await
+            .unwrap();
+        assert_eq!(records.len(), MAX_LISTED_RECORDS);
+    }
+
+    #[tokio::test]
+    async fn test_create_reports_invalid_records() {
+        let (server, tools) = mock_server().await;
+        server
+            .state()
+            .add_table("app", "tbl", vec![field("股票名称", 1, "Text"), field("日期", 5, "DateTime")]);
+
+        let Json(report) = tools
+            .create_table_records_json(
+                "app".to_string(),
+                "tbl".to_string(),
+                r#"[{"股票名称": "太阳电缆", "日期": "昨天"}]"#.to_string(),
+                None,
+            )
+            .await
+            .unwrap();
+        assert_eq!(report.validation.errors[0].field, "日期");
+        assert!(server.state().records("app", "tbl").is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_create_returns_rejected_batch() {
+        let (server, tools) = mock_server().await;
+        server
+            .state()
+            .add_table("app", "tbl", vec![field("股票名称", 1, "Text"), field("负责人", 11, "User")]);
+
+        // A valid id, but not an open id Lark can find
+        let Json(report) = tools
+            .create_table_records_json(
+                "app".to_string(),
+                "tbl".to_string(),
+                r#"[{"股票名称": "太阳电缆", "负责人": "Ann"}]"#.to_string(),
+                None,
+            )
+            .await
+            .unwrap();
+        let err = report.error.unwrap();
+        assert!(err.to_string().contains("1254066"), "{}", err);
+        assert!(report.record_ids.is_empty());
+        assert!(server.state().records("app", "tbl").is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_record_tools() {
+        let (server, tools) = mock_server().await;
+        server
+            .state()
+            .add_table("app", "tbl", vec![field("股票名称", 1, "Text")]);
+        tools
+            .create_table_records_json(
+                "app".to_string(),
+                "tbl".to_string(),
+                r#"[{"股票名称": "太阳电缆"}]"#.to_string(),
+                None,
+            )
+            .await
+            .unwrap();
+        let Json(records) = tools
+            .table_records_list("app".to_string(), "tbl".to_string(), None)
+            .await
+            .unwrap();
+        let record_id = records[0].record_id.clone();
+
+        let Json(updated) = tools
+            .update_table_record(
+                "app".to_string(),
+                "tbl".to_string(),
+                record_id.clone(),
+                r#"{"股票名称": "海洋电缆"}"#.to_string(),
+                None,
+            )
+            .await
+            .unwrap();
+        assert_eq!(updated.fields["股票名称"], "海洋电缆");
+
+        let Json(found) = tools
+            .batch_get_table_records(
+                "app".to_string(),
+                "tbl".to_string(),
+                vec![record_id.clone(), "rec_missing".to_string()],
+                None,
+            )
+            .await
+            .unwrap();
+        assert_eq!(found.records.len(), 1);
+        assert_eq!(found.absent_record_ids, ["rec_missing"]);
+
+        let Json(deleted) = tools
+            .batch_delete_table_records("app".to_string(), "tbl".to_string(), vec![record_id.clone()], None)
+            .await
+            .unwrap();
+        assert!(deleted[0].deleted);
+
+        // Failures reach the model as the error text instead of an empty record
+        let err = tools
+            .get_table_record("app".to_string(), "tbl".to_string(), record_id, None)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("1254043"), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn test_upsert_tool() {
+        let (server, tools) = mock_server().await;
+        server
+            .state()
+            .add_table("app", "tbl", vec![field("股票名称", 1, "Text"), field("梯队", 4, "MultiSelect")]);
+
+        for _ in 0..2 {
+            tools
+                .upsert_table_records_json(
+                    "app".to_string(),
+                    "tbl".to_string(),
+                    vec!["股票名称".to_string()],
+                    r#"[{"股票名称": "太阳电缆", "梯队": ["四板"]}]"#.to_string(),
+                    None,
+                )
+                .await
+                .unwrap();
+        }
+        assert_eq!(server.state().records("app", "tbl").len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_table_management_tools() {
+        let (server, tools) = mock_server().await;
+
+        let Json(created) = tools
+            .create_table(
+                "app".to_string(),
+                "涨停复盘".to_string(),
+                Some(r#"[{"field_name": "股票名称", "type": 1}, {"field_name": "梯队", "type": 4}]"#.to_string()),
+                None,
+            )
+            .await
+            .unwrap();
+        assert_eq!(created.field_id_list.len(), 2);
+        let table_id = created.table_id;
+
+        let Json(field) = tools
+            .create_table_field(
+                "app".to_string(),
+                table_id.clone(),
+                r#"{"field_name": "日期", "type": 5}"#.to_string(),
+                None,
+            )
+            .await
+            .unwrap();
+        let Json(deleted) = tools
+            .delete_table_field("app".to_string(), table_id.clone(), field.field_id, None)
+            .await
+            .unwrap();
+        assert!(deleted.deleted);
+
+        let Json(view) = tools
+            .create_table_view(
+                "app".to_string(),
+                table_id.clone(),
+                "看板".to_string(),
+                Some("kanban".to_string()),
+                None,
+            )
+            .await
+            .unwrap();
+        let Json(views) = tools
+            .list_table_views("app".to_string(), table_id.clone(), None)
+            .await
+            .unwrap();
+        assert_eq!(views.len(), 2);
+        let Json(deleted) = tools
+            .delete_table_view("app".to_string(), table_id.clone(), view.view_id, None)
+            .await
+            .unwrap();
+        assert!(deleted);
+
+        let err = tools
+            .create_table_view(
+                "app".to_string(),
+                table_id.clone(),
+                "看板".to_string(),
+                Some("kanbn".to_string()),
+                None,
+            )
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("grid, kanban, gallery, gantt, form"), "{}", err);
+
+        let Json(renamed) = tools
+            .rename_table("app".to_string(), table_id.clone(), "每日复盘".to_string(), None)
+            .await
+            .unwrap();
+        assert!(renamed);
+        let Json(tables) = tools.list_tables("app".to_string(), None).await.unwrap();
+        assert_eq!(tables[0].name, "每日复盘");
+
+        let Json(deleted) = tools
+            .delete_tables("app".to_string(), vec![table_id.clone()], None)
+            .await
+            .unwrap();
+        assert!(deleted);
+        assert!(server.state().tables("app").is_empty());
+
+        let err = tools
+            .rename_table("app".to_string(), table_id, "周报".to_string(), None)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("1254004"), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn test_chat_list_follows_pages_up_to_the_cap() {
+        let (server, tools) = mock_server().await;
+        for i in 0..=MAX_LISTED_CHATS {
+            server.state().add_chat(&format!("oc_{}", i), "test");
+        }
+
+        let Json(chats) = tools.chat_group_list(None).await.unwrap();
+        assert_eq!(chats.len(), MAX_LISTED_CHATS);
+    }
+
+    #[tokio::test]
+    async fn test_chat_tools() {
+        let (server, tools) = mock_server().await;
+        server.state().add_chat("oc_1", "test");
+
+        let Json(chats) = tools.chat_group_list(None).await.unwrap();
+        assert_eq!(chats[0].chat_id, "oc_1");
+
+        tools
+            .send_text_message("oc_1".to_string(), "hello".to_string(), Some("default".to_string()))
+            .await
+            .unwrap();
+        assert_eq!(server.state().messages().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_unknown_profile() {
+        let (server, tools) = mock_server().await;
+        server.state().add_chat("oc_1", "test");
+
+        let Json(profiles) = tools.list_profiles().await;
+        assert_eq!(profiles, ["default"]);
+
+        let err = tools.chat_group_list(Some("other".to_string())).await.unwrap_err();
+        assert!(err.to_string().contains("unknown profile"), "{}", err);
+        let err = tools
+            .read_wiki_content("https://x.feishu.cn/wiki/abc".to_string(), Some("other".to_string()))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("unknown profile"), "{}", err);
+        assert_eq!(server.state().token_requests(), 0);
+    }
 }